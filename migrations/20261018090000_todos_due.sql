@@ -0,0 +1,2 @@
+-- Optional date (YYYY-MM-DD) when a todo must be finished.
+ALTER TABLE todos ADD COLUMN due TEXT;
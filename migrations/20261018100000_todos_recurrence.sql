@@ -0,0 +1,2 @@
+-- Optional rule for how a todo recurs when done, e.g. weekly:mon,fri.
+ALTER TABLE todos ADD COLUMN recurrence TEXT;
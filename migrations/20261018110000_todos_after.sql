@@ -0,0 +1,2 @@
+-- Optional date (YYYY-MM-DD) before which a todo is deferred.
+ALTER TABLE todos ADD COLUMN after TEXT;
@@ -0,0 +1,13 @@
+-- Audit history of all changes made to todos and contexts.
+CREATE TABLE events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    timestamp INTEGER NOT NULL, -- Unix timestamp in seconds
+    author TEXT,
+    action TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    todo_id INTEGER,
+    data TEXT NOT NULL -- The kind serialized as JSON
+);
+
+CREATE INDEX events_timestamp ON events (timestamp);
+CREATE INDEX events_todo_id ON events (todo_id);
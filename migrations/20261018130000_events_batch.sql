@@ -0,0 +1,8 @@
+-- Events recorded by the same command share a batch, which is the unit
+-- reverted by undo. State is one of: applied, undone or discarded.
+ALTER TABLE events ADD COLUMN batch INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE events ADD COLUMN state TEXT NOT NULL DEFAULT 'applied';
+
+UPDATE events SET batch = id;
+
+CREATE INDEX events_batch ON events (batch);
@@ -0,0 +1,6 @@
+-- Optional ID of the parent todo, making this todo a subtask.
+-- There's no foreign key, since the service detaches subtasks itself
+-- and records it as events, so that removing a parent can be undone.
+ALTER TABLE todos ADD COLUMN parent INTEGER;
+
+CREATE INDEX todos_parent ON todos (parent);
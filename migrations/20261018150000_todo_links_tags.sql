@@ -0,0 +1,63 @@
+-- Tags of a todo, one row per tag.
+CREATE TABLE todo_tags (
+    todo_id INTEGER NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (todo_id, tag),
+    FOREIGN KEY (todo_id) REFERENCES todos(id)
+    ON DELETE CASCADE
+);
+
+CREATE INDEX todo_tags_tag ON todo_tags (tag);
+
+-- Links from one todo to another, where kind is the encoded link name,
+-- e.g. 'blocks' or 'blockedby'.
+CREATE TABLE todo_links (
+    from_id INTEGER NOT NULL,
+    to_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    PRIMARY KEY (from_id, to_id, kind),
+    FOREIGN KEY (from_id) REFERENCES todos(id)
+    ON DELETE CASCADE,
+    FOREIGN KEY (to_id) REFERENCES todos(id)
+    ON DELETE CASCADE
+);
+
+CREATE INDEX todo_links_to_id ON todo_links (to_id);
+
+-- Convert the comma separated tags, e.g. 'feat, test'.
+WITH RECURSIVE split (todo_id, item, rest) AS (
+    SELECT id, '', tags || ',' FROM todos WHERE tags IS NOT NULL
+    UNION ALL
+    SELECT
+        todo_id,
+        trim(substr(rest, 1, instr(rest, ',') - 1)),
+        substr(rest, instr(rest, ',') + 1)
+    FROM split WHERE rest <> ''
+)
+INSERT OR IGNORE INTO todo_tags (todo_id, tag)
+SELECT todo_id, item FROM split WHERE item <> '';
+
+-- Convert the comma separated links, e.g. 'blocks:1,relatesto:2'.
+-- Links to todos that no longer exist are dropped.
+WITH RECURSIVE split (todo_id, item, rest) AS (
+    SELECT id, '', links || ',' FROM todos WHERE links IS NOT NULL
+    UNION ALL
+    SELECT
+        todo_id,
+        trim(substr(rest, 1, instr(rest, ',') - 1)),
+        substr(rest, instr(rest, ',') + 1)
+    FROM split WHERE rest <> ''
+),
+parsed (from_id, kind, to_id) AS (
+    SELECT
+        todo_id,
+        lower(substr(item, 1, instr(item, ':') - 1)),
+        CAST(substr(item, instr(item, ':') + 1) AS INTEGER)
+    FROM split WHERE instr(item, ':') > 0
+)
+INSERT OR IGNORE INTO todo_links (from_id, to_id, kind)
+SELECT from_id, to_id, kind FROM parsed
+WHERE kind <> '' AND to_id IN (SELECT id FROM todos);
+
+ALTER TABLE todos DROP COLUMN tags;
+ALTER TABLE todos DROP COLUMN links;
@@ -0,0 +1,4 @@
+-- Supports the filters evaluated when listing todos.
+CREATE INDEX todos_status ON todos (status);
+CREATE INDEX todos_context ON todos (context);
+CREATE INDEX todos_due ON todos (due);
@@ -0,0 +1,29 @@
+-- Full-text index over the subject and description of todos,
+-- using the todos table as external content.
+CREATE VIRTUAL TABLE todos_fts USING fts5 (
+    subject,
+    description,
+    content = 'todos',
+    content_rowid = 'id'
+);
+
+-- Keep the index in sync with the todos table.
+CREATE TRIGGER todos_fts_insert AFTER INSERT ON todos BEGIN
+    INSERT INTO todos_fts (rowid, subject, description)
+    VALUES (new.id, new.subject, new.description);
+END;
+
+CREATE TRIGGER todos_fts_delete AFTER DELETE ON todos BEGIN
+    INSERT INTO todos_fts (todos_fts, rowid, subject, description)
+    VALUES ('delete', old.id, old.subject, old.description);
+END;
+
+CREATE TRIGGER todos_fts_update AFTER UPDATE OF subject, description ON todos BEGIN
+    INSERT INTO todos_fts (todos_fts, rowid, subject, description)
+    VALUES ('delete', old.id, old.subject, old.description);
+    INSERT INTO todos_fts (rowid, subject, description)
+    VALUES (new.id, new.subject, new.description);
+END;
+
+-- Index existing todos.
+INSERT INTO todos_fts (todos_fts) VALUES ('rebuild');
@@ -0,0 +1,6 @@
+-- Named filter expressions for listing todos.
+CREATE TABLE views (
+    name TEXT PRIMARY KEY,
+    filter TEXT NOT NULL,
+    layout TEXT NOT NULL
+);
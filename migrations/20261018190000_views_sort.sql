@@ -0,0 +1,2 @@
+-- Sort order of a view, e.g. 'prio,-created'. Null uses the default order.
+ALTER TABLE views ADD COLUMN sort TEXT;
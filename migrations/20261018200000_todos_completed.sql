@@ -0,0 +1,2 @@
+-- Optional time when the todo was done, set when its status becomes done.
+ALTER TABLE todos ADD COLUMN completed TEXT;
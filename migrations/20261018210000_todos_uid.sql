@@ -0,0 +1,3 @@
+-- Optional UID of the todo in another tool, kept from imports so that
+-- the todo is matched and exported with the same UID.
+ALTER TABLE todos ADD COLUMN uid TEXT;
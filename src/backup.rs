@@ -0,0 +1,305 @@
+use crate::err;
+use crate::error::Result;
+use chrono::Local;
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use sqlx::Row;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory next to the database with its backups.
+pub const BACKUPS_DIR: &str = "backups";
+
+// The migrations of this version, which a backup must not be ahead of.
+static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Writes a consistent snapshot of the database to `path`, which must not
+/// exist. Uses `VACUUM INTO`, so it is safe while the database is in use.
+pub async fn write(pool: &SqlitePool, path: &Path) -> Result<()> {
+    if path.exists() {
+        return err!("backup file already exists: {}", path.display());
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    // VACUUM doesn't take parameters
+    let sql = format!(
+        "VACUUM INTO '{}'",
+        path.to_string_lossy().replace('\'', "''")
+    );
+    sqlx::query(&sql).execute(pool).await?;
+    log::info!("Backed up database to {:?}", path);
+    Ok(())
+}
+
+/// Returns the path of a new backup of `database` in the backups directory.
+pub fn path(database: &Path) -> PathBuf {
+    backups_dir(database).join(format!("{}-{}.db", stem(database), timestamp()))
+}
+
+/// Returns the path of a new automatic backup of `database`, made before
+/// the command given by `label`.
+pub fn auto_path(database: &Path, label: &str) -> PathBuf {
+    backups_dir(database).join(format!(
+        "{}-auto-{}-{}.db",
+        stem(database),
+        timestamp(),
+        label
+    ))
+}
+
+/// Removes the oldest automatic backups of `database`, keeping `keep`.
+pub fn rotate(database: &Path, keep: usize) -> Result<()> {
+    let dir = backups_dir(database);
+    let prefix = format!("{}-auto-", stem(database));
+    let mut backups: Vec<PathBuf> = fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".db"))
+        })
+        .collect();
+    // Names start with the time they were made
+    backups.sort();
+    let remove = backups.len().saturating_sub(keep);
+    for path in backups.into_iter().take(remove) {
+        fs::remove_file(&path)?;
+        log::info!("Removed old backup {:?}", path);
+    }
+    Ok(())
+}
+
+/// Checks that `path` is an intact backup of a todo database with
+/// migrations known to this version, returning the latest migration.
+pub async fn check(path: &Path) -> Result<i64> {
+    if !path.is_file() {
+        return err!("no such backup file: {}", path.display());
+    }
+    let pool = open(path).await?;
+    let result = check_pool(&pool).await;
+    pool.close().await;
+    match result {
+        Ok(version) => Ok(version),
+        Err(e) => err!("invalid backup {}: {}", path.display(), e),
+    }
+}
+
+/// Replaces `database` with the backup at `path`, which should be checked
+/// first. The database must be closed, as its journal files are removed.
+pub async fn restore(path: &Path, database: &Path) -> Result<()> {
+    // The backup is copied next to the database first, so that the
+    // database is replaced in one step.
+    let tmp = with_suffix(database, ".restore");
+    if tmp.exists() {
+        fs::remove_file(&tmp)?;
+    }
+    let pool = open(path).await?;
+    let result = write(&pool, &tmp).await;
+    pool.close().await;
+    result?;
+
+    fs::rename(&tmp, database)?;
+    for suffix in ["-wal", "-shm"] {
+        let journal = with_suffix(database, suffix);
+        if journal.exists() {
+            fs::remove_file(journal)?;
+        }
+    }
+    log::info!("Restored database {:?} from {:?}", database, path);
+    Ok(())
+}
+
+async fn open(path: &Path) -> Result<SqlitePool> {
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(false)
+        .read_only(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await?;
+    Ok(pool)
+}
+
+async fn check_pool(pool: &SqlitePool) -> Result<i64> {
+    let integrity: String = sqlx::query("PRAGMA integrity_check")
+        .fetch_one(pool)
+        .await?
+        .get(0);
+    if integrity != "ok" {
+        return err!("integrity check failed: {}", integrity);
+    }
+
+    let rows = match sqlx::query("SELECT version, success, checksum FROM _sqlx_migrations")
+        .fetch_all(pool)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(_) => return err!("not a todo database"),
+    };
+    let mut latest = None;
+    for row in rows {
+        let version: i64 = row.get("version");
+        let success: bool = row.get("success");
+        let checksum: Vec<u8> = row.get("checksum");
+        match MIGRATOR.iter().find(|m| m.version == version) {
+            Some(_) if !success => return err!("migration {} failed", version),
+            Some(m) if *m.checksum != checksum[..] => {
+                return err!("migration {} differs from this version of todo", version)
+            }
+            Some(_) => latest = latest.max(Some(version)),
+            None => {
+                return err!(
+                    "migration {} is unknown, upgrade todo to restore it",
+                    version
+                )
+            }
+        }
+    }
+    match latest {
+        Some(version) => Ok(version),
+        None => err!("not a todo database"),
+    }
+}
+
+fn backups_dir(database: &Path) -> PathBuf {
+    match database.parent() {
+        Some(dir) => dir.join(BACKUPS_DIR),
+        None => PathBuf::from(BACKUPS_DIR),
+    }
+}
+
+fn stem(database: &Path) -> String {
+    database
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| "todo".to_string())
+}
+
+// Appends to the file name, as SQLite does for the names of journals.
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn timestamp() -> String {
+    Local::now().format("%Y%m%d-%H%M%S-%3f").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Backups are written next to the database, so it's a file in `dir`
+    // rather than in memory.
+    async fn setup(dir: &Path) -> SqlitePool {
+        let options = SqliteConnectOptions::new()
+            .filename(dir.join("live.db"))
+            .create_if_missing(true)
+            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        pool
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("todo-{}-{}", name, rand::random::<u32>()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    async fn count_contexts(pool: &SqlitePool) -> i64 {
+        sqlx::query("SELECT COUNT(*) FROM contexts")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+            .get(0)
+    }
+
+    #[tokio::test]
+    async fn write_check_and_restore() {
+        let dir = temp_dir("backup");
+        let pool = setup(&dir).await;
+        let path = dir.join("todo.db");
+        sqlx::query("INSERT INTO contexts (name) VALUES ('work')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        write(&pool, &path).await.unwrap();
+        let latest = MIGRATOR.iter().map(|m| m.version).max();
+        assert_eq!(Some(check(&path).await.unwrap()), latest);
+        assert!(write(&pool, &path).await.is_err());
+
+        sqlx::query("INSERT INTO contexts (name) VALUES ('home')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count_contexts(&pool).await, 2);
+        pool.close().await;
+
+        restore(&path, &dir.join("live.db")).await.unwrap();
+        let pool = setup(&dir).await;
+        assert_eq!(count_contexts(&pool).await, 1);
+        pool.close().await;
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn check_unknown_migration() {
+        let dir = temp_dir("future");
+        let pool = setup(&dir).await;
+        sqlx::query(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+            VALUES (99991231000000, 'future', TRUE, x'00', 0)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let path = dir.join("todo.db");
+        write(&pool, &path).await.unwrap();
+        pool.close().await;
+
+        let err = check(&path).await.unwrap_err().to_string();
+        assert!(err.contains("99991231000000 is unknown"), "{}", err);
+        fs::write(dir.join("notes.txt"), "not a database").unwrap();
+        assert!(check(&dir.join("notes.txt")).await.is_err());
+        assert!(check(&dir.join("missing.db")).await.is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn rotate_auto_backups() {
+        let dir = temp_dir("rotate");
+        let database = dir.join("todo.db");
+        let names = [
+            "todo-auto-20261016-090000-000-prune.db",
+            "todo-auto-20261017-090000-000-context.db",
+            "todo-auto-20261018-090000-000-prune.db",
+            "todo-20261015-090000-000.db",
+            "work-auto-20261015-090000-000-prune.db",
+        ];
+        fs::create_dir_all(dir.join(BACKUPS_DIR)).unwrap();
+        for name in names {
+            fs::write(dir.join(BACKUPS_DIR).join(name), "").unwrap();
+        }
+
+        rotate(&database, 2).unwrap();
+        let mut left: Vec<String> = fs::read_dir(dir.join(BACKUPS_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec![names[3], names[1], names[2], names[4]]);
+        assert!(auto_path(&database, "prune")
+            .to_string_lossy()
+            .ends_with("-prune.db"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,24 +1,56 @@
+use clap::ArgMatches;
 use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
 use std::path::PathBuf;
-use std::str::FromStr;
+use std::process;
 use todo::cli::Cli;
+use todo::config::{self, Config};
 use todo::err;
-use todo::error::Result;
+use todo::error::{Error, Result};
 use todo::repository::Repository;
 use todo::service::Service;
 use todo::style::{Color, Styler};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let matches = Cli::parse();
+    let mut cli = match setup(&matches).await {
+        Ok(cli) => cli,
+        Err(err) => {
+            print_error(&err);
+            process::exit(1);
+        }
+    };
+
+    // Execute CLI command
+    // Failed commands, e.g. doctor finding problems, exit non-zero
+    if let Err(err) = cli.exec(&matches).await {
+        print_error(&err);
+        process::exit(1);
+    }
+    Ok(())
+}
+
+fn print_error(err: &Error) {
+    let red = Styler::default().bold(true).fg(Color::Red);
+    eprintln!("{}: {}", red.style("error"), err);
+}
+
+// Opens the database given by the arguments and loads the configuration.
+async fn setup(matches: &ArgMatches) -> Result<Cli> {
     // Init configuration
     let root = init()?;
-    let database_uri = match root.to_str() {
-        Some(root) => format!("{root}/todo.db"),
-        None => return err!("invalid root path: {:?}", root),
-    };
+    let database = config::database_path(
+        &root,
+        matches.get_one::<String>("db").map(|s| s.as_str()),
+        matches.get_one::<String>("profile").map(|s| s.as_str()),
+    )?;
+    if let Some(dir) = database.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
 
     // Setup database connection and run migrations
-    let connection_options = SqliteConnectOptions::from_str(&database_uri)?
+    let connection_options = SqliteConnectOptions::new()
+        .filename(&database)
         .create_if_missing(true)
         .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
         .read_only(false);
@@ -33,16 +65,21 @@ async fn main() -> Result<()> {
         .await
         .expect("failed to run migrations");
 
+    // Settings aren't checked for the config command, so that invalid
+    // ones can be fixed using it. The defaults are used for those.
+    let config = match matches.subcommand_name() {
+        Some("config") => Config::read(&root)?,
+        _ => Config::load(&root)?,
+    };
+    config.theme().unwrap_or_default().apply();
     let repository = Repository::new(pool);
-    let service = Service::new(repository);
+    let service = Service::new(repository)
+        .with_link_types(config.link_types().unwrap_or_default())
+        .with_sort(config.sort().unwrap_or_default());
 
-    // Execute CLI command
-    let cli = Cli::new(root, service);
-    if let Err(err) = cli.exec().await {
-        let red = Styler::default().bold(true).fg(Color::Red);
-        eprintln!("{}: {}", red.style("error"), err);
-    }
-    Ok(())
+    Ok(Cli::new(root, service)
+        .with_config(config)
+        .with_database(database))
 }
 
 // Creates the directory: ~/.config/todo/
@@ -58,7 +95,7 @@ fn init() -> Result<PathBuf> {
 
     if !root.exists() {
         std::fs::create_dir_all(&root)?;
-        return err!("initialized new root at {:?}", root);
+        log::info!("Initialized new root at {:?}", root);
     }
     Ok(root)
 }
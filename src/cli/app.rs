@@ -1,8 +1,76 @@
-use crate::model::Prio;
-use clap::{builder::PossibleValuesParser, command, Arg, Command};
+use crate::config;
+use crate::format::Output;
+use crate::interop::Format;
+use crate::model::{Layout, Prio};
+use clap::builder::{PossibleValue, PossibleValuesParser};
+use clap::{command, Arg, Command};
 
 const STATUSES: [&str; 5] = ["any", "new", "started", "done", "blocked"];
 
+const RECUR_HELP: &str = "A rule for when the todo recurs once done, like so:
+    daily
+    weekly            same weekday as the due date
+    weekly:mon,fri    on the given weekdays
+    monthly:15        on the given day of month
+    every:3           3 days after completion
+
+When a recurring todo is done a new todo is added with the next due date
+after today, skipping any dates that were missed.";
+
+const DATE_HELP: &str = "A date given as YYYY-MM-DD, today, tomorrow, a weekday (e.g. fri)
+or an offset from today in days or weeks (e.g. 3d or 2w).";
+
+const SEARCH_HELP: &str = "Search todos by the text of their subject and description, with the
+best matches first. Like list, only todos with status != done and in
+the current context (if set) are searched by default.
+
+The query supports:
+    deploy server     todos containing both words
+    \"release notes\"   todos containing the phrase
+    dep*              words starting with dep
+    fix OR bug        todos containing either word
+    NOT urgent        todos not containing the word
+    subject:deploy    only search the subject";
+
+const SORT_HELP: &str = "Sort by comma separated keys, where later keys break ties and a
+leading - reverses the order of a key, e.g. 'prio,-created'. Keys:
+    prio       highest priority first
+    status     started, new, blocked and then done
+    created    oldest first
+    due        earliest first, todos without a due date last
+    id         lowest first
+    context    by name, todos without a context last
+    subject    alphabetically, ignoring case
+
+Defaults to the 'sort' setting in config.toml, if set, or else todos
+that are done last, overdue first and then by priority.";
+
+const CONFIG_HELP: &str = "Show or change settings in config.toml in ~/.config/todo/, or the file
+given by TODO_CONFIG. Without a sub-command all settings are listed.
+The settings are:
+    prio       priority of new todos, defaults to normal
+    filter     filter expression used by list unless given, e.g. 'not tag:someday'
+    sort       order of listed todos, e.g. 'prio,-created'
+    editor     command used to edit todos, defaults to $EDITOR, $VISUAL or nano
+    theme      styling of output: default, mono (no colors) or plain
+    columns    columns of tables of todos, e.g. 'id,prio,subject'
+    prompts    false to never prompt for values not given as options
+    backups    automatic backups kept per database, 0 to turn them off, defaults to 5
+
+Link types are declared as [[link]] tables, see 'set --help'.";
+
+const WHERE_HELP: &str = "A filter expression combining conditions on fields with 'and', 'or',
+'not' and parentheses, e.g.
+    prio>=high and (tag:backend or tag:api) and not status:blocked
+
+Conditions next to each other must both match, i.e. 'and' is optional.
+
+Fields are id, status, prio, tag, context, subject, created, due and after.
+Conditions use ':' (or '='), '!=' and for id, prio and dates also '<',
+'<=', '>' and '>='. Dates are given as for --due, and 'none' matches
+todos without a tag, context, due or after date. Conditions on status
+and context replace the default filter on those.";
+
 pub fn build_app() -> Command<'static> {
     command!()
         .about("CLI tool for managing tasks.")
@@ -21,17 +89,58 @@ default behaviour of the list command.",
                 ]))
                 .global(true),
         )
+        .arg(
+            Arg::new("db")
+                .long("db")
+                .help("Path of the database to use instead of the default, also set by TODO_DB.")
+                .takes_value(true)
+                .value_name("PATH")
+                .global(true),
+        )
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Use the separate database of a profile, e.g. work.")
+                .takes_value(true)
+                .value_name("NAME")
+                .conflicts_with("db")
+                .global(true),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .help("Output format, where json, ndjson and csv are meant for scripts.")
+                .takes_value(true)
+                .value_parser(PossibleValuesParser::new(Output::values()))
+                .default_value("table")
+                .global(true),
+        )
         .subcommand(show())
         .subcommand(list())
+        .subcommand(search())
+        .subcommand(next())
         .subcommand(add())
         .subcommand(done())
         .subcommand(start())
         .subcommand(set())
         .subcommand(edit())
+        .subcommand(snooze())
         .subcommand(remove())
         .subcommand(context())
+        .subcommand(view())
+        .subcommand(config())
         .subcommand(starship())
         .subcommand(prune())
+        .subcommand(log())
+        .subcommand(undo())
+        .subcommand(redo())
+        .subcommand(doctor())
+        .subcommand(graph())
+        .subcommand(impact())
+        .subcommand(export())
+        .subcommand(import())
+        .subcommand(backup())
+        .subcommand(restore())
 }
 
 fn show() -> Command<'static> {
@@ -54,42 +163,149 @@ fn list() -> Command<'static> {
 and in the current context (if set). To get more details about
 a todo use the sub-command 'show'.",
         )
+        .arg(all_arg("List all todos."))
+        .args(filter_args())
+        .arg(sort_arg())
+        .arg(view_arg("List todos using a saved view, e.g. @triage."))
         .arg(
-            Arg::new("all")
-                .long("all")
-                .short('a')
-                .exclusive(true)
-                .help("List all todos.")
-                .required(false),
+            Arg::new("tree")
+                .long("tree")
+                .short('t')
+                .conflicts_with("details")
+                .help("List subtasks indented under their parent."),
         )
         .arg(
-            Arg::new("status")
-                .long("status")
-                .short('s')
-                .help("Filter on status.")
+            Arg::new("details")
+                .visible_alias("detailed")
+                .visible_alias("show")
+                .long("details")
+                .short('D')
+                .help("Show detailed output format."),
+        )
+}
+
+// Arguments for filtering todos, shared by list and search.
+fn filter_args() -> Vec<Arg<'static>> {
+    vec![
+        where_arg(),
+        Arg::new("status")
+            .long("status")
+            .short('s')
+            .help("Filter on status.")
+            .takes_value(true)
+            .value_parser(PossibleValuesParser::new(STATUSES)),
+        Arg::new("context")
+            .long("context")
+            .help("Filter on context. Defaults to only those in current context.")
+            .takes_value(true),
+        Arg::new("tags")
+            .long("tags")
+            .multiple_values(true)
+            .takes_value(true)
+            .help("Filter on tags. Any matching tag is considered a match."),
+        Arg::new("due")
+            .long("due")
+            .takes_value(true)
+            .value_name("WHEN")
+            .help("Filter on due date: overdue, today or a number of days.")
+            .long_help(
+                "Filter on due date. Valid values are:
+    overdue   todos with a due date that has passed
+    today     todos due today, including overdue
+    N         todos due within N days, including overdue",
+            ),
+        Arg::new("include-deferred")
+            .long("include-deferred")
+            .help("Include todos that are deferred to a later date, e.g. using snooze."),
+    ]
+}
+
+// Selects all todos, ignoring filters and views.
+fn all_arg(help: &'static str) -> Arg<'static> {
+    Arg::new("all")
+        .long("all")
+        .short('a')
+        .conflicts_with_all(&[
+            "where",
+            "status",
+            "context",
+            "tags",
+            "due",
+            "include-deferred",
+            "view",
+        ])
+        .help(help)
+        .required(false)
+}
+
+fn view_arg(help: &'static str) -> Arg<'static> {
+    Arg::new("view")
+        .takes_value(true)
+        .value_name("@VIEW")
+        .help(help)
+}
+
+fn sort_arg() -> Arg<'static> {
+    Arg::new("sort")
+        .long("sort")
+        .takes_value(true)
+        .value_name("KEYS")
+        .allow_hyphen_values(true)
+        .help("Sort by comma separated keys, e.g. 'prio,-created'.")
+        .long_help(SORT_HELP)
+}
+
+fn where_arg() -> Arg<'static> {
+    Arg::new("where")
+        .long("where")
+        .short('w')
+        .takes_value(true)
+        .value_name("EXPR")
+        .help("Filter using an expression, e.g. 'prio>=high and tag:api'.")
+        .long_help(WHERE_HELP)
+}
+
+fn search() -> Command<'static> {
+    Command::new("search")
+        .about("Search todos by the text of their subject and description.")
+        .long_about(SEARCH_HELP)
+        .arg(
+            Arg::new("query")
+                .help("The search query.")
                 .takes_value(true)
-                .value_parser(PossibleValuesParser::new(STATUSES)),
+                .multiple_values(true)
+                .required(true),
         )
+        .args(filter_args())
         .arg(
-            Arg::new("context")
-                .long("context")
-                .help("Filter on context. Defaults to only those in current context.")
-                .takes_value(true),
+            Arg::new("details")
+                .long("details")
+                .short('D')
+                .help("Show detailed output format."),
+        )
+}
+
+fn next() -> Command<'static> {
+    Command::new("next")
+        .about("List todos that can be started right now.")
+        .long_about(
+            "List todos that can be started right now, most important first.
+That is todos that aren't done, blocked by an open todo or deferred,
+that has no open subtasks and that are in the current context (if set).",
         )
         .arg(
-            Arg::new("tags")
-                .long("tags")
-                .multiple_values(true)
+            Arg::new("limit")
+                .short('n')
+                .long("limit")
                 .takes_value(true)
-                .help("Filter on tags. Any matching tag is considered a match."),
+                .value_parser(clap::value_parser!(usize))
+                .help("List at most this many todos."),
         )
         .arg(
             Arg::new("details")
-                .visible_alias("detailed")
-                .visible_alias("show")
                 .long("details")
                 .short('D')
-                .help("Show detailed output format."),
+                .help("Also show why the other todos that aren't done are excluded."),
         )
 }
 
@@ -125,10 +341,31 @@ pub fn add() -> Command<'static> {
             Arg::new("prio")
                 .long("prio")
                 .takes_value(true)
-                .value_parser(PossibleValuesParser::new([
-                    "low", "normal", "high", "cirital",
-                ]))
-                .help("Sets another priority than the default: normal."),
+                .value_parser(PossibleValuesParser::new(Prio::values()))
+                .help("Sets another priority than the default, see the 'prio' setting."),
+        )
+        .arg(
+            Arg::new("due")
+                .long("due")
+                .takes_value(true)
+                .value_name("DATE")
+                .help("Sets a due date of the todo.")
+                .long_help(DATE_HELP),
+        )
+        .arg(
+            Arg::new("recur")
+                .long("recur")
+                .takes_value(true)
+                .value_name("RULE")
+                .help("Makes the todo recurring, e.g. daily or weekly:mon,fri.")
+                .long_help(RECUR_HELP),
+        )
+        .arg(
+            Arg::new("parent")
+                .long("parent")
+                .takes_value(true)
+                .value_name("ID")
+                .help("Adds the todo as a subtask of the todo with this ID."),
         )
 }
 
@@ -180,6 +417,29 @@ pub fn set() -> Command<'static> {
                 .takes_value(true)
                 .help("Sets context of the todo. Use empty string to unset the context."),
         )
+        .arg(
+            Arg::new("due")
+                .long("due")
+                .takes_value(true)
+                .value_name("DATE")
+                .help("Sets due date of the todo. Use empty string to unset the due date.")
+                .long_help(DATE_HELP),
+        )
+        .arg(
+            Arg::new("recur")
+                .long("recur")
+                .takes_value(true)
+                .value_name("RULE")
+                .help("Sets recurrence of the todo. Use empty string to unset it.")
+                .long_help(RECUR_HELP),
+        )
+        .arg(
+            Arg::new("parent")
+                .long("parent")
+                .takes_value(true)
+                .value_name("ID")
+                .help("Makes the todo a subtask of the todo with this ID. Use empty string to unset it."),
+        )
         .arg(
             Arg::new("link")
                 .long("link")
@@ -189,8 +449,17 @@ pub fn set() -> Command<'static> {
     blocks:id
     blocked-by:id
     relates-to:id
+    duplicates:id
+    duplicated-by:id
+    follows:id
+    precedes:id
 
 Some links are bi-directional: `a blocks b` implices `b blocked by a`.
+
+Other link types can be declared in config.toml, like so:
+    [[link]]
+    name = \"implements\"
+    inverse = \"implemented-by\"  # optional, makes it bi-directional
 ",
                 )
                 .takes_value(true)
@@ -240,6 +509,17 @@ To update other fields use the 'update' command.",
 Only valid IDs of type unsigned integers will be considered.",
                 ),
         )
+        .arg(where_arg().conflicts_with("ids"))
+        .arg(
+            Arg::new("cascade")
+                .long("cascade")
+                .takes_value(false)
+                .help("Set all open subtasks as done as well.")
+                .long_help(
+                    "Set all open subtasks as done as well.
+Without this a todo with open subtasks cannot be set as done.",
+                ),
+        )
 }
 
 fn start() -> Command<'static> {
@@ -255,6 +535,31 @@ To update other fields use the 'update' command.",
                 .takes_value(true)
                 .help("IDs of the todos to remove. Only valid IDs will be considered."),
         )
+        .arg(where_arg().conflicts_with("ids"))
+}
+
+fn snooze() -> Command<'static> {
+    Command::new("snooze")
+        .about("Defers one or more todos until a later date.")
+        .long_about(
+            "Defers one or more todos until a later date. Deferred todos are
+hidden from the default listing until that date. Use 'none' to
+stop deferring the todos.",
+        )
+        .arg(
+            Arg::new("ids")
+                .multiple_values(true)
+                .takes_value(true)
+                .required(true)
+                .help("IDs of the todos to defer."),
+        )
+        .arg(
+            Arg::new("when")
+                .takes_value(true)
+                .required(true)
+                .help("The date to defer the todos until.")
+                .long_help(DATE_HELP),
+        )
 }
 
 fn remove() -> Command<'static> {
@@ -267,6 +572,13 @@ fn remove() -> Command<'static> {
                 .takes_value(true)
                 .help("IDs of the todos to remove. Only valid IDs will be considered."),
         )
+        .arg(where_arg().conflicts_with("ids"))
+        .arg(
+            Arg::new("cascade")
+                .long("cascade")
+                .takes_value(false)
+                .help("Remove all subtasks as well. Else they are kept without a parent."),
+        )
         .arg(
             Arg::new("yes")
                 .long("yes")
@@ -276,6 +588,65 @@ fn remove() -> Command<'static> {
         )
 }
 
+fn view() -> Command<'static> {
+    Command::new("view")
+        .about("Run or manage saved views, i.e. named filters.")
+        .long_about(
+            "Run or manage saved views, i.e. filter expressions saved under a name.
+A view is run using 'view NAME' or 'list @NAME', where other filter
+options narrow it down further. A view named 'default' replaces the
+default listing. Without arguments the saved views are listed.",
+        )
+        .args_conflicts_with_subcommands(true)
+        .arg(
+            Arg::new("name")
+                .takes_value(true)
+                .help("Name of the view to run."),
+        )
+        .subcommand(
+            Command::new("save")
+                .about("Save a view, replacing any view with the same name.")
+                .arg(
+                    Arg::new("name")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of the view."),
+                )
+                .arg(
+                    Arg::new("filter")
+                        .takes_value(true)
+                        .required(true)
+                        .value_name("EXPR")
+                        .help("Filter expression, e.g. 'prio>=high status:new'.")
+                        .long_help(WHERE_HELP),
+                )
+                .arg(
+                    Arg::new("layout")
+                        .long("layout")
+                        .takes_value(true)
+                        .value_parser(PossibleValuesParser::new(Layout::values()))
+                        .help("How to show the todos, defaults to table."),
+                )
+                .arg(sort_arg()),
+        )
+        .subcommand(
+            Command::new("ls")
+                .visible_alias("list")
+                .about("List saved views."),
+        )
+        .subcommand(
+            Command::new("rm")
+                .visible_alias("remove")
+                .about("Remove a saved view.")
+                .arg(
+                    Arg::new("name")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of the view."),
+                ),
+        )
+}
+
 fn context() -> Command<'static> {
     Command::new("context")
         .visible_alias("cx")
@@ -303,7 +674,7 @@ via the 'context add' sub-command.",
                 .short('l')
                 .help("List available contexts.")
                 .takes_value(false)
-                .exclusive(true),
+                .conflicts_with_all(&["add", "set", "unset"]),
         )
         .arg(
             Arg::new("remove")
@@ -347,10 +718,264 @@ fn starship() -> Command<'static> {
 }
 
 fn prune() -> Command<'static> {
-    Command::new("prune").about("Prune todos.").arg(
-        Arg::new("done")
-            .help("Prune all todos with status 'done'.")
-            .long("done")
-            .takes_value(false),
-    )
+    Command::new("prune")
+        .about("Prune todos.")
+        .arg(
+            Arg::new("done")
+                .help("Prune all todos with status 'done'.")
+                .long("done")
+                .takes_value(false),
+        )
+        .arg(where_arg().help("Prune all todos matching an expression, e.g. 'created<2026-01-01'."))
+}
+
+fn log() -> Command<'static> {
+    Command::new("log")
+        .about("Show history of changes made to todos and contexts.")
+        .arg(
+            Arg::new("id")
+                .long("id")
+                .takes_value(true)
+                .help("Only show changes made to the todo with this ID."),
+        )
+        .arg(
+            Arg::new("since")
+                .long("since")
+                .takes_value(true)
+                .value_name("DATE")
+                .help("Only show changes made since this date, e.g. -7d or 2022-03-02.")
+                .long_help(DATE_HELP),
+        )
+}
+
+fn undo() -> Command<'static> {
+    Command::new("undo")
+        .about("Undo the changes made by the last command.")
+        .long_about(
+            "Undo the changes made by the last command that changed any todos or contexts.
+Can be used repeatedly to undo earlier commands as well.",
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .help("Do not confirm.")
+                .required(false),
+        )
+}
+
+fn redo() -> Command<'static> {
+    Command::new("redo")
+        .about("Redo the changes that was last undone.")
+        .long_about(
+            "Redo the changes that was last undone. Undone changes can no longer
+be redone after any other change has been made.",
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .help("Do not confirm.")
+                .required(false),
+        )
+}
+
+fn config() -> Command<'static> {
+    Command::new("config")
+        .about("Show or change settings in the configuration file.")
+        .long_about(CONFIG_HELP)
+        .subcommand(
+            Command::new("list")
+                .visible_alias("ls")
+                .about("List all settings."),
+        )
+        .subcommand(
+            Command::new("get").about("Show a setting.").arg(
+                Arg::new("key")
+                    .takes_value(true)
+                    .required(true)
+                    .value_parser(PossibleValuesParser::new(config::KEYS))
+                    .help("Key of the setting."),
+            ),
+        )
+        .subcommand(
+            Command::new("set")
+                .about("Change a setting, where an empty value restores the default.")
+                .arg(
+                    Arg::new("key")
+                        .takes_value(true)
+                        .required(true)
+                        .value_parser(PossibleValuesParser::new(config::KEYS))
+                        .help("Key of the setting."),
+                )
+                .arg(
+                    Arg::new("value")
+                        .takes_value(true)
+                        .required(true)
+                        .allow_hyphen_values(true)
+                        .help("New value of the setting."),
+                ),
+        )
+}
+
+fn doctor() -> Command<'static> {
+    Command::new("doctor")
+        .about("Check the todos for problems.")
+        .long_about(
+            "Check the todos for problems, such as cycles of blocking links
+where each todo in the cycle is blocked by another forever.
+Exits with an error if any problems are found.",
+        )
+}
+
+fn graph() -> Command<'static> {
+    Command::new("graph")
+        .about("Output the graph of linked todos, e.g. for rendering with Graphviz.")
+        .long_about(
+            "Output the graph of todos and the links between them. Nodes are
+filled with the color of the status and outlined with the color of
+the priority. Defaults to todos in the current context (if set).
+
+Example: todo graph | dot -Tsvg > todos.svg",
+        )
+        .arg(
+            Arg::new("context")
+                .long("context")
+                .takes_value(true)
+                .help("Only include todos in this context."),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .short('f')
+                .takes_value(true)
+                .default_value("dot")
+                .value_parser(PossibleValuesParser::new(["dot", "mermaid"]))
+                .help("The output format."),
+        )
+        .arg(
+            Arg::new("all")
+                .long("all")
+                .short('a')
+                .help("Include todos that are done."),
+        )
+}
+
+fn impact() -> Command<'static> {
+    Command::new("impact")
+        .about("Show which todos unblock the most work.")
+        .long_about(
+            "Show the impact of open todos in the current context (if set) through
+blocking links, ordered by the number of todos they block:
+    Blocks    number of todos blocked, directly or transitively
+    Chain     number of todos in the longest chain of blocking links
+              the todo is part of
+    Critical  marks todos on the critical path, i.e. the longest chain",
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Output as JSON, the same as --output json."),
+        )
+}
+
+fn format_arg(values: &[&'static str]) -> Arg<'static> {
+    Arg::new("format")
+        .long("format")
+        .short('f')
+        .takes_value(true)
+        .required(true)
+        .value_parser(PossibleValuesParser::new(values.iter().map(
+            |value| match *value {
+                "todotxt" => PossibleValue::new(value).alias("todo.txt"),
+                value => PossibleValue::new(value),
+            },
+        )))
+        .help("The format of the other tool.")
+}
+
+fn export() -> Command<'static> {
+    Command::new("export")
+        .about("Export todos in the format of another tool.")
+        .long_about(
+            "Export todos in the format of another tool to stdout. Fields the
+format has no syntax for are kept as key:value pairs, so that todos can
+be imported again without loss. The markdown format is a report grouped
+by context and status, which can't be imported.
+
+The todos are selected the same way as by 'list', use --all to export
+every todo.
+
+Example: todo export --format todotxt --all > todo.txt
+         todo export --format markdown --context work --status any",
+        )
+        .arg(format_arg(&Format::values()))
+        .arg(all_arg("Export all todos."))
+        .args(filter_args())
+        .arg(sort_arg())
+        .arg(view_arg("Export todos using a saved view, e.g. @triage."))
+}
+
+fn import() -> Command<'static> {
+    Command::new("import")
+        .about("Import todos from a file in the format of another tool.")
+        .long_about(
+            "Import todos from a file in the format of another tool. The todos are
+added with new IDs, keeping links and subtasks between the imported
+todos, and contexts are added if missing. Todos with the UID of an
+existing todo, as given by ical and taskwarrior, update that todo.
+
+Example: todo import --format todotxt todo.txt
+         task export | todo import --format taskwarrior -",
+        )
+        .arg(format_arg(&Format::importable()))
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .help("The file to import, or - to read from stdin."),
+        )
+        .arg(
+            Arg::new("dry-run")
+                .long("dry-run")
+                .short('n')
+                .takes_value(false)
+                .help("Show the todos that would be added or updated without changing them."),
+        )
+}
+
+fn backup() -> Command<'static> {
+    Command::new("backup")
+        .about("Back up the database.")
+        .long_about(
+            "Write a consistent snapshot of the database to a file, which defaults
+to a new file in the backups directory next to the database. Backups
+are also made automatically before prune and context --remove --cascade,
+keeping the number given by the setting 'backups'.",
+        )
+        .arg(
+            Arg::new("path")
+                .required(false)
+                .help("The file or directory to write the backup to."),
+        )
+}
+
+fn restore() -> Command<'static> {
+    Command::new("restore")
+        .about("Replace the database with a backup.")
+        .long_about(
+            "Replace the database with a backup, after checking that it's intact and
+made by this or an earlier version. The database is backed up first.",
+        )
+        .arg(
+            Arg::new("file")
+                .required(true)
+                .help("The backup to restore."),
+        )
+        .arg(
+            Arg::new("yes")
+                .long("yes")
+                .short('y')
+                .help("Do not confirm.")
+                .required(false),
+        )
 }
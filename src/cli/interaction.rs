@@ -1,12 +1,12 @@
 use crate::error::Result;
-use crate::model::{Prio, Status, Todo, CSV};
+use crate::model::{Code, Prio, Recurrence, Status, Todo, CSV};
 use crate::service::Changeset;
 use crate::{err, util};
 use inquire::{Confirm, Select, Text};
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 #[derive(Default)]
@@ -51,15 +51,23 @@ impl StdinPrompt {
 
 pub struct EditorBuilder {
     content: Vec<String>,
+    /// Editor command to use instead of $EDITOR or $VISUAL.
+    command: Option<String>,
 }
 
 impl EditorBuilder {
     pub fn new() -> Self {
         Self {
             content: Vec::new(),
+            command: None,
         }
     }
 
+    pub fn with_command(mut self, command: Option<&str>) -> Self {
+        self.command = command.map(String::from);
+        self
+    }
+
     pub fn push(&mut self, s: String) {
         self.content.push(s);
     }
@@ -68,7 +76,9 @@ impl EditorBuilder {
         let ext = file_ext.unwrap_or("");
 
         let path = env::temp_dir().join(format!("todo-{}{}", util::random_string(8), ext));
-        let editor = if let Some(e) = util::try_get_env("EDITOR") {
+        let editor = if let Some(e) = &self.command {
+            e.to_string()
+        } else if let Some(e) = util::try_get_env("EDITOR") {
             e
         } else if let Some(e) = util::try_get_env("VISUAL") {
             e
@@ -97,14 +107,17 @@ impl Editor {
         Self { path, editor }
     }
 
-    pub fn empty() -> Self {
-        EditorBuilder::new().build(None).unwrap()
+    pub fn empty(command: Option<&str>) -> Self {
+        EditorBuilder::new()
+            .with_command(command)
+            .build(None)
+            .unwrap()
     }
 
-    pub fn todo(todo: &Todo) -> Result<Changeset> {
+    pub fn todo(todo: &Todo, command: Option<&str>) -> Result<Changeset> {
         let text = toml::to_string_pretty(&EditTodo::from(todo))?;
 
-        let mut builder = EditorBuilder::new();
+        let mut builder = EditorBuilder::new().with_command(command);
         for line in text.lines() {
             builder.push(line.to_string());
         }
@@ -114,6 +127,18 @@ impl Editor {
 
         let prio = Prio::try_from(updated.prio)?;
         let tags = CSV::try_from(updated.tags.join(","))?;
+        let due = match updated.due.trim() {
+            "" => None,
+            s => Some(util::parse_date(s)?),
+        };
+        let after = match updated.after.trim() {
+            "" => None,
+            s => Some(util::parse_date(s)?),
+        };
+        let recurrence = match updated.recurrence.trim() {
+            "" => None,
+            s => Some(Recurrence::try_from(s)?),
+        };
 
         let cs = Changeset::default()
             .with_subject(updated.subject)
@@ -121,13 +146,16 @@ impl Editor {
             .with_prio(prio)
             .with_description(updated.description)
             .with_context(updated.context)
-            .with_tags(tags);
+            .with_tags(tags)
+            .with_due(due)
+            .with_recurrence(recurrence)
+            .with_after(after);
 
         Ok(cs)
     }
 
-    pub fn string(text: &str) -> Result<String> {
-        let mut builder = EditorBuilder::new();
+    pub fn string(text: &str, command: Option<&str>) -> Result<String> {
+        let mut builder = EditorBuilder::new().with_command(command);
         for line in text.lines() {
             builder.push(line.to_string());
         }
@@ -137,7 +165,15 @@ impl Editor {
     }
 
     pub fn edit(&self) -> Result<String> {
-        let mut cmd = Command::new(&self.editor);
+        let words = command_words(&self.editor)?;
+        let mut cmd = match words.split_first() {
+            Some((program, args)) => {
+                let mut cmd = Command::new(program);
+                cmd.args(args);
+                cmd
+            }
+            None => return err!("empty editor command"),
+        };
         cmd.arg(self.path.to_str().unwrap());
 
         match cmd.status() {
@@ -150,6 +186,20 @@ impl Editor {
     }
 }
 
+// Splits an editor command into the program and its arguments, e.g.
+// 'code --wait'. A path to a program is used as is, even if it contains
+// spaces, and otherwise the words are split as by a shell.
+fn command_words(command: &str) -> Result<Vec<String>> {
+    let command = command.trim();
+    if Path::new(command).is_file() {
+        return Ok(vec![command.to_string()]);
+    }
+    match shell_words::split(command) {
+        Ok(words) => Ok(words),
+        Err(e) => err!("invalid editor command: {}: {}", command, e),
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 /// Used to edit a todo from an editor.
 struct EditTodo {
@@ -158,6 +208,15 @@ struct EditTodo {
     prio: String,
     tags: Vec<String>,
     context: String,
+    /// Due date as YYYY-MM-DD, empty if none.
+    #[serde(default)]
+    due: String,
+    /// Recurrence rule, e.g. weekly:mon,fri, empty if none.
+    #[serde(default)]
+    recurrence: String,
+    /// Date as YYYY-MM-DD the todo is deferred until, empty if none.
+    #[serde(default)]
+    after: String,
     description: String,
 }
 
@@ -174,7 +233,40 @@ impl From<&Todo> for EditTodo {
             prio: todo.prio.to_string(),
             tags: todo.tags.display_values(),
             context: c,
+            due: todo.due.map(|d| d.to_string()).unwrap_or_default(),
+            recurrence: todo
+                .recurrence
+                .as_ref()
+                .map(|r| r.encode())
+                .unwrap_or_default(),
+            after: todo.after.map(|d| d.to_string()).unwrap_or_default(),
             description: todo.description.to_string(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_command_words() {
+        assert_eq!(
+            command_words("code --wait").unwrap(),
+            vec!["code", "--wait"]
+        );
+        assert_eq!(
+            command_words("'/opt/Sublime Text/subl' -w").unwrap(),
+            vec!["/opt/Sublime Text/subl", "-w"]
+        );
+        assert!(command_words("vim 'unclosed").is_err());
+
+        let dir = env::temp_dir().join(format!("todo editor {}", rand::random::<u32>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let program = dir.join("my editor");
+        std::fs::write(&program, "").unwrap();
+        let path = program.to_str().unwrap();
+        assert_eq!(command_words(path).unwrap(), vec![path]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
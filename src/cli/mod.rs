@@ -1,13 +1,22 @@
+use crate::backup;
+use crate::config::{self, Config};
 use crate::err;
 use crate::error::Result;
-use crate::format::{Card, Formatter, TableFormatter};
-use crate::model::{Link, Prio, Status, CSV, ID};
+use crate::format::{Card, Formatter, GraphFormat, GraphFormatter, Output, TableFormatter};
+use crate::interop::Format;
+use crate::model::event::{Event, State};
+use crate::model::{Layout, Link, Prio, Recurrence, Status, Todo, View, CSV, ID};
 use crate::service::changeset::Changeset;
-use crate::service::{ContextFilter, Filter, PruneFilter, Service, StatusFilter};
-use crate::style::{Color, Styler};
+use crate::service::{
+    graph, ContextFilter, DueFilter, Expr, Filter, PruneFilter, Service, Sort, StatusFilter,
+};
+use crate::style::{Color, StyleDisplay, Styler};
+use crate::util;
+use chrono::{Local, NaiveDate};
 use clap::ArgMatches;
 use std::path::PathBuf;
 use std::process;
+use std::{fs, io};
 
 mod app;
 mod interaction;
@@ -15,10 +24,12 @@ mod interaction;
 use interaction::{Editor, StdinPrompt};
 
 pub struct Cli {
-    #[allow(dead_code)]
     root: PathBuf,
+    database: PathBuf,
     service: Service,
+    config: Config,
     prompt: StdinPrompt,
+    output: Output,
     formatter: Box<dyn Formatter>,
     blue_styler: Styler,
     bold_styler: Styler,
@@ -30,10 +41,13 @@ pub struct Cli {
 impl Cli {
     pub fn new(root: PathBuf, service: Service) -> Self {
         Self {
+            database: root.join(config::DATABASE_FILE),
             root,
             service,
+            config: Config::default(),
             prompt: StdinPrompt::default(),
-            formatter: Box::new(TableFormatter::new(true)),
+            output: Output::default(),
+            formatter: Output::default().formatter(),
             blue_styler: Styler::default().fg(Color::Blue),
             bold_styler: Styler::default().bold(true),
             red_styler: Styler::default().bold(true).fg(Color::Red),
@@ -42,24 +56,63 @@ impl Cli {
         }
     }
 
-    pub async fn exec(&self) -> Result<()> {
-        let matches = app::build_app().get_matches();
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_database(mut self, database: PathBuf) -> Self {
+        self.database = database;
+        self
+    }
+
+    /// Parses the command line arguments.
+    pub fn parse() -> ArgMatches {
+        app::build_app().get_matches()
+    }
+
+    pub async fn exec(&mut self, matches: &ArgMatches) -> Result<()> {
         let log_level = matches.get_one::<String>("log");
         self.enable_log(log_level.map(|s| s.as_str()))?;
 
+        if let Some(output) = matches.get_one::<String>("output") {
+            self.output = Output::try_from(output.as_str())?;
+            self.formatter = match self.output {
+                Output::Table => {
+                    Box::new(TableFormatter::new(true).with_columns(self.config.columns()?))
+                }
+                output => output.formatter(),
+            };
+        }
+
         match matches.subcommand() {
             None => self.handle_default().await?,
             Some(("show", sub_matches)) => self.handle_show(sub_matches).await?,
             Some(("list", sub_matches)) => self.handle_list(sub_matches).await?,
+            Some(("search", sub_matches)) => self.handle_search(sub_matches).await?,
+            Some(("next", sub_matches)) => self.handle_next(sub_matches).await?,
             Some(("add", sub_matches)) => self.handle_add(sub_matches).await?,
             Some(("remove", sub_matches)) => self.handle_remove(sub_matches).await?,
+            Some(("snooze", sub_matches)) => self.handle_snooze(sub_matches).await?,
             Some(("done", sub_matches)) => self.handle_done(sub_matches).await?,
             Some(("start", sub_matches)) => self.handle_start(sub_matches).await?,
             Some(("set", sub_matches)) => self.handle_set(sub_matches).await?,
             Some(("edit", sub_matches)) => self.handle_edit(sub_matches).await?,
             Some(("context", sub_matches)) => self.handle_context(sub_matches).await?,
+            Some(("view", sub_matches)) => self.handle_view(sub_matches).await?,
+            Some(("config", sub_matches)) => self.handle_config(sub_matches)?,
             Some(("starship", sub_matches)) => self.handle_starship(sub_matches).await?,
             Some(("prune", sub_matches)) => self.handle_prune(sub_matches).await?,
+            Some(("log", sub_matches)) => self.handle_log(sub_matches).await?,
+            Some(("undo", sub_matches)) => self.handle_undo(sub_matches).await?,
+            Some(("redo", sub_matches)) => self.handle_redo(sub_matches).await?,
+            Some(("doctor", _)) => self.handle_doctor().await?,
+            Some(("graph", sub_matches)) => self.handle_graph(sub_matches).await?,
+            Some(("impact", sub_matches)) => self.handle_impact(sub_matches).await?,
+            Some(("export", sub_matches)) => self.handle_export(sub_matches).await?,
+            Some(("import", sub_matches)) => self.handle_import(sub_matches).await?,
+            Some(("backup", sub_matches)) => self.handle_backup(sub_matches).await?,
+            Some(("restore", sub_matches)) => self.handle_restore(sub_matches).await?,
             _ => unreachable!(),
         }
 
@@ -88,53 +141,190 @@ impl Cli {
 impl Cli {
     // Only list todos with default filter. No options supported.
     async fn handle_default(&self) -> Result<()> {
-        let todos = self.service.list_todos(Some(Filter::default())).await?;
-        if !todos.is_empty() {
-            println!("{}", self.formatter.todos(&todos));
+        if let Some(view) = self.service.default_view().await? {
+            return self.run_view(&view).await;
         }
+
+        let filter = match self.config.filter()? {
+            Some(expr) => Filter::default().expr(expr),
+            None => Filter::default(),
+        };
+        let todos = self.service.list_todos(Some(filter), None).await?;
+        self.print_todos(&todos, &Layout::Table);
         Ok(())
     }
 
     async fn handle_show(&self, matches: &ArgMatches) -> Result<()> {
         let id = Self::parse_id(matches.get_one::<String>("id").unwrap().as_str())?;
         let todo = self.service.get_todo(&id).await?;
+        if self.output.is_structured() {
+            self.print(self.formatter.todo(&todo));
+            return Ok(());
+        }
+
+        let subtasks = self.service.get_subtasks(&id, false).await?;
         let card = Card::new(true);
-        let s = card.format(&todo);
+        let s = card.format_with_subtasks(&todo, &subtasks);
         println!("{s}");
         Ok(())
     }
 
     async fn handle_list(&self, matches: &ArgMatches) -> Result<()> {
+        let (view, filter, sort) = self.parse_selection(matches).await?;
+        let layout = if matches.contains_id("details") {
+            Layout::Details
+        } else if matches.contains_id("tree") {
+            Layout::Tree
+        } else {
+            view.as_ref()
+                .map(|view| view.layout.clone())
+                .unwrap_or_default()
+        };
+
+        let todos = self.service.list_todos(filter, sort.as_ref()).await?;
+        self.print_todos(&todos, &layout);
+        Ok(())
+    }
+
+    // Resolves the view, filter and sort of the todos to select, as given
+    // by the arguments shared by list and export.
+    async fn parse_selection(
+        &self,
+        matches: &ArgMatches,
+    ) -> Result<(Option<View>, Option<Filter>, Option<Sort>)> {
+        let view = match matches.get_one::<String>("view") {
+            Some(s) => match s.strip_prefix('@') {
+                Some(name) => Some(self.service.get_view(name).await?),
+                None => return err!("invalid view: {}, views are given as @NAME", s),
+            },
+            None => None,
+        };
+
         let filter = if matches.contains_id("all") {
             None
         } else {
-            let filter = Filter::default();
-
-            let filter = match matches.get_one::<String>("status") {
-                Some(status) => match status.as_str() {
-                    "any" => filter.status(StatusFilter::Any),
-                    status => {
-                        let s = Status::try_from(status.to_string())?;
-                        filter.status(StatusFilter::Status(s))
-                    }
+            let filter = Self::parse_filter(matches)?;
+            match &view {
+                Some(view) => Some(filter.expr(Expr::try_from(view.filter.as_str())?)),
+                // The configured filter is used unless another one is given
+                None if !matches.contains_id("where") => match self.config.filter()? {
+                    Some(expr) => Some(filter.expr(expr)),
+                    None => Some(filter),
                 },
-                None => filter,
-            };
+                None => Some(filter),
+            }
+        };
 
-            let filter = match matches.get_one::<String>("context") {
-                Some(s) => filter.context(ContextFilter::Name(s.to_string())),
-                None => filter,
-            };
+        let sort = match matches.get_one::<String>("sort") {
+            Some(s) => Some(Sort::try_from(s.as_str())?),
+            None => match view.as_ref().and_then(|view| view.sort.as_deref()) {
+                Some(s) => Some(Sort::try_from(s)?),
+                None => None,
+            },
+        };
+        Ok((view, filter, sort))
+    }
 
-            let filter = match matches.get_many::<String>("tags") {
-                Some(tags) => filter.tags(tags.map(String::from).collect()),
-                None => filter,
-            };
+    // Prints the todos using the given layout, if any. Structured output
+    // ignores the layout and is printed even if there are no todos.
+    fn print_todos(&self, todos: &[Todo], layout: &Layout) {
+        if self.output.is_structured() {
+            self.print(self.formatter.todos(todos));
+            return;
+        }
+        if todos.is_empty() {
+            return;
+        }
+
+        match layout {
+            Layout::Table => println!("{}", self.formatter.todos(todos)),
+            Layout::Tree => println!("{}", self.formatter.tree(todos)),
+            Layout::Details => {
+                let card = Card::new(true);
+                let todos: Vec<String> = todos.iter().map(|todo| card.format(todo)).collect();
+                println!("{}", todos.join("\n\n"))
+            }
+        }
+    }
+
+    async fn handle_view(&self, matches: &ArgMatches) -> Result<()> {
+        match matches.subcommand() {
+            Some(("save", sub_matches)) => {
+                let name = sub_matches.get_one::<String>("name").unwrap();
+                let filter = sub_matches.get_one::<String>("filter").unwrap();
+                let layout = match sub_matches.get_one::<String>("layout") {
+                    Some(s) => Layout::try_from(s.as_str())?,
+                    None => Layout::default(),
+                };
+                let view = View::new(name.to_string(), filter.to_string())
+                    .with_layout(layout)
+                    .with_sort(sub_matches.get_one::<String>("sort").cloned());
+                self.service.save_view(&view).await?;
+                println!("Saved view {}", self.green_styler.style(name));
+            }
+            Some(("rm", sub_matches)) => {
+                let name = sub_matches.get_one::<String>("name").unwrap();
+                self.service.remove_view(name).await?;
+                println!("Removed view {}", self.green_styler.style(name));
+            }
+            _ => match matches.get_one::<String>("name") {
+                Some(name) => {
+                    let view = self.service.get_view(name).await?;
+                    self.run_view(&view).await?;
+                }
+                None => {
+                    for view in self.service.list_views().await? {
+                        println!(
+                            "{:<20} {:<8} {:<16} {}",
+                            view.name,
+                            view.layout.to_string(),
+                            view.sort.unwrap_or_default(),
+                            self.blue_styler.style(&view.filter)
+                        );
+                    }
+                }
+            },
+        }
+        Ok(())
+    }
 
-            Some(filter)
+    // Prints formatted output unless empty, e.g. NDJSON without items.
+    fn print(&self, s: String) {
+        if !s.is_empty() {
+            println!("{s}");
+        }
+    }
+
+    // Lists todos matching the view, using the default filter otherwise.
+    async fn run_view(&self, view: &View) -> Result<()> {
+        let expr = Expr::try_from(view.filter.as_str())?;
+        let sort = match &view.sort {
+            Some(s) => Some(Sort::try_from(s.as_str())?),
+            None => None,
         };
+        let todos = self
+            .service
+            .list_todos(Some(Filter::default().expr(expr)), sort.as_ref())
+            .await?;
+        self.print_todos(&todos, &view.layout);
+        Ok(())
+    }
 
-        let todos = self.service.list_todos(filter).await?;
+    async fn handle_search(&self, matches: &ArgMatches) -> Result<()> {
+        let query: Vec<String> = matches
+            .get_many::<String>("query")
+            .unwrap()
+            .map(String::from)
+            .collect();
+        let query = query.join(" ");
+        let todos = self
+            .service
+            .search_todos(&query, Self::parse_filter(matches)?)
+            .await?;
+        if self.output.is_structured() {
+            self.print(self.formatter.search(&todos, &[]));
+            return Ok(());
+        }
         if todos.is_empty() {
             return Ok(());
         }
@@ -144,37 +334,76 @@ impl Cli {
             let todos: Vec<String> = todos.iter().map(|todo| card.format(todo)).collect();
             println!("{}", todos.join("\n\n"))
         } else {
-            println!("{}", self.formatter.todos(&todos));
+            let terms = util::search_terms(&query);
+            println!("{}", self.formatter.search(&todos, &terms));
+        }
+
+        Ok(())
+    }
+
+    async fn handle_next(&self, matches: &ArgMatches) -> Result<()> {
+        let limit = matches.get_one::<usize>("limit").copied();
+        let queue = self.service.next_todos(limit).await?;
+        if self.output.is_structured() {
+            self.print(self.formatter.todos(&queue.ready));
+            return Ok(());
+        }
+
+        if queue.ready.is_empty() {
+            println!("No todos ready to be started.");
+        } else {
+            println!("{}", self.formatter.todos(&queue.ready));
+        }
+
+        if matches.contains_id("details") && !queue.excluded.is_empty() {
+            println!();
+            println!("{}", self.bold_styler.style("Excluded:"));
+            for (todo, reason) in &queue.excluded {
+                println!(
+                    "{:>3}  {}  ({})",
+                    todo.id.to_string(),
+                    todo.subject,
+                    self.yellow_styler.style(&reason.to_string())
+                );
+            }
         }
 
         Ok(())
     }
 
     async fn handle_add(&self, matches: &ArgMatches) -> Result<()> {
+        let prompts = self.config.prompts();
         let subject = match matches.get_one::<String>("subject") {
             Some(s) => s.to_string(),
+            None if !prompts => return err!("no subject provided, prompts are disabled"),
             None => self.prompt.line("subject>", false)?,
         };
         log::debug!("New todo: subject={}", subject);
 
+        let default_prio = self.config.prio()?;
         let prio = match matches.get_one::<String>("prio") {
             Some(s) => Prio::try_from(s.to_string())?,
-            None => match self
-                .prompt
-                .select(
-                    "priority",
-                    vec![
-                        self.bold_styler.style("normal"),
-                        self.blue_styler.style("low"),
-                        self.yellow_styler.style("high"),
-                        self.red_styler.style("critical"),
-                    ],
-                )?
-                .as_str()
-            {
-                "" => Prio::Normal,
-                s => Prio::try_from(s.to_string())?,
-            },
+            None if !prompts => default_prio,
+            None => {
+                // The default priority is the first option
+                let mut options = vec![
+                    self.bold_styler.style("normal"),
+                    self.blue_styler.style("low"),
+                    self.yellow_styler.style("high"),
+                    self.red_styler.style("critical"),
+                ];
+                if let Some(i) = options
+                    .iter()
+                    .position(|s| s.contains(&default_prio.to_string()))
+                {
+                    let option = options.remove(i);
+                    options.insert(0, option);
+                }
+                match self.prompt.select("priority", options)?.as_str() {
+                    "" => default_prio,
+                    s => Prio::try_from(s.to_string())?,
+                }
+            }
         };
         log::info!("New todo: priority={}", &prio);
 
@@ -183,6 +412,7 @@ impl Cli {
 
         let tags: Vec<String> = match matches.get_many::<String>("tag") {
             Some(s) => s.map(String::from).collect(),
+            None if !prompts => vec![],
             None => match self
                 .prompt
                 .line("tags (single words, comma separated)", true)?
@@ -194,9 +424,31 @@ impl Cli {
         };
         log::debug!("New todo: tags={:?}", tags);
 
+        let extras = match matches.get_one::<String>("due") {
+            Some(s) => Changeset::default().with_due(Self::parse_due(s)?),
+            None => Changeset::default(),
+        };
+
+        let extras = match matches.get_one::<String>("recur") {
+            Some(s) => extras.with_recurrence(Self::parse_recurrence(s)?),
+            None => extras,
+        };
+
+        let extras = match matches.get_one::<String>("parent") {
+            Some(s) => extras.with_parent(Self::parse_parent(s)?),
+            None => extras,
+        };
+
         let todo = self
             .service
-            .add_todo(Status::New, prio, subject, description, CSV::new(tags))
+            .add_todo_with(
+                Status::New,
+                prio,
+                subject,
+                description,
+                CSV::new(tags),
+                extras,
+            )
             .await?;
 
         println!("{}", self.formatter.todo(&todo));
@@ -205,23 +457,23 @@ impl Cli {
     }
 
     async fn handle_done(&self, matches: &ArgMatches) -> Result<()> {
-        let ids = Self::get_ids(matches)?;
+        let ids = self.get_target_ids(matches).await?;
+        let cascade = matches.contains_id("cascade");
         let mut updated = Vec::new();
 
         for id in ids {
-            let changeset = Changeset::default().with_status(Status::Done);
-            let todo = self.service.update_todo(&id, changeset).await?;
+            let todo = self.service.complete_todo(&id, cascade).await?;
             updated.push(todo);
         }
 
-        if !updated.is_empty() {
-            println!("{}", self.formatter.todos(&updated));
+        if !updated.is_empty() || self.output.is_structured() {
+            self.print(self.formatter.todos(&updated));
         }
         Ok(())
     }
 
     async fn handle_start(&self, matches: &ArgMatches) -> Result<()> {
-        let ids = Self::get_ids(matches)?;
+        let ids = self.get_target_ids(matches).await?;
         let mut updated = Vec::new();
 
         for id in ids {
@@ -230,8 +482,8 @@ impl Cli {
             updated.push(todo);
         }
 
-        if !updated.is_empty() {
-            println!("{}", self.formatter.todos(&updated));
+        if !updated.is_empty() || self.output.is_structured() {
+            self.print(self.formatter.todos(&updated));
         }
         Ok(())
     }
@@ -265,6 +517,21 @@ impl Cli {
             None => changeset,
         };
 
+        let changeset = match matches.get_one::<String>("due") {
+            Some(s) => changeset.with_due(Self::parse_due(s)?),
+            None => changeset,
+        };
+
+        let changeset = match matches.get_one::<String>("recur") {
+            Some(s) => changeset.with_recurrence(Self::parse_recurrence(s)?),
+            None => changeset,
+        };
+
+        let changeset = match matches.get_one::<String>("parent") {
+            Some(s) => changeset.with_parent(Self::parse_parent(s)?),
+            None => changeset,
+        };
+
         let todo = self.service.update_todo(&id, changeset).await?;
 
         // Linking requires additional rules and validation
@@ -287,11 +554,11 @@ impl Cli {
         let todo = self.service.get_todo(&id).await?;
         let changeset = if matches.contains_id("description") {
             log::debug!("Only editing description of the todo");
-            let desc = Editor::string(&todo.description)?;
+            let desc = Editor::string(&todo.description, self.config.editor())?;
             Changeset::default().with_description(desc)
         } else {
             log::debug!("Editing the whole todo");
-            Editor::todo(&todo)?
+            Editor::todo(&todo, self.config.editor())?
         };
 
         let todo = self.service.update_todo(&id, changeset).await?;
@@ -317,6 +584,9 @@ impl Cli {
             println!("Context set to {}.", self.green_styler.style(cx));
         } else if let Some(cx) = matches.get_one::<String>("remove") {
             let cascade = matches.contains_id("cascade");
+            if cascade {
+                self.auto_backup("context").await?;
+            }
             self.service.remove_context(cx, cascade).await?;
             println!("Removed context {}", cx);
         } else if matches.contains_id("unset") {
@@ -324,12 +594,12 @@ impl Cli {
             println!("Current context unset.");
         } else if matches.contains_id("list") {
             let contexts = self.service.list_contexts().await?;
-            if contexts.is_empty() {
+            if self.output.is_structured() {
+                self.print(self.formatter.contexts(&contexts));
+            } else if contexts.is_empty() {
                 println!("No contexts created.");
             } else {
-                for ctx in contexts {
-                    println!("{ctx}");
-                }
+                println!("{}", self.formatter.contexts(&contexts));
             }
         } else {
             match self.service.get_context().await? {
@@ -341,13 +611,45 @@ impl Cli {
         Ok(())
     }
 
+    fn handle_config(&self, matches: &ArgMatches) -> Result<()> {
+        match matches.subcommand() {
+            Some(("get", sub_matches)) => {
+                let key = sub_matches.get_one::<String>("key").unwrap();
+                if let Some(value) = self.config.get(key)? {
+                    println!("{value}");
+                }
+            }
+            Some(("set", sub_matches)) => {
+                let key = sub_matches.get_one::<String>("key").unwrap();
+                let value = sub_matches.get_one::<String>("value").unwrap();
+                let mut config = Config::read(&self.root)?;
+                config.set(key, value)?;
+                config.save(&Config::path(&self.root))?;
+                match config.get(key)? {
+                    Some(value) => println!("Set {} to {}", key, self.green_styler.style(&value)),
+                    None => println!("Set {} to the default", key),
+                }
+            }
+            _ => {
+                for key in config::KEYS {
+                    match self.config.get(key)? {
+                        Some(value) => println!("{:<8} = {}", key, value),
+                        None => println!("{:<8}   {}", key, self.blue_styler.style("(default)")),
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     async fn handle_remove(&self, matches: &ArgMatches) -> Result<()> {
         let yes = matches.contains_id("yes");
-        let ids = Self::get_ids(matches)?;
+        let cascade = matches.contains_id("cascade");
+        let ids = self.get_target_ids(matches).await?;
 
         for id in ids {
             if yes {
-                self.service.remove_todo(&id).await?;
+                self.service.remove_todo(&id, cascade).await?;
             } else {
                 let msg = format!(
                     "{} todo with ID {}",
@@ -355,15 +657,46 @@ impl Cli {
                     self.green_styler.style(&id.to_string())
                 );
                 if self.prompt.confirm(&msg, false)? {
-                    self.service.remove_todo(&id).await?;
+                    self.service.remove_todo(&id, cascade).await?;
                 }
             }
         }
         Ok(())
     }
 
+    async fn handle_snooze(&self, matches: &ArgMatches) -> Result<()> {
+        let ids = Self::get_ids(matches)?;
+        let after = match matches.get_one::<String>("when").unwrap().trim() {
+            "none" => None,
+            s => Some(util::parse_date(s)?),
+        };
+
+        let mut updated = Vec::new();
+        for id in ids {
+            let changeset = Changeset::default().with_after(after);
+            let todo = self.service.update_todo(&id, changeset).await?;
+            updated.push(todo);
+        }
+
+        if self.output.is_structured() {
+            self.print(self.formatter.todos(&updated));
+            return Ok(());
+        }
+        if let Some(after) = after {
+            println!(
+                "Deferred until {}:",
+                self.green_styler.style(&after.to_string())
+            );
+        }
+        println!("{}", self.formatter.todos(&updated));
+        Ok(())
+    }
+
     async fn handle_starship(&self, matches: &ArgMatches) -> Result<()> {
-        let todos = self.service.list_todos(Some(Filter::default())).await?;
+        let todos = self
+            .service
+            .list_todos(Some(Filter::default()), None)
+            .await?;
 
         if matches.contains_id("when") {
             if todos.is_empty() {
@@ -379,14 +712,264 @@ impl Cli {
 
     async fn handle_prune(&self, matches: &ArgMatches) -> Result<()> {
         let filter = PruneFilter::default().with_done(matches.contains_id("done"));
+        let filter = match matches.get_one::<String>("where") {
+            Some(s) => filter.with_expr(Expr::try_from(s.as_str())?),
+            None => filter,
+        };
+        // Only back up if there is anything to remove
+        if self.service.list_prunable(&filter).await?.is_empty() {
+            return Ok(());
+        }
+        self.auto_backup("prune").await?;
         self.service.prune(filter).await
     }
 
+    async fn handle_log(&self, matches: &ArgMatches) -> Result<()> {
+        let id = match matches.get_one::<String>("id") {
+            Some(id) => Some(Self::parse_id(id)?),
+            None => None,
+        };
+
+        let since = match matches.get_one::<String>("since") {
+            Some(s) => {
+                let date = util::parse_date(s)?;
+                date.and_hms_opt(0, 0, 0)
+                    .and_then(|dt| dt.and_local_timezone(Local).earliest())
+            }
+            None => None,
+        };
+
+        let events = self.service.list_events(id, since).await?;
+        for event in events {
+            let action = event
+                .action
+                .styler()
+                .style(&format!("{:<6}", event.action.to_string()));
+            let state = match event.state {
+                State::Applied => String::new(),
+                state => self.yellow_styler.style(&format!(" ({})", state)),
+            };
+            println!(
+                "{}  {:<10}  {}  {}{}",
+                event.datetime().format("%Y-%m-%d %H:%M:%S"),
+                event.author.unwrap_or_default(),
+                action,
+                event.kind,
+                state,
+            );
+        }
+        Ok(())
+    }
+
+    async fn handle_undo(&self, matches: &ArgMatches) -> Result<()> {
+        let events = self.service.pending_undo().await?;
+        if events.is_empty() {
+            println!("Nothing to undo.");
+            return Ok(());
+        }
+
+        if !matches.contains_id("yes") {
+            let msg = format!("{} the following changes?", self.red_styler.style("Undo"));
+            if !self.confirm_events(&msg, &events)? {
+                return Ok(());
+            }
+        }
+
+        let events = self.service.undo().await?;
+        println!("Undid {} change(s).", events.len());
+        Ok(())
+    }
+
+    async fn handle_redo(&self, matches: &ArgMatches) -> Result<()> {
+        let events = self.service.pending_redo().await?;
+        if events.is_empty() {
+            println!("Nothing to redo.");
+            return Ok(());
+        }
+
+        if !matches.contains_id("yes") {
+            let msg = format!("{} the following changes?", self.green_styler.style("Redo"));
+            if !self.confirm_events(&msg, &events)? {
+                return Ok(());
+            }
+        }
+
+        let events = self.service.redo().await?;
+        println!("Redid {} change(s).", events.len());
+        Ok(())
+    }
+
+    async fn handle_doctor(&self) -> Result<()> {
+        let cycles = self.service.find_cycles().await?;
+        if cycles.is_empty() {
+            println!("No problems found.");
+            return Ok(());
+        }
+
+        for cycle in &cycles {
+            println!(
+                "{}: {}",
+                self.red_styler.style("Cycle of blocking links"),
+                graph::format_path(cycle)
+            );
+        }
+        println!("Use 'todo set ID --unlink blocks:ID' to break a cycle.");
+        err!("found {} problem(s)", cycles.len())
+    }
+
+    async fn handle_graph(&self, matches: &ArgMatches) -> Result<()> {
+        let filter = Filter::default().include_deferred(true);
+        let filter = match matches.get_one::<String>("context") {
+            Some(s) => filter.context(ContextFilter::Name(s.to_string())),
+            None => filter,
+        };
+        let filter = if matches.contains_id("all") {
+            filter.status(StatusFilter::Any)
+        } else {
+            filter
+        };
+
+        let format = GraphFormat::try_from(matches.get_one::<String>("format").unwrap().as_str())?;
+        let todos = self.service.list_todos(Some(filter), None).await?;
+        println!("{}", GraphFormatter::new(format).format(&todos));
+        Ok(())
+    }
+
+    async fn handle_impact(&self, matches: &ArgMatches) -> Result<()> {
+        let impact = self.service.impact().await?;
+        // --json is short for --output json
+        if matches.contains_id("json") {
+            self.print(Output::Json.formatter().impact(&impact));
+        } else if self.output.is_structured() {
+            self.print(self.formatter.impact(&impact));
+        } else if !impact.is_empty() {
+            println!("{}", self.formatter.impact(&impact));
+        }
+        Ok(())
+    }
+
+    async fn handle_export(&self, matches: &ArgMatches) -> Result<()> {
+        let format = Format::try_from(matches.get_one::<String>("format").unwrap().as_str())?;
+        let (_, filter, sort) = self.parse_selection(matches).await?;
+        let todos = self.service.list_todos(filter, sort.as_ref()).await?;
+        self.print(format.export(&todos));
+        Ok(())
+    }
+
+    async fn handle_import(&self, matches: &ArgMatches) -> Result<()> {
+        let format = Format::try_from(matches.get_one::<String>("format").unwrap().as_str())?;
+        let s = match matches.get_one::<String>("file").unwrap().as_str() {
+            "-" => io::read_to_string(io::stdin())?,
+            path => match fs::read_to_string(path) {
+                Ok(s) => s,
+                Err(e) => return err!("failed to read {}: {}", path, e),
+            },
+        };
+
+        let import = format.import(&s)?;
+        // Todos that were exported from here are updated rather than added.
+        let existing = import.matches(&self.service.list_todos(None, None).await?);
+        if matches.contains_id("dry-run") {
+            let (mut updated, added): (Vec<Todo>, Vec<Todo>) = import
+                .todos
+                .into_iter()
+                .partition(|todo| existing.contains_key(&todo.id));
+            for todo in updated.iter_mut() {
+                todo.id = existing[&todo.id];
+            }
+            if self.output.is_structured() {
+                self.print_todos(&[updated, added].concat(), &Layout::Table);
+                return Ok(());
+            }
+            if !added.is_empty() {
+                println!("Would add {} todos, which get new IDs:", added.len());
+                self.print_todos(&added, &Layout::Table);
+            }
+            if !updated.is_empty() {
+                println!("Would update {} todos:", updated.len());
+                self.print_todos(&updated, &Layout::Table);
+            }
+            return Ok(());
+        }
+
+        let todos = self.service.import_todos(import.todos, &existing).await?;
+        self.print_todos(&todos, &Layout::Table);
+        Ok(())
+    }
+
+    async fn handle_backup(&self, matches: &ArgMatches) -> Result<()> {
+        let path = match matches.get_one::<String>("path") {
+            Some(path) if fs::metadata(path).is_ok_and(|m| m.is_dir()) => {
+                let name = backup::path(&self.database);
+                PathBuf::from(path).join(name.file_name().unwrap())
+            }
+            Some(path) => PathBuf::from(path),
+            None => backup::path(&self.database),
+        };
+        self.service.backup(&path).await?;
+        println!("Backed up database to {}", path.display());
+        Ok(())
+    }
+
+    async fn handle_restore(&self, matches: &ArgMatches) -> Result<()> {
+        let path = PathBuf::from(matches.get_one::<String>("file").unwrap());
+        let version = backup::check(&path).await?;
+        let msg = format!(
+            "Replace {} with {}?",
+            self.database.display(),
+            path.display()
+        );
+        if !matches.contains_id("yes") && !self.prompt.confirm(&msg, true)? {
+            return Ok(());
+        }
+
+        // Made even if automatic backups are turned off.
+        let current = backup::auto_path(&self.database, "restore");
+        self.service.backup(&current).await?;
+        backup::rotate(&self.database, self.config.backups().max(1))?;
+        self.service.close().await;
+        backup::restore(&path, &self.database).await?;
+        println!(
+            "Restored database from {} (migration {}), previous database backed up to {}",
+            path.display(),
+            version,
+            current.display()
+        );
+        Ok(())
+    }
+
+    // Backs up the database before a command that removes todos, keeping
+    // the configured number of automatic backups.
+    async fn auto_backup(&self, label: &str) -> Result<()> {
+        let keep = self.config.backups();
+        if keep == 0 {
+            return Ok(());
+        }
+        self.service
+            .backup(&backup::auto_path(&self.database, label))
+            .await?;
+        backup::rotate(&self.database, keep)
+    }
+
+    fn confirm_events(&self, msg: &str, events: &[Event]) -> Result<bool> {
+        for event in events {
+            let action = event
+                .action
+                .styler()
+                .style(&format!("{:<6}", event.action.to_string()));
+            println!("  {}  {}", action, event.kind);
+        }
+        self.prompt.confirm(msg, false)
+    }
+
     fn get_description(&self, matches: &ArgMatches) -> Result<String> {
         if let Some(s) = matches.get_one::<String>("description") {
             log::info!("Using description from flag");
             return Ok(s.to_string());
         }
+        if !self.config.prompts() {
+            return Ok(String::new());
+        }
 
         let options = vec![
             "Open editor".to_string(),
@@ -396,13 +979,26 @@ impl Cli {
 
         let option = self.prompt.select("Description", options)?;
         match option.as_str() {
-            "Open editor" => Editor::empty().edit(),
+            "Open editor" => Editor::empty(self.config.editor()).edit(),
             "Prompt" => self.prompt.line("Enter description", true),
             "Skip" => Ok(String::new()),
             _ => unreachable!(),
         }
     }
 
+    // Gets the IDs given as arguments or the IDs of the todos matching
+    // the filter expression given by --where.
+    async fn get_target_ids(&self, matches: &ArgMatches) -> Result<Vec<ID>> {
+        match matches.get_one::<String>("where") {
+            Some(s) => {
+                let filter = Filter::default().expr(Expr::try_from(s.as_str())?);
+                let todos = self.service.list_todos(Some(filter), None).await?;
+                Ok(todos.iter().map(|todo| todo.id).collect())
+            }
+            None => Self::get_ids(matches),
+        }
+    }
+
     fn get_ids(matches: &ArgMatches) -> Result<Vec<ID>> {
         let ids = match matches.get_many::<String>("ids") {
             Some(ids) => ids,
@@ -417,6 +1013,68 @@ impl Cli {
             .collect())
     }
 
+    // Parses a due date where an empty string means no due date.
+    fn parse_due(s: &str) -> Result<Option<NaiveDate>> {
+        match s.trim() {
+            "" => Ok(None),
+            s => Ok(Some(util::parse_date(s)?)),
+        }
+    }
+
+    // Parses a recurrence rule where an empty string means no recurrence.
+    fn parse_recurrence(s: &str) -> Result<Option<Recurrence>> {
+        match s.trim() {
+            "" => Ok(None),
+            s => Ok(Some(Recurrence::try_from(s)?)),
+        }
+    }
+
+    // Gets the filter given by the arguments shared by list and search.
+    fn parse_filter(matches: &ArgMatches) -> Result<Filter> {
+        let filter = match matches.get_one::<String>("where") {
+            Some(s) => Filter::default().expr(Expr::try_from(s.as_str())?),
+            None => Filter::default(),
+        };
+
+        let filter = match matches.get_one::<String>("status") {
+            Some(status) => match status.as_str() {
+                "any" => filter.status(StatusFilter::Any),
+                status => {
+                    let s = Status::try_from(status.to_string())?;
+                    filter.status(StatusFilter::Status(s))
+                }
+            },
+            None => filter,
+        };
+
+        let filter = match matches.get_one::<String>("context") {
+            Some(s) => filter.context(ContextFilter::Name(s.to_string())),
+            None => filter,
+        };
+
+        let filter = match matches.get_many::<String>("tags") {
+            Some(tags) => filter.tags(tags.map(String::from).collect()),
+            None => filter,
+        };
+
+        let filter = match matches.get_one::<String>("due") {
+            Some(s) => filter.due(DueFilter::try_from(s.as_str())?),
+            None => filter,
+        };
+
+        let filter = filter.include_deferred(matches.contains_id("include-deferred"));
+
+        Ok(filter)
+    }
+
+    // Parses the ID of a parent where an empty string means no parent.
+    fn parse_parent(s: &str) -> Result<Option<ID>> {
+        match s.trim() {
+            "" => Ok(None),
+            s => Ok(Some(Self::parse_id(s)?)),
+        }
+    }
+
     fn parse_id(id: &str) -> Result<ID> {
         match id.parse::<u16>() {
             Ok(n) => Ok(ID::new(n)),
@@ -424,3 +1082,109 @@ impl Cli {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::Repository;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+    // Backups are written next to the database, so it's a file rather
+    // than in memory.
+    async fn setup(name: &str) -> Cli {
+        let dir = std::env::temp_dir().join(format!("todo-{}-{}", name, rand::random::<u32>()));
+        fs::create_dir_all(&dir).unwrap();
+        let options = SqliteConnectOptions::new()
+            .filename(dir.join(config::DATABASE_FILE))
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        Cli::new(dir, Service::new(Repository::new(pool)))
+    }
+
+    async fn exec(cli: &mut Cli, args: &[&str]) {
+        let matches = app::build_app()
+            .try_get_matches_from([&["todo"], args].concat())
+            .unwrap();
+        cli.exec(&matches).await.unwrap();
+    }
+
+    fn backups(cli: &Cli) -> Vec<String> {
+        match fs::read_dir(cli.root.join(backup::BACKUPS_DIR)) {
+            Ok(entries) => entries
+                .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    async fn add_todo(cli: &Cli, status: Status) {
+        cli.service
+            .add_todo(
+                status,
+                Prio::Normal,
+                "Subject".to_string(),
+                "Description.".to_string(),
+                CSV::empty(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn prune_backs_up_removed_todos() {
+        let mut cli = setup("prune").await;
+        add_todo(&cli, Status::New).await;
+        exec(&mut cli, &["prune", "--done"]).await;
+        assert!(backups(&cli).is_empty());
+
+        add_todo(&cli, Status::Done).await;
+        exec(&mut cli, &["prune", "--done"]).await;
+        let names = backups(&cli);
+        assert_eq!(names.len(), 1);
+        assert!(names[0].ends_with("-prune.db"), "{:?}", names);
+        assert_eq!(cli.service.list_todos(None, None).await.unwrap().len(), 1);
+        fs::remove_dir_all(&cli.root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn remove_context_with_cascade_backs_up() {
+        let mut cli = setup("context").await;
+        cli.service.add_context("work").await.unwrap();
+        cli.service.add_context("home").await.unwrap();
+        exec(&mut cli, &["context", "--remove", "home"]).await;
+        assert!(backups(&cli).is_empty());
+
+        exec(&mut cli, &["context", "--remove", "work", "--cascade"]).await;
+        let names = backups(&cli);
+        assert_eq!(names.len(), 1);
+        assert!(names[0].ends_with("-context.db"), "{:?}", names);
+
+        cli.config.set("backups", "0").unwrap();
+        cli.service.add_context("work").await.unwrap();
+        exec(&mut cli, &["context", "--remove", "work", "--cascade"]).await;
+        assert_eq!(backups(&cli), names);
+        fs::remove_dir_all(&cli.root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn restore_rotates_backups() {
+        let mut cli = setup("restore").await;
+        cli.config.set("backups", "1").unwrap();
+        let path = cli.root.join("manual.db");
+        cli.service.backup(&path).await.unwrap();
+        let dir = cli.root.join(backup::BACKUPS_DIR);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("todo-auto-20261016-090000-000-prune.db"), "").unwrap();
+
+        exec(&mut cli, &["restore", path.to_str().unwrap(), "--yes"]).await;
+        let names = backups(&cli);
+        assert_eq!(names.len(), 1);
+        assert!(names[0].ends_with("-restore.db"), "{:?}", names);
+        fs::remove_dir_all(&cli.root).unwrap();
+    }
+}
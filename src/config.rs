@@ -0,0 +1,492 @@
+use crate::err;
+use crate::error::Result;
+use crate::format::table::Column;
+use crate::model::{LinkType, LinkTypes, Prio};
+use crate::service::{Expr, Sort};
+use crate::style::Theme;
+use crate::util;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use toml_edit::{value, Document, Item, Value};
+
+/// Name of the configuration file in the root directory.
+pub const CONFIG_FILE: &str = "config.toml";
+
+/// Environment variable with the path of a configuration file to use
+/// instead of the one in the root directory.
+pub const CONFIG_ENV: &str = "TODO_CONFIG";
+
+/// Name of the default database file in the root directory.
+pub const DATABASE_FILE: &str = "todo.db";
+
+/// Environment variable with the path of the database to use.
+pub const DATABASE_ENV: &str = "TODO_DB";
+
+/// Directory in the root directory with a database file per profile.
+pub const PROFILES_DIR: &str = "profiles";
+
+/// Returns the path of the database, given by `db` if set, or else the
+/// database of `profile`, or else `TODO_DB` or the default database.
+pub fn database_path(root: &Path, db: Option<&str>, profile: Option<&str>) -> Result<PathBuf> {
+    if let Some(db) = db {
+        return Ok(PathBuf::from(db));
+    }
+    if let Some(profile) = profile {
+        let valid = profile
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if profile.is_empty() || profile.len() > 20 || !valid {
+            return err!(
+                "invalid profile name: {}, use 1 to 20 lowercase letters, digits, - and _",
+                profile
+            );
+        }
+        return Ok(root.join(PROFILES_DIR).join(format!("{profile}.db")));
+    }
+    match util::try_get_env(DATABASE_ENV) {
+        Some(path) if !path.is_empty() => Ok(PathBuf::from(path)),
+        _ => Ok(root.join(DATABASE_FILE)),
+    }
+}
+
+/// Keys of the settings that can be read and written using `todo config`.
+pub const KEYS: [&str; 8] = [
+    "prio", "filter", "sort", "editor", "theme", "columns", "prompts", "backups",
+];
+
+/// Number of automatic backups kept per database unless configured.
+pub const DEFAULT_BACKUPS: usize = 5;
+
+/// Configuration loaded from `config.toml` in the root directory.
+/// Settings that aren't set use their default.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Priority of new todos unless given, defaults to normal.
+    pub prio: Option<String>,
+    /// Filter expression used when listing todos without one,
+    /// e.g. `not tag:someday`.
+    pub filter: Option<String>,
+    /// Default order of listed todos, e.g. `prio,-created`.
+    pub sort: Option<String>,
+    /// Command used to edit todos, instead of $EDITOR or $VISUAL.
+    pub editor: Option<String>,
+    /// How output is styled: default, mono or plain.
+    pub theme: Option<String>,
+    /// Columns of tables of todos, in order.
+    pub columns: Option<Vec<String>>,
+    /// Prompt for values that aren't given as options, defaults to true.
+    pub prompts: Option<bool>,
+    /// Number of automatic backups made before destructive commands that
+    /// are kept per database, where 0 turns them off. Defaults to 5.
+    pub backups: Option<usize>,
+    /// User-defined link types, declared as `[[link]]` tables.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub link: Vec<LinkType>,
+}
+
+impl Config {
+    /// Returns the path of the configuration file, which is given by
+    /// `TODO_CONFIG` if set or else is `config.toml` in the root directory.
+    pub fn path(root: &Path) -> PathBuf {
+        match util::try_get_env(CONFIG_ENV) {
+            Some(path) if !path.is_empty() => PathBuf::from(path),
+            _ => root.join(CONFIG_FILE),
+        }
+    }
+
+    /// Loads the configuration from the file given by `path`.
+    /// The default configuration is used if there's no file.
+    pub fn load(root: &Path) -> Result<Self> {
+        let config = Self::read(root)?;
+        if let Err(e) = config.validate() {
+            return err!("invalid configuration in {:?}: {}", Self::path(root), e);
+        }
+        Ok(config)
+    }
+
+    /// Loads the configuration like `load` without checking the settings,
+    /// so that invalid ones can be fixed using `todo config`.
+    pub fn read(root: &Path) -> Result<Self> {
+        let path = Self::path(root);
+        if !path.exists() {
+            log::debug!("No configuration file found at {:?}", path);
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        let config: Self = match toml::from_str(&content) {
+            Ok(config) => config,
+            Err(e) => return err!("invalid configuration in {:?}: {}", path, e),
+        };
+        log::debug!("Loaded configuration from {:?}", path);
+        Ok(config)
+    }
+
+    /// Writes the configuration to the file given by `path`. The settings
+    /// of an existing file are updated in place, keeping its comments.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let content = if path.exists() {
+            let mut doc = match std::fs::read_to_string(path)?.parse::<Document>() {
+                Ok(doc) => doc,
+                Err(e) => return err!("invalid configuration in {:?}: {}", path, e),
+            };
+            for key in KEYS {
+                match (
+                    doc.get_mut(key).and_then(Item::as_value_mut),
+                    self.value(key),
+                ) {
+                    // Keeps comments on the same line
+                    (Some(old), Some(mut new)) => {
+                        *new.decor_mut() = old.decor().clone();
+                        *old = new;
+                    }
+                    (None, Some(new)) => doc[key] = value(new),
+                    (_, None) => {
+                        doc.remove(key);
+                    }
+                }
+            }
+            doc.to_string()
+        } else {
+            toml::to_string_pretty(self)?
+        };
+        std::fs::write(path, content)?;
+        log::debug!("Saved configuration to {:?}", path);
+        Ok(())
+    }
+
+    /// Checks that all settings are valid, naming the key of the first
+    /// setting that isn't.
+    pub fn validate(&self) -> Result<()> {
+        for key in ["prio", "filter", "sort", "theme", "columns", "link"] {
+            self.check(key)?;
+        }
+        Ok(())
+    }
+
+    // Checks the setting with `key`, if it's one that can be invalid.
+    fn check(&self, key: &str) -> Result<()> {
+        let error = match key {
+            "prio" => self.prio().err(),
+            "filter" => self.filter().err(),
+            "sort" => self.sort().err(),
+            "theme" => self.theme().err(),
+            "columns" => self.columns().err(),
+            "link" => self.link_types().err(),
+            _ => None,
+        };
+        match error {
+            Some(error) => err!("invalid value for '{}': {}", key, error),
+            None => Ok(()),
+        }
+    }
+
+    // Returns the setting with `key` as a TOML value, if set.
+    fn value(&self, key: &str) -> Option<Value> {
+        let v = match key {
+            "columns" => {
+                let columns = self.columns.as_ref()?;
+                Value::Array(columns.iter().collect())
+            }
+            "prompts" => Value::from(self.prompts?),
+            "backups" => Value::from(self.backups? as i64),
+            key => Value::from(self.get(key).ok()??),
+        };
+        Some(v)
+    }
+
+    /// Gets the setting with `key` as a string, if set.
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        let value = match key {
+            "prio" => self.prio.clone(),
+            "filter" => self.filter.clone(),
+            "sort" => self.sort.clone(),
+            "editor" => self.editor.clone(),
+            "theme" => self.theme.clone(),
+            "columns" => self.columns.as_ref().map(|columns| columns.join(",")),
+            "prompts" => self.prompts.map(|yes| yes.to_string()),
+            "backups" => self.backups.map(|n| n.to_string()),
+            _ => return err!("unknown config key: {}", key),
+        };
+        Ok(value)
+    }
+
+    /// Sets the setting with `key` from a string, where an empty string
+    /// removes the setting so that the default is used.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let value = match value.trim() {
+            "" => None,
+            s => Some(s.to_string()),
+        };
+        match key {
+            "prio" => self.prio = value,
+            "filter" => self.filter = value,
+            "sort" => self.sort = value,
+            "editor" => self.editor = value,
+            "theme" => self.theme = value,
+            "columns" => {
+                self.columns = value.map(|s| s.split(',').map(|c| c.trim().to_string()).collect())
+            }
+            "prompts" => {
+                self.prompts = match value.as_deref() {
+                    None => None,
+                    Some("true") => Some(true),
+                    Some("false") => Some(false),
+                    Some(s) => {
+                        return err!("invalid value for 'prompts': {}, expected true or false", s)
+                    }
+                }
+            }
+            "backups" => {
+                self.backups = match value.as_deref().map(str::parse) {
+                    None => None,
+                    Some(Ok(n)) => Some(n),
+                    Some(Err(_)) => {
+                        return err!(
+                            "invalid value for 'backups': {}, expected a number",
+                            value.unwrap()
+                        )
+                    }
+                }
+            }
+            _ => return err!("unknown config key: {}", key),
+        }
+        self.check(key)
+    }
+
+    /// Returns the priority of new todos.
+    pub fn prio(&self) -> Result<Prio> {
+        match self.prio.as_deref() {
+            None => Ok(Prio::Normal),
+            Some(s) if Prio::values().contains(&s) => Prio::try_from(s),
+            Some(s) => err!("invalid prio value: {}", s),
+        }
+    }
+
+    /// Returns the filter expression used when listing todos, if any.
+    pub fn filter(&self) -> Result<Option<Expr>> {
+        match &self.filter {
+            Some(filter) => Ok(Some(Expr::try_from(filter.as_str())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the default sort order of listed todos.
+    pub fn sort(&self) -> Result<Sort> {
+        match &self.sort {
+            Some(sort) => Sort::try_from(sort.as_str()),
+            None => Ok(Sort::default()),
+        }
+    }
+
+    /// Returns the command used to edit todos, if set.
+    pub fn editor(&self) -> Option<&str> {
+        self.editor.as_deref()
+    }
+
+    pub fn theme(&self) -> Result<Theme> {
+        match &self.theme {
+            Some(theme) => Theme::try_from(theme.as_str()),
+            None => Ok(Theme::default()),
+        }
+    }
+
+    /// Returns the columns of tables of todos, in order.
+    pub fn columns(&self) -> Result<Vec<Column>> {
+        match &self.columns {
+            Some(columns) if columns.is_empty() => err!("at least one column is required"),
+            Some(columns) => columns
+                .iter()
+                .map(|c| Column::try_from(c.as_str()))
+                .collect(),
+            None => Ok(Column::all()),
+        }
+    }
+
+    pub fn prompts(&self) -> bool {
+        self.prompts.unwrap_or(true)
+    }
+
+    /// Returns the number of automatic backups to keep.
+    pub fn backups(&self) -> usize {
+        self.backups.unwrap_or(DEFAULT_BACKUPS)
+    }
+
+    /// Returns the registry of the declared link types.
+    pub fn link_types(&self) -> Result<LinkTypes> {
+        LinkTypes::new(self.link.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn database_paths() {
+        let root = Path::new("/home/me/.config/todo");
+        let path = database_path(root, Some("/tmp/test.db"), Some("work")).unwrap();
+        assert_eq!(path, PathBuf::from("/tmp/test.db"));
+
+        let path = database_path(root, None, Some("work")).unwrap();
+        assert_eq!(path, root.join("profiles").join("work.db"));
+
+        for profile in ["", "Work", "../work", "a/b"] {
+            assert!(
+                database_path(root, None, Some(profile)).is_err(),
+                "{}",
+                profile
+            );
+        }
+    }
+
+    #[test]
+    fn parse_links() {
+        let config: Config = toml::from_str(
+            r#"
+            [[link]]
+            name = "implements"
+            inverse = "implemented-by"
+
+            [[link]]
+            name = "mentions"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.link.len(), 2);
+        assert_eq!(config.link[0].inverse.as_deref(), Some("implemented-by"));
+        assert!(!config.link[1].bidirectional);
+        assert!(config.link_types().is_ok());
+    }
+
+    #[test]
+    fn parse_empty() {
+        let config: Config = toml::from_str("").unwrap();
+        assert!(config.link.is_empty());
+        assert_eq!(config.sort().unwrap(), Sort::default());
+    }
+
+    #[test]
+    fn parse_sort() {
+        let config: Config = toml::from_str(r#"sort = "prio, -created""#).unwrap();
+        assert_eq!(config.sort().unwrap().to_string(), "prio,-created");
+
+        let config: Config = toml::from_str(r#"sort = "size""#).unwrap();
+        assert!(config.sort().is_err());
+    }
+
+    #[test]
+    fn parse_unknown_key() {
+        let err = toml::from_str::<Config>(r#"colour = "red""#).unwrap_err();
+        assert!(err.to_string().contains("colour"));
+    }
+
+    #[test]
+    fn validate_names_key() {
+        let config: Config = toml::from_str(
+            r#"
+            prio = "high"
+            columns = ["id", "size"]
+            "#,
+        )
+        .unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("'columns'"), "{}", err);
+    }
+
+    #[test]
+    fn get_and_set() {
+        let mut config = Config::default();
+        config.set("columns", "id, subject").unwrap();
+        config.set("prompts", "false").unwrap();
+        config.set("prio", "high").unwrap();
+        assert_eq!(
+            config.get("columns").unwrap().as_deref(),
+            Some("id,subject")
+        );
+        assert_eq!(config.columns().unwrap(), vec![Column::Id, Column::Subject]);
+        assert!(!config.prompts());
+        assert_eq!(config.prio().unwrap(), Prio::High);
+
+        config.set("prio", "").unwrap();
+        assert_eq!(config.get("prio").unwrap(), None);
+        assert_eq!(config.backups(), DEFAULT_BACKUPS);
+        config.set("backups", "0").unwrap();
+        assert_eq!(config.backups(), 0);
+        assert!(config.set("backups", "-1").is_err());
+        assert!(config.set("theme", "neon").is_err());
+        assert!(config.set("prompts", "maybe").is_err());
+        assert!(config.set("colour", "red").is_err());
+        assert!(config.get("colour").is_err());
+    }
+
+    #[test]
+    fn save_round_trip() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [[link]]
+            name = "mentions"
+            "#,
+        )
+        .unwrap();
+        config.set("filter", "not tag:someday").unwrap();
+        config.set("columns", "id,subject").unwrap();
+
+        let content = toml::to_string_pretty(&config).unwrap();
+        let loaded: Config = toml::from_str(&content).unwrap();
+        assert_eq!(loaded.filter.as_deref(), Some("not tag:someday"));
+        assert_eq!(loaded.columns, config.columns);
+        assert_eq!(loaded.link.len(), 1);
+        assert!(loaded.validate().is_ok());
+    }
+
+    #[test]
+    fn set_with_invalid_setting() {
+        let mut config: Config = toml::from_str("theme = \"neon\"").unwrap();
+        assert!(config.validate().is_err());
+        config.set("prio", "high").unwrap();
+        config.set("theme", "mono").unwrap();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn save_keeps_comments() {
+        let dir = std::env::temp_dir().join(format!("todo-config-{}", rand::random::<u32>()));
+        let path = dir.join(CONFIG_FILE);
+        std::fs::create_dir_all(&dir).unwrap();
+        let content = r#"# Settings of todo
+prio = "low" # for everything
+theme = "mono"
+
+[[link]]
+name = "mentions"
+"#;
+        std::fs::write(&path, content).unwrap();
+
+        let mut config: Config = toml::from_str(content).unwrap();
+        config.set("prio", "high").unwrap();
+        config.set("theme", "").unwrap();
+        config.set("columns", "id,subject").unwrap();
+        config.set("backups", "3").unwrap();
+        config.save(&path).unwrap();
+
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with("# Settings of todo\n"), "{}", saved);
+        assert!(
+            saved.contains("prio = \"high\" # for everything"),
+            "{}",
+            saved
+        );
+        assert!(!saved.contains("theme"), "{}", saved);
+        let loaded: Config = toml::from_str(&saved).unwrap();
+        assert_eq!(loaded.columns, config.columns);
+        assert_eq!(loaded.backups, Some(3));
+        assert_eq!(loaded.link.len(), 1);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
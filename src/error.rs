@@ -24,7 +24,7 @@ macro_rules! err {
     };
     ($fmt:expr, $($e:expr),*) => {
         {
-            let s = format!($fmt, $($e)*);
+            let s = format!($fmt, $($e),*);
             Err($crate::error::Error::new(s))
         }
     };
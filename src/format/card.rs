@@ -10,6 +10,7 @@ pub struct Card {
     heading: Styler,
     bold_white: Styler,
     blue: Styler,
+    red: Styler,
 }
 
 impl Card {
@@ -17,15 +18,18 @@ impl Card {
         let heading: Styler;
         let bold_white: Styler;
         let blue: Styler;
+        let red: Styler;
 
         if color {
             heading = Styler::default().underline(true).bold(true);
             bold_white = Styler::default().bold(true);
             blue = Styler::default().bold(true).fg(Color::Blue);
+            red = Styler::default().bold(true).fg(Color::Red);
         } else {
             heading = Styler::default();
             bold_white = Styler::default();
             blue = Styler::default();
+            red = Styler::default();
         }
 
         Self {
@@ -33,11 +37,17 @@ impl Card {
             heading,
             bold_white,
             blue,
+            red,
         }
     }
 
     /// Format `todo` into a detailed string.
     pub fn format(&self, todo: &Todo) -> String {
+        self.format_with_subtasks(todo, &[])
+    }
+
+    /// Same as `format` but also shows progress of the given subtasks.
+    pub fn format_with_subtasks(&self, todo: &Todo, subtasks: &[Todo]) -> String {
         let mut lines: Vec<String> = Vec::new();
 
         let subject = self.heading.style(&todo.subject);
@@ -62,6 +72,31 @@ impl Card {
         let prio = format!("{}:    {}", self.bold_white.style("Priority"), prio);
         lines.push(prio);
 
+        if let Some(due) = &todo.due {
+            let due = if todo.is_overdue() {
+                self.red.style(&format!("{due} (overdue)"))
+            } else {
+                due.to_string()
+            };
+            lines.push(format!("{}:         {}", self.bold_white.style("Due"), due));
+        }
+
+        if let Some(after) = &todo.after {
+            lines.push(format!(
+                "{}:    {}",
+                self.bold_white.style("Deferred"),
+                after
+            ));
+        }
+
+        if let Some(recurrence) = &todo.recurrence {
+            lines.push(format!(
+                "{}:      {}",
+                self.bold_white.style("Recurs"),
+                recurrence
+            ));
+        }
+
         if let Some(c) = &todo.context {
             let context = format!(
                 "{}:     {}",
@@ -76,6 +111,24 @@ impl Card {
             lines.push(tags);
         }
 
+        if let Some(parent) = &todo.parent {
+            lines.push(format!(
+                "{}:      {}",
+                self.bold_white.style("Parent"),
+                parent
+            ));
+        }
+
+        if !subtasks.is_empty() {
+            let done = subtasks.iter().filter(|t| t.is_done()).count();
+            lines.push(format!(
+                "{}:    {}/{} done",
+                self.bold_white.style("Subtasks"),
+                done,
+                subtasks.len()
+            ));
+        }
+
         if let Some(links) = self.format_links(&todo.links) {
             for link in links {
                 lines.push(link);
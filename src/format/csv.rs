@@ -0,0 +1,150 @@
+use super::record::Record;
+use super::Formatter;
+use crate::model::Todo;
+use crate::service::graph::Impact;
+
+const TODO_HEADER: [&str; 13] = [
+    "id",
+    "created",
+    "status",
+    "prio",
+    "subject",
+    "description",
+    "context",
+    "tags",
+    "links",
+    "due",
+    "after",
+    "recurrence",
+    "parent",
+];
+
+/// CsvFormatter formats items as comma separated values with a header,
+/// where lists such as tags are joined by commas within a single field.
+/// Links are given as `kind:id`.
+#[derive(Default)]
+pub struct CsvFormatter;
+
+impl CsvFormatter {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+// Quotes the field if needed, doubling any quotes within it.
+fn field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn row(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|s| field(s))
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn todo_fields(todo: &Todo) -> Vec<String> {
+    let record = Record::from(todo);
+    let links: Vec<String> = record
+        .links
+        .iter()
+        .map(|link| format!("{}:{}", link.kind, link.id))
+        .collect();
+    vec![
+        record.id.to_string(),
+        record.created,
+        record.status,
+        record.prio,
+        record.subject.to_string(),
+        record.description.to_string(),
+        record.context.unwrap_or_default().to_string(),
+        record.tags.join(","),
+        links.join(","),
+        record.due.unwrap_or_default(),
+        record.after.unwrap_or_default(),
+        record.recurrence.unwrap_or_default(),
+        record.parent.map(|id| id.to_string()).unwrap_or_default(),
+    ]
+}
+
+fn header(names: &[&str]) -> String {
+    names.join(",")
+}
+
+impl Formatter for CsvFormatter {
+    fn todos(&self, todos: &[Todo]) -> String {
+        let mut rows = vec![header(&TODO_HEADER)];
+        rows.extend(todos.iter().map(|todo| row(&todo_fields(todo))));
+        rows.join("\n")
+    }
+
+    fn todo(&self, todo: &Todo) -> String {
+        self.todos(std::slice::from_ref(todo))
+    }
+
+    fn tree(&self, todos: &[Todo]) -> String {
+        self.todos(todos)
+    }
+
+    fn search(&self, todos: &[Todo], _terms: &[String]) -> String {
+        self.todos(todos)
+    }
+
+    fn impact(&self, impact: &[Impact]) -> String {
+        let mut names = TODO_HEADER.to_vec();
+        names.extend(["blocks", "chain", "critical"]);
+        let mut rows = vec![header(&names)];
+        for impact in impact {
+            let mut fields = todo_fields(&impact.todo);
+            fields.push(impact.blocks.to_string());
+            fields.push(impact.chain.to_string());
+            fields.push(impact.critical.to_string());
+            rows.push(row(&fields));
+        }
+        rows.join("\n")
+    }
+
+    fn contexts(&self, contexts: &[String]) -> String {
+        let mut rows = vec![header(&["name"])];
+        rows.extend(contexts.iter().map(|name| field(name)));
+        rows.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Link, Prio, Status, CSV, ID};
+    use chrono::Local;
+
+    #[test]
+    fn format_todos() {
+        let todo = Todo::new(
+            ID::new(1),
+            Local::now(),
+            Status::New,
+            Prio::High,
+            "Write \"spec\", then review".to_string(),
+            "Line 1\nLine 2".to_string(),
+            CSV::new(vec!["feat".to_string(), "api".to_string()]),
+            None,
+            CSV::new(vec![Link::Blocks(ID::new(2)), Link::RelatesTo(ID::new(3))]),
+        );
+        let s = CsvFormatter::new().todo(&todo);
+        let (header, row) = s.split_once('\n').unwrap();
+        assert_eq!(header, TODO_HEADER.join(","));
+        assert_eq!(
+            row,
+            format!(
+                "1,{},new,high,\"Write \"\"spec\"\", then review\",\"Line 1\nLine 2\",,\
+                \"feat,api\",\"blocks:2,relatesto:3\",,,,",
+                todo.created.to_rfc3339()
+            )
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use crate::err;
+use crate::error::Error;
+use crate::model::{Link, Todo};
+use crate::style::StyleDisplay;
+
+/// Supported output formats of a graph.
+pub enum GraphFormat {
+    /// Graphviz DOT language.
+    Dot,
+    /// Mermaid flowchart.
+    Mermaid,
+}
+
+impl TryFrom<&str> for GraphFormat {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "dot" | "graphviz" => Ok(Self::Dot),
+            "mermaid" => Ok(Self::Mermaid),
+            s => err!("invalid graph format: {}", s),
+        }
+    }
+}
+
+/// GraphFormatter formats todos and the links between them as a graph
+/// that can be rendered by other tools. Nodes are filled with the color
+/// of the status and outlined with the color of the priority.
+pub struct GraphFormatter {
+    format: GraphFormat,
+}
+
+// An edge between two todos in the graph.
+struct Edge<'a> {
+    from: &'a Todo,
+    to: &'a Todo,
+    link: Link,
+}
+
+impl GraphFormatter {
+    pub fn new(format: GraphFormat) -> Self {
+        Self { format }
+    }
+
+    /// Formats `todos` as a graph. Only links between the given todos are included.
+    pub fn format(&self, todos: &[Todo]) -> String {
+        let edges = edges(todos);
+        match self.format {
+            GraphFormat::Dot => dot(todos, &edges),
+            GraphFormat::Mermaid => mermaid(todos, &edges),
+        }
+    }
+}
+
+// Collects the edges of the graph. Bi-directional links are stored on both
+// todos, so only one side is used to get a single edge.
+fn edges(todos: &[Todo]) -> Vec<Edge<'_>> {
+    let mut edges = Vec::new();
+    for from in todos {
+        for link in from.links.values() {
+            let to = match todos.iter().find(|t| t.id == link.id()) {
+                Some(to) => to,
+                None => continue,
+            };
+            if is_inverse(from, to, link) {
+                continue;
+            }
+            edges.push(Edge {
+                from,
+                to,
+                link: link.clone(),
+            });
+        }
+    }
+    edges
+}
+
+// Returns true if `link` is the inverse side of a bi-directional link,
+// i.e. `to` has a link back to `from` that is used for the edge instead.
+fn is_inverse(from: &Todo, to: &Todo, link: &Link) -> bool {
+    match link {
+        Link::BlockedBy(_) | Link::DuplicatedBy(_) | Link::Follows(_) => true,
+        Link::Custom { name, .. } => to.links.values().iter().any(|back| match back {
+            Link::Custom { name: other, .. } if back.id() == from.id => {
+                other < name || (other == name && to.id < from.id)
+            }
+            _ => false,
+        }),
+        _ => false,
+    }
+}
+
+fn label(todo: &Todo) -> String {
+    format!("{}: {}", todo.id, todo.subject)
+}
+
+fn fill_color(todo: &Todo) -> &'static str {
+    match todo.status.styler().color() {
+        Some(color) => color.name(),
+        None => "white",
+    }
+}
+
+fn border_color(todo: &Todo) -> &'static str {
+    match todo.prio.styler().color() {
+        Some(color) => color.name(),
+        None => "black",
+    }
+}
+
+fn dot(todos: &[Todo], edges: &[Edge]) -> String {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+
+    let mut lines = vec![
+        "digraph todos {".to_string(),
+        "    node [shape=box, style=\"rounded,filled\", penwidth=2];".to_string(),
+    ];
+    for todo in todos {
+        lines.push(format!(
+            "    {} [label=\"{}\", fillcolor={}, color={}];",
+            todo.id,
+            escape(&label(todo)),
+            fill_color(todo),
+            border_color(todo),
+        ));
+    }
+    for edge in edges {
+        let style = match edge.link {
+            Link::Blocks(_) => "",
+            _ => ", style=dashed, arrowhead=none",
+        };
+        lines.push(format!(
+            "    {} -> {} [label=\"{}\"{}];",
+            edge.from.id,
+            edge.to.id,
+            link_name(&edge.link),
+            style
+        ));
+    }
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+fn mermaid(todos: &[Todo], edges: &[Edge]) -> String {
+    let escape = |s: &str| s.replace('"', "#quot;");
+
+    let mut lines = vec!["flowchart LR".to_string()];
+    for todo in todos {
+        lines.push(format!(
+            "    {}[\"{}\"]",
+            node_id(todo),
+            escape(&label(todo))
+        ));
+    }
+    for edge in edges {
+        let arrow = match edge.link {
+            Link::Blocks(_) => "-->",
+            _ => "-.-",
+        };
+        lines.push(format!(
+            "    {} {}|{}| {}",
+            node_id(edge.from),
+            arrow,
+            link_name(&edge.link),
+            node_id(edge.to)
+        ));
+    }
+    for todo in todos {
+        lines.push(format!(
+            "    style {} fill:{},stroke:{},stroke-width:2px",
+            node_id(todo),
+            fill_color(todo),
+            border_color(todo),
+        ));
+    }
+    lines.join("\n")
+}
+
+// Mermaid node IDs cannot be plain numbers.
+fn node_id(todo: &Todo) -> String {
+    format!("t{}", todo.id)
+}
+
+fn link_name(link: &Link) -> String {
+    link.name().replace(['-', '_'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Prio, Status, CSV, ID};
+    use chrono::Local;
+
+    fn build_todos() -> Vec<Todo> {
+        let todo = |id, status, prio, subject: &str, links| {
+            Todo::new(
+                ID::new(id),
+                Local::now(),
+                status,
+                prio,
+                subject.to_string(),
+                "".to_string(),
+                CSV::default(),
+                None,
+                CSV::new(links),
+            )
+        };
+
+        vec![
+            todo(
+                1,
+                Status::Started,
+                Prio::High,
+                "Write \"spec\"",
+                vec![Link::Blocks(ID::new(2)), Link::RelatesTo(ID::new(9))],
+            ),
+            todo(
+                2,
+                Status::Blocked,
+                Prio::Normal,
+                "Implement",
+                vec![Link::BlockedBy(ID::new(1)), Link::RelatesTo(ID::new(1))],
+            ),
+        ]
+    }
+
+    #[test]
+    fn format_from_str() {
+        assert!(matches!(GraphFormat::try_from("dot"), Ok(GraphFormat::Dot)));
+        assert!(matches!(
+            GraphFormat::try_from("Mermaid"),
+            Ok(GraphFormat::Mermaid)
+        ));
+        assert!(GraphFormat::try_from("svg").is_err());
+    }
+
+    #[test]
+    fn format_dot() {
+        let s = GraphFormatter::new(GraphFormat::Dot).format(&build_todos());
+        assert!(s.starts_with("digraph todos {"));
+        assert!(s.contains("1 [label=\"1: Write \\\"spec\\\"\", fillcolor=blue, color=yellow];"));
+        assert!(s.contains("2 [label=\"2: Implement\", fillcolor=red, color=black];"));
+        assert!(s.contains("1 -> 2 [label=\"blocks\"];"));
+        assert!(s.contains("2 -> 1 [label=\"relates to\", style=dashed, arrowhead=none];"));
+        // Links to todos not in the graph are left out
+        assert!(!s.contains("-> 9"));
+        assert!(s.ends_with('}'));
+    }
+
+    #[test]
+    fn format_mermaid() {
+        let s = GraphFormatter::new(GraphFormat::Mermaid).format(&build_todos());
+        assert!(s.starts_with("flowchart LR"));
+        assert!(s.contains("t1[\"1: Write #quot;spec#quot;\"]"));
+        assert!(s.contains("t1 -->|blocks| t2"));
+        assert!(s.contains("t2 -.-|relates to| t1"));
+        assert!(s.contains("style t2 fill:red,stroke:black,stroke-width:2px"));
+    }
+}
@@ -0,0 +1,133 @@
+use super::record::{ContextRecord, ImpactRecord, Record};
+use super::Formatter;
+use crate::model::Todo;
+use crate::service::graph::Impact;
+use serde::Serialize;
+
+/// JsonFormatter formats items as JSON, either as a single document or
+/// as newline delimited JSON with one item per line.
+pub struct JsonFormatter {
+    lines: bool,
+}
+
+impl JsonFormatter {
+    /// Creates a formatter of a single JSON document.
+    pub fn new() -> Self {
+        Self { lines: false }
+    }
+
+    /// Creates a formatter of newline delimited JSON, i.e. NDJSON.
+    pub fn lines() -> Self {
+        Self { lines: true }
+    }
+
+    fn format<T: Serialize>(&self, items: &[T]) -> String {
+        if self.lines {
+            items
+                .iter()
+                .map(to_string)
+                .collect::<Vec<String>>()
+                .join("\n")
+        } else {
+            serde_json::to_string_pretty(items).expect("serialize records")
+        }
+    }
+}
+
+impl Default for JsonFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_string<T: Serialize>(item: &T) -> String {
+    serde_json::to_string(item).expect("serialize record")
+}
+
+impl Formatter for JsonFormatter {
+    fn todos(&self, todos: &[Todo]) -> String {
+        let records: Vec<Record> = todos.iter().map(Record::from).collect();
+        self.format(&records)
+    }
+
+    fn todo(&self, todo: &Todo) -> String {
+        let record = Record::from(todo);
+        if self.lines {
+            to_string(&record)
+        } else {
+            serde_json::to_string_pretty(&record).expect("serialize record")
+        }
+    }
+
+    fn tree(&self, todos: &[Todo]) -> String {
+        self.todos(todos)
+    }
+
+    fn search(&self, todos: &[Todo], _terms: &[String]) -> String {
+        self.todos(todos)
+    }
+
+    fn impact(&self, impact: &[Impact]) -> String {
+        let records: Vec<ImpactRecord> = impact.iter().map(ImpactRecord::from).collect();
+        self.format(&records)
+    }
+
+    fn contexts(&self, contexts: &[String]) -> String {
+        let records: Vec<ContextRecord> =
+            contexts.iter().map(|name| ContextRecord { name }).collect();
+        self.format(&records)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Link, Prio, Status, CSV, ID};
+    use chrono::{Local, TimeZone};
+
+    fn build_todo() -> Todo {
+        let created = Local.with_ymd_and_hms(2026, 10, 18, 9, 30, 0).unwrap();
+        Todo::new(
+            ID::new(1),
+            created,
+            Status::New,
+            Prio::High,
+            "Write \"spec\"".to_string(),
+            "Line 1\nLine 2".to_string(),
+            CSV::new(vec!["feat".to_string(), "api".to_string()]),
+            Some("work".to_string()),
+            CSV::new(vec![Link::Blocks(ID::new(2))]),
+        )
+    }
+
+    #[test]
+    fn format_todo() {
+        let todo = build_todo();
+        let value: serde_json::Value =
+            serde_json::from_str(&JsonFormatter::new().todo(&todo)).unwrap();
+        assert_eq!(value["id"], 1);
+        assert_eq!(value["created"], todo.created.to_rfc3339());
+        assert_eq!(value["status"], "new");
+        assert_eq!(value["prio"], "high");
+        assert_eq!(value["subject"], "Write \"spec\"");
+        assert_eq!(value["context"], "work");
+        assert_eq!(value["tags"], serde_json::json!(["feat", "api"]));
+        assert_eq!(
+            value["links"],
+            serde_json::json!([{"kind": "blocks", "id": 2}])
+        );
+        assert!(value["due"].is_null());
+    }
+
+    #[test]
+    fn format_lines() {
+        let todos = vec![build_todo(), build_todo()];
+        let s = JsonFormatter::lines().todos(&todos);
+        assert_eq!(s.lines().count(), 2);
+        for line in s.lines() {
+            assert!(serde_json::from_str::<serde_json::Value>(line).is_ok());
+        }
+        assert_eq!(JsonFormatter::lines().todos(&[]), "");
+        assert_eq!(JsonFormatter::new().todos(&[]), "[]");
+    }
+}
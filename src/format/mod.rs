@@ -1,15 +1,82 @@
+use crate::err;
+use crate::error::Error;
 use crate::model::Todo;
+use crate::service::graph::Impact;
 
 pub mod card;
+pub mod csv;
+pub mod graph;
+pub mod json;
+pub mod record;
 pub mod table;
 
 pub use card::Card;
+pub use csv::CsvFormatter;
+pub use graph::{GraphFormat, GraphFormatter};
+pub use json::JsonFormatter;
 pub use table::TableFormatter;
 
+/// Supported output formats of commands.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Output {
+    /// Tables for humans.
+    #[default]
+    Table,
+    /// A JSON document.
+    Json,
+    /// Newline delimited JSON, one item per line.
+    Ndjson,
+    /// Comma separated values with a header.
+    Csv,
+}
+
+impl Output {
+    pub fn values() -> [&'static str; 4] {
+        ["table", "json", "ndjson", "csv"]
+    }
+
+    /// Returns true if the output is meant for other programs, in which
+    /// case nothing but the formatted items should be printed.
+    pub fn is_structured(&self) -> bool {
+        !matches!(self, Output::Table)
+    }
+
+    pub fn formatter(&self) -> Box<dyn Formatter> {
+        match self {
+            Output::Table => Box::new(TableFormatter::new(true)),
+            Output::Json => Box::new(JsonFormatter::new()),
+            Output::Ndjson => Box::new(JsonFormatter::lines()),
+            Output::Csv => Box::new(CsvFormatter::new()),
+        }
+    }
+}
+
+impl TryFrom<&str> for Output {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "table" => Ok(Self::Table),
+            "json" => Ok(Self::Json),
+            "ndjson" => Ok(Self::Ndjson),
+            "csv" => Ok(Self::Csv),
+            s => err!("invalid output format: {}", s),
+        }
+    }
+}
+
 /// Formatter is used to format items in a user-friendly way.
 pub trait Formatter {
     /// Format a slice of todos nicely.
     fn todos(&self, todos: &[Todo]) -> String;
     /// Format a single Todo.
     fn todo(&self, todo: &Todo) -> String;
+    /// Format a slice of todos with subtasks placed under their parent.
+    fn tree(&self, todos: &[Todo]) -> String;
+    /// Format todos found by a search, highlighting the search terms.
+    fn search(&self, todos: &[Todo], terms: &[String]) -> String;
+    /// Format the impact of todos on other todos.
+    fn impact(&self, impact: &[Impact]) -> String;
+    /// Format the names of contexts.
+    fn contexts(&self, contexts: &[String]) -> String;
 }
@@ -0,0 +1,87 @@
+use crate::model::{Todo, ID};
+use crate::service::graph::Impact;
+use serde::Serialize;
+
+/// A todo as emitted by the machine-readable output formats. Field names
+/// are kept stable, so change with care.
+#[derive(Debug, Serialize)]
+pub struct Record<'a> {
+    pub id: ID,
+    /// RFC 3339, e.g. `2026-10-18T09:30:00+02:00`.
+    pub created: String,
+    pub status: String,
+    pub prio: String,
+    pub subject: &'a str,
+    pub description: &'a str,
+    pub context: Option<&'a str>,
+    pub tags: Vec<&'a str>,
+    pub links: Vec<LinkRecord>,
+    /// YYYY-MM-DD.
+    pub due: Option<String>,
+    /// YYYY-MM-DD.
+    pub after: Option<String>,
+    pub recurrence: Option<String>,
+    pub parent: Option<ID>,
+}
+
+/// A link to another todo, e.g. `blocks` todo 2.
+#[derive(Debug, Serialize)]
+pub struct LinkRecord {
+    pub kind: String,
+    pub id: ID,
+}
+
+/// The impact of a todo along with the todo itself.
+#[derive(Debug, Serialize)]
+pub struct ImpactRecord<'a> {
+    #[serde(flatten)]
+    pub todo: Record<'a>,
+    pub blocks: usize,
+    pub chain: usize,
+    pub critical: bool,
+}
+
+impl<'a> From<&'a Todo> for Record<'a> {
+    fn from(todo: &'a Todo) -> Self {
+        Self {
+            id: todo.id,
+            created: todo.created.to_rfc3339(),
+            status: todo.status.to_string(),
+            prio: todo.prio.to_string(),
+            subject: &todo.subject,
+            description: &todo.description,
+            context: todo.context.as_deref(),
+            tags: todo.tags.values().iter().map(String::as_str).collect(),
+            links: todo
+                .links
+                .values()
+                .iter()
+                .map(|link| LinkRecord {
+                    kind: link.kind().to_string(),
+                    id: link.id(),
+                })
+                .collect(),
+            due: todo.due.map(|date| date.to_string()),
+            after: todo.after.map(|date| date.to_string()),
+            recurrence: todo.recurrence.as_ref().map(|r| r.to_string()),
+            parent: todo.parent,
+        }
+    }
+}
+
+impl<'a> From<&'a Impact> for ImpactRecord<'a> {
+    fn from(impact: &'a Impact) -> Self {
+        Self {
+            todo: Record::from(&impact.todo),
+            blocks: impact.blocks,
+            chain: impact.chain,
+            critical: impact.critical,
+        }
+    }
+}
+
+/// A context as emitted by the machine-readable output formats.
+#[derive(Debug, Serialize)]
+pub struct ContextRecord<'a> {
+    pub name: &'a str,
+}
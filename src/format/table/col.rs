@@ -1,6 +1,6 @@
 use super::{pad, Align};
-use crate::style::{StyleDisplay, Styler};
-use crate::util::word_chunks;
+use crate::style::{Color, StyleDisplay, Styler};
+use crate::util::{matches_term, word_chunks};
 
 // Col represents a column in a table that spans
 // one or more lines, depending on `width`.
@@ -8,6 +8,8 @@ pub struct Col {
     width: usize,
     lines: Vec<String>,
     styler: Styler,
+    /// Search terms to highlight.
+    terms: Vec<String>,
 }
 
 impl Col {
@@ -22,9 +24,16 @@ impl Col {
             width,
             lines,
             styler: content.styler(),
+            terms: Vec::new(),
         }
     }
 
+    // Highlights words matching any of the search terms.
+    pub fn with_highlight(mut self, terms: &[String]) -> Self {
+        self.terms = terms.to_vec();
+        self
+    }
+
     // Returns the number of lines this column spans.
     pub fn height(&self) -> usize {
         self.lines.len()
@@ -35,8 +44,52 @@ impl Col {
     // string with length `self.width`.
     pub fn nth(&self, n: usize) -> String {
         match self.lines.get(n) {
-            Some(line) => self.styler.style(line),
+            Some(line) if self.terms.is_empty() => self.styler.style(line),
+            Some(line) => self.highlight(line),
             None => " ".repeat(self.width),
         }
     }
+
+    // Styles `line` word by word, where matching words are highlighted.
+    fn highlight(&self, line: &str) -> String {
+        let mark = Styler::default().bold(true).fg(Color::Yellow);
+        let style = |part: &str, word: bool| {
+            if part.is_empty() {
+                String::new()
+            } else if word && matches_term(part, &self.terms) {
+                mark.style(part)
+            } else {
+                self.styler.style(part)
+            }
+        };
+
+        let mut styled = String::new();
+        let mut start = 0;
+        let mut word = false;
+        for (i, c) in line.char_indices() {
+            if c.is_alphanumeric() != word {
+                styled.push_str(&style(&line[start..i], word));
+                start = i;
+                word = !word;
+            }
+        }
+        styled.push_str(&style(&line[start..], word));
+        styled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn highlight_matching_words() {
+        let terms = vec!["dep*".to_string()];
+        let subject = "Deploy, then test".to_string();
+        let col = Col::new(20, &subject, Align::Left).with_highlight(&terms);
+        let line = col.nth(0);
+        let mark = Styler::default().bold(true).fg(Color::Yellow);
+        assert!(line.starts_with(&mark.style("Deploy")));
+        assert!(line.ends_with(", then test   "));
+    }
 }
@@ -1,6 +1,10 @@
 use super::Formatter;
+use crate::err;
+use crate::error::Error;
 use crate::model::Todo;
-use crate::style::{StyleDisplay, Styler};
+use crate::service::graph::Impact;
+use crate::style::{Color, StyleDisplay, Styler};
+use chrono::Local;
 use core::fmt;
 
 mod col;
@@ -12,8 +16,11 @@ use col::Col;
 const ID_COL_WIDTH: usize = 3;
 const PRIO_COL_WIDTH: usize = 10;
 const STATUS_COL_WIDTH: usize = 8;
+const DUE_COL_WIDTH: usize = 10;
 const CTX_COL_WIDTH: usize = 12;
 const SUBJECT_COL_WIDTH: usize = 80;
+const COUNT_COL_WIDTH: usize = 6;
+const CRITICAL_COL_WIDTH: usize = 8;
 
 /// Specifies how to align the content.
 pub enum Align {
@@ -56,43 +63,195 @@ fn truncate(s: &str, size: usize) -> String {
     }
 }
 
+/// A column of the table of todos.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Column {
+    Id,
+    Prio,
+    Status,
+    Due,
+    Context,
+    Subject,
+}
+
+impl Column {
+    pub fn values() -> [&'static str; 6] {
+        ["id", "prio", "status", "due", "context", "subject"]
+    }
+
+    /// All columns in the default order.
+    pub fn all() -> Vec<Column> {
+        vec![
+            Column::Id,
+            Column::Prio,
+            Column::Status,
+            Column::Due,
+            Column::Context,
+            Column::Subject,
+        ]
+    }
+
+    fn width(&self) -> usize {
+        match self {
+            Column::Id => ID_COL_WIDTH,
+            Column::Prio => PRIO_COL_WIDTH,
+            Column::Status => STATUS_COL_WIDTH,
+            Column::Due => DUE_COL_WIDTH,
+            Column::Context => CTX_COL_WIDTH,
+            Column::Subject => SUBJECT_COL_WIDTH,
+        }
+    }
+
+    fn header(&self) -> Header {
+        match self {
+            Column::Id => Header::from(" ID"),
+            Column::Prio => Header::from("Priority"),
+            Column::Status => Header::from("Status"),
+            Column::Due => Header::from("Due"),
+            Column::Context => Header::from("Context"),
+            Column::Subject => Header::from("Subject"),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Column::Id => "id",
+            Column::Prio => "prio",
+            Column::Status => "status",
+            Column::Due => "due",
+            Column::Context => "context",
+            Column::Subject => "subject",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl TryFrom<&str> for Column {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "id" => Ok(Column::Id),
+            "prio" | "priority" => Ok(Column::Prio),
+            "status" => Ok(Column::Status),
+            "due" => Ok(Column::Due),
+            "context" | "ctx" => Ok(Column::Context),
+            "subject" => Ok(Column::Subject),
+            _ => err!(
+                "invalid column: {}, expected one of {}",
+                value,
+                Column::values().join(", ")
+            ),
+        }
+    }
+}
+
 /// Formats a list of todos into a table.
-pub struct TableFormatter {}
+pub struct TableFormatter {
+    columns: Vec<Column>,
+}
 
 impl TableFormatter {
     pub fn new(_color: bool) -> Self {
-        Self {}
+        Self {
+            columns: Column::all(),
+        }
+    }
+
+    /// Sets the columns of the table of todos, in order.
+    pub fn with_columns(mut self, columns: Vec<Column>) -> Self {
+        self.columns = columns;
+        self
     }
 
     fn todo_table_header(&self) -> String {
+        let header: Vec<Col> = self
+            .columns
+            .iter()
+            .map(|column| Col::new(column.width(), &column.header(), Align::Left))
+            .collect();
+
+        format_row(&header)
+    }
+
+    fn impact_table_header(&self) -> String {
         let id = Header::from(" ID");
         let prio = Header::from("Priority");
         let status = Header::from("Status");
-        let ctx = Header::from("Context");
+        let blocks = Header::from("Blocks");
+        let chain = Header::from("Chain");
+        let critical = Header::from("Critical");
         let subject = Header::from("Subject");
 
         let header = vec![
             Col::new(ID_COL_WIDTH, &id, Align::Left),
             Col::new(PRIO_COL_WIDTH, &prio, Align::Left),
             Col::new(STATUS_COL_WIDTH, &status, Align::Left),
-            Col::new(CTX_COL_WIDTH, &ctx, Align::Left),
+            Col::new(COUNT_COL_WIDTH, &blocks, Align::Left),
+            Col::new(COUNT_COL_WIDTH, &chain, Align::Left),
+            Col::new(CRITICAL_COL_WIDTH, &critical, Align::Left),
             Col::new(SUBJECT_COL_WIDTH, &subject, Align::Left),
         ];
 
         format_row(&header)
     }
 
-    // ID | Prio | Status | Context | Subject
-    fn map_todo(todo: &Todo) -> Vec<Col> {
-        let id = Col::new(ID_COL_WIDTH, &format!(" {}", todo.id), Align::Left);
-        let prio = Col::new(PRIO_COL_WIDTH, &todo.prio, Align::Left);
-        let status = Col::new(STATUS_COL_WIDTH, &todo.status, Align::Left);
-        let subject = Col::new(SUBJECT_COL_WIDTH, &todo.subject, Align::Left);
-        let context = match &todo.context {
-            Some(cx) => Col::new(CTX_COL_WIDTH, &truncate(cx, CTX_COL_WIDTH), Align::Left),
-            None => Col::new(CTX_COL_WIDTH, &"".to_string(), Align::Left),
-        };
-        vec![id, prio, status, context, subject]
+    // ID | Prio | Status | Blocks | Chain | Critical | Subject
+    fn map_impact(impact: &Impact) -> Vec<Col> {
+        let todo = &impact.todo;
+        vec![
+            Col::new(ID_COL_WIDTH, &format!(" {}", todo.id), Align::Left),
+            Col::new(PRIO_COL_WIDTH, &todo.prio, Align::Left),
+            Col::new(STATUS_COL_WIDTH, &todo.status, Align::Left),
+            Col::new(COUNT_COL_WIDTH, &impact.blocks.to_string(), Align::Center),
+            Col::new(COUNT_COL_WIDTH, &impact.chain.to_string(), Align::Center),
+            Col::new(
+                CRITICAL_COL_WIDTH,
+                &Critical(impact.critical),
+                Align::Center,
+            ),
+            Col::new(SUBJECT_COL_WIDTH, &todo.subject, Align::Left),
+        ]
+    }
+
+    // ID | Prio | Status | Due | Context | Subject, by default
+    fn map_todo(&self, todo: &Todo) -> Vec<Col> {
+        self.map_subtask(todo, 0)
+    }
+
+    // Same as map_todo but highlights the search terms in the subject.
+    fn map_match(&self, todo: &Todo, terms: &[String]) -> Vec<Col> {
+        self.columns
+            .iter()
+            .zip(self.map_todo(todo))
+            .map(|(column, col)| match column {
+                Column::Subject => col.with_highlight(terms),
+                _ => col,
+            })
+            .collect()
+    }
+
+    // Same as map_todo but indents the subject by `depth` levels.
+    fn map_subtask(&self, todo: &Todo, depth: usize) -> Vec<Col> {
+        self.columns
+            .iter()
+            .map(|column| match column {
+                Column::Id => Col::new(ID_COL_WIDTH, &format!(" {}", todo.id), Align::Left),
+                Column::Prio => Col::new(PRIO_COL_WIDTH, &todo.prio, Align::Left),
+                Column::Status => Col::new(STATUS_COL_WIDTH, &todo.status, Align::Left),
+                Column::Due => Col::new(DUE_COL_WIDTH, &Due(todo), Align::Left),
+                Column::Context => match &todo.context {
+                    Some(cx) => Col::new(CTX_COL_WIDTH, &truncate(cx, CTX_COL_WIDTH), Align::Left),
+                    None => Col::new(CTX_COL_WIDTH, &"".to_string(), Align::Left),
+                },
+                Column::Subject => {
+                    let subject = format!("{}{}", "  ".repeat(depth), todo.subject);
+                    Col::new(SUBJECT_COL_WIDTH, &subject, Align::Left)
+                }
+            })
+            .collect()
     }
 }
 
@@ -100,7 +259,7 @@ impl Formatter for TableFormatter {
     fn todos(&self, todos: &[Todo]) -> String {
         let table = todos
             .iter()
-            .map(Self::map_todo)
+            .map(|todo| self.map_todo(todo))
             .map(|cols| format_row(&cols))
             .collect::<Vec<String>>()
             .join("\n");
@@ -109,8 +268,78 @@ impl Formatter for TableFormatter {
     }
 
     fn todo(&self, todo: &Todo) -> String {
-        self.todos(&[todo.clone()])
+        self.todos(std::slice::from_ref(todo))
     }
+
+    fn search(&self, todos: &[Todo], terms: &[String]) -> String {
+        let table = todos
+            .iter()
+            .map(|todo| self.map_match(todo, terms))
+            .map(|cols| format_row(&cols))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        format!("{}\n{}", self.todo_table_header(), table)
+    }
+
+    fn impact(&self, impact: &[Impact]) -> String {
+        let table = impact
+            .iter()
+            .map(Self::map_impact)
+            .map(|cols| format_row(&cols))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        format!("{}\n{}", self.impact_table_header(), table)
+    }
+
+    fn contexts(&self, contexts: &[String]) -> String {
+        contexts.join("\n")
+    }
+
+    fn tree(&self, todos: &[Todo]) -> String {
+        let table = tree_order(todos)
+            .into_iter()
+            .map(|(depth, todo)| self.map_subtask(todo, depth))
+            .map(|cols| format_row(&cols))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        format!("{}\n{}", self.todo_table_header(), table)
+    }
+}
+
+// Orders `todos` so that subtasks follow their parent, together with the depth
+// of each todo. Todos whose parent isn't in `todos` are placed at the top level.
+fn tree_order(todos: &[Todo]) -> Vec<(usize, &Todo)> {
+    let is_root = |todo: &Todo| match &todo.parent {
+        Some(parent) => !todos.iter().any(|t| &t.id == parent),
+        None => true,
+    };
+
+    let mut ordered: Vec<(usize, &Todo)> = Vec::new();
+    let mut stack: Vec<(usize, &Todo)> = todos
+        .iter()
+        .filter(|t| is_root(t))
+        .map(|t| (0, t))
+        .rev()
+        .collect();
+    while let Some((depth, todo)) = stack.pop() {
+        if ordered.iter().any(|(_, t)| t.id == todo.id) {
+            continue;
+        }
+        ordered.push((depth, todo));
+        let subtasks = todos.iter().filter(|t| t.parent == Some(todo.id)).rev();
+        stack.extend(subtasks.map(|t| (depth + 1, t)));
+    }
+
+    // Subtasks in a cycle are never reached from any root
+    for todo in todos {
+        if !ordered.iter().any(|(_, t)| t.id == todo.id) {
+            ordered.push((0, todo));
+        }
+    }
+    ordered
 }
 
 pub fn format_row(cols: &[Col]) -> String {
@@ -149,3 +378,46 @@ impl StyleDisplay for Header {
         Styler::default().bold(true)
     }
 }
+
+// Displays the due date of a todo, highlighted if it's overdue or due today.
+struct Due<'a>(&'a Todo);
+
+impl fmt::Display for Due<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0.due {
+            Some(due) => write!(f, "{}", due),
+            None => write!(f, ""),
+        }
+    }
+}
+
+impl StyleDisplay for Due<'_> {
+    fn styler(&self) -> Styler {
+        let styler = Styler::default();
+        if self.0.is_overdue() {
+            styler.fg(Color::Red)
+        } else if self.0.due == Some(Local::now().date_naive()) {
+            styler.fg(Color::Yellow)
+        } else {
+            styler
+        }
+    }
+}
+
+// Marks todos on the critical path.
+struct Critical(bool);
+
+impl fmt::Display for Critical {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            true => write!(f, "*"),
+            false => write!(f, ""),
+        }
+    }
+}
+
+impl StyleDisplay for Critical {
+    fn styler(&self) -> Styler {
+        Styler::default().bold(true).fg(Color::Red)
+    }
+}
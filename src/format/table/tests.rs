@@ -14,11 +14,57 @@ fn test_format_todos() {
 fn test_format_todo() {
     let f = TableFormatter::new(true);
     let todos = build_todos();
-    let todo = todos.get(0).unwrap();
+    let todo = todos.first().unwrap();
     let s = f.todo(todo);
     assert!(!s.is_empty());
 }
 
+#[test]
+fn test_format_columns() {
+    let f = TableFormatter::new(true).with_columns(vec![Column::Subject, Column::Id]);
+    let todos = build_todos();
+    let s = f.todo(&todos[0]);
+    let header = s.lines().next().unwrap();
+    assert!(header.contains("Subject"));
+    assert!(!header.contains("Priority"));
+    assert!(s.find("Subject").unwrap() < s.find("ID").unwrap());
+    assert!(Column::try_from("size").is_err());
+}
+
+#[test]
+fn test_tree_order() {
+    let todos = build_todos();
+    let child = todos[0].clone().with_parent(Some(todos[2].id));
+    let todos = vec![child, todos[1].clone(), todos[2].clone()];
+
+    let ordered: Vec<(usize, String)> = tree_order(&todos)
+        .into_iter()
+        .map(|(depth, todo)| (depth, todo.id.to_string()))
+        .collect();
+    let expected = [(0, "2"), (0, "3"), (1, "1")];
+    assert_eq!(ordered.len(), expected.len());
+    for ((depth, id), (exp_depth, exp_id)) in ordered.iter().zip(expected) {
+        assert_eq!(*depth, exp_depth);
+        assert_eq!(id, exp_id);
+    }
+}
+
+#[test]
+fn test_format_impact() {
+    let f = TableFormatter::new(true);
+    let impact: Vec<Impact> = build_todos()
+        .into_iter()
+        .map(|todo| Impact {
+            todo,
+            blocks: 0,
+            chain: 1,
+            critical: false,
+        })
+        .collect();
+    let s = f.impact(&impact);
+    assert_eq!(s.lines().count(), impact.len() + 1);
+}
+
 fn build_todos() -> Vec<Todo> {
     let dt = Local::now();
 
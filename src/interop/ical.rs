@@ -0,0 +1,548 @@
+use super::{link_blocks, start_of_day, uuid};
+use crate::err;
+use crate::error::Result;
+use crate::model::{Code, Link, Prio, Recurrence, Status, Todo, CSV, ID};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use std::collections::HashMap;
+
+const TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const DATE_FORMAT: &str = "%Y%m%d";
+
+// Lines are folded to at most this many octets.
+const LINE_LIMIT: usize = 75;
+
+/// A property of a component, e.g. `DUE;VALUE=DATE:20261020`.
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Formats the todos as a VCALENDAR with one VTODO per todo.
+pub fn export(todos: &[Todo]) -> String {
+    let uids: HashMap<ID, String> = todos.iter().map(|todo| (todo.id, uuid(todo))).collect();
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:-//todo//todo {}//EN", env!("CARGO_PKG_VERSION")),
+    ];
+    for todo in todos {
+        lines.extend(format_todo(todo, &uids));
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines
+        .iter()
+        .map(|line| fold(line))
+        .collect::<Vec<String>>()
+        .join("\r\n")
+}
+
+/// Parses the VTODOs of a VCALENDAR, skipping those that are cancelled.
+/// The todos get IDs in the order of the VTODOs and keep their UIDs.
+pub fn import(s: &str) -> Result<Vec<Todo>> {
+    let mut components = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    // Depth of components within the VTODO, e.g. a VALARM.
+    let mut nested = 0;
+    for line in unfold(s) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let property = parse_property(&line)?;
+        let value = property.value.to_uppercase();
+        match (property.name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value == "VTODO" => current = Some(Vec::new()),
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if value == "VTODO" => components.extend(current.take()),
+            (_, Some(properties)) if nested == 0 => properties.push(property),
+            _ => {}
+        }
+    }
+    components.retain(|properties: &Vec<Property>| {
+        !properties
+            .iter()
+            .any(|p| p.name == "STATUS" && p.value.eq_ignore_ascii_case("CANCELLED"))
+    });
+
+    let mut ids = HashMap::new();
+    let mut uids = HashMap::new();
+    for (i, properties) in components.iter().enumerate() {
+        let id = ID::try_from((i + 1).to_string().as_str())?;
+        if let Some(uid) = properties.iter().find(|p| p.name == "UID") {
+            ids.insert(uid.value.as_str(), id);
+            uids.insert(id, uid.value.clone());
+        }
+    }
+
+    let mut todos = Vec::new();
+    for (i, properties) in components.iter().enumerate() {
+        let id = ID::try_from((i + 1).to_string().as_str())?;
+        match parse_todo(id, properties, &ids) {
+            Ok(todo) => todos.push(todo.with_uid(uids.get(&id).cloned())),
+            Err(e) => return err!("invalid VTODO {}: {}", i + 1, e),
+        }
+    }
+    link_blocks(&mut todos);
+    Ok(todos)
+}
+
+fn format_todo(todo: &Todo, uids: &HashMap<ID, String>) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VTODO".to_string(),
+        format!("UID:{}", uids[&todo.id]),
+        // The creation time keeps exports of the same todos the same.
+        format!("DTSTAMP:{}", format_time(todo.created)),
+        format!("CREATED:{}", format_time(todo.created)),
+        format!("SUMMARY:{}", escape(&todo.subject)),
+    ];
+    if !todo.description.is_empty() {
+        lines.push(format!("DESCRIPTION:{}", escape(&todo.description)));
+    }
+    lines.push(format!("STATUS:{}", status_name(&todo.status)));
+    lines.push(format!("PRIORITY:{}", prio_number(&todo.prio)));
+    if !todo.tags.is_empty() {
+        let tags: Vec<String> = todo.tags.values().iter().map(|tag| escape(tag)).collect();
+        lines.push(format!("CATEGORIES:{}", tags.join(",")));
+    }
+    if let Some(due) = todo.due {
+        lines.push(format!("DUE;VALUE=DATE:{}", due.format(DATE_FORMAT)));
+    }
+    if let Some(after) = todo.after {
+        lines.push(format!("DTSTART;VALUE=DATE:{}", after.format(DATE_FORMAT)));
+    }
+    if todo.is_done() {
+        let completed = todo.completed.unwrap_or(todo.created);
+        lines.push(format!("COMPLETED:{}", format_time(completed)));
+    }
+    if let Some(uid) = todo.parent.and_then(|parent| uids.get(&parent)) {
+        lines.push(format!("RELATED-TO;RELTYPE=PARENT:{}", uid));
+    }
+    for link in todo.links.values() {
+        if let Some(uid) = uids.get(&link.id()) {
+            let kind = match link {
+                Link::BlockedBy(_) => "DEPENDS-ON".to_string(),
+                link => format!("X-{}", link.kind().to_uppercase()),
+            };
+            lines.push(format!("RELATED-TO;RELTYPE={}:{}", kind, uid));
+        }
+    }
+    if let Some(context) = &todo.context {
+        lines.push(format!("X-TODO-CONTEXT:{}", escape(context)));
+    }
+    if let Some(recurrence) = &todo.recurrence {
+        lines.push(format!(
+            "X-TODO-RECURRENCE:{}",
+            escape(&recurrence.encode())
+        ));
+    }
+    lines.push("END:VTODO".to_string());
+    lines
+}
+
+fn parse_todo(id: ID, properties: &[Property], ids: &HashMap<&str, ID>) -> Result<Todo> {
+    let mut created = None;
+    let mut stamp = None;
+    let mut status = Status::New;
+    let mut prio = Prio::Normal;
+    let mut subject = String::new();
+    let mut description = String::new();
+    let mut tags = CSV::empty();
+    let mut context = None;
+    let mut links = CSV::empty();
+    let mut parent = None;
+    let mut due = None;
+    let mut after = None;
+    let mut completed = None;
+    let mut recurrence = None;
+    for property in properties {
+        let value = property.value.as_str();
+        match property.name.as_str() {
+            "CREATED" => created = Some(parse_time(value)?),
+            "DTSTAMP" => stamp = Some(parse_time(value)?),
+            "SUMMARY" => subject = unescape(value),
+            "DESCRIPTION" => description = unescape(value),
+            "STATUS" => status = parse_status(value),
+            "PRIORITY" => prio = parse_prio(value),
+            "CATEGORIES" => {
+                for tag in split_list(value) {
+                    tags.push_not_exists(tag);
+                }
+            }
+            "DUE" => due = Some(parse_time(value)?.date_naive()),
+            "DTSTART" => after = Some(parse_time(value)?.date_naive()),
+            "COMPLETED" => completed = Some(parse_time(value)?),
+            "RELATED-TO" => {
+                let related = match ids.get(value) {
+                    Some(id) => *id,
+                    None => {
+                        log::warn!("Dropped relation to unknown UID {}", value);
+                        continue;
+                    }
+                };
+                match property.param("RELTYPE").unwrap_or("PARENT") {
+                    "PARENT" => parent = Some(related),
+                    "CHILD" => {}
+                    "DEPENDS-ON" => links.push_not_exists(Link::BlockedBy(related)),
+                    kind => match kind.strip_prefix("X-") {
+                        Some(kind) => links.push_not_exists(Link::decode(&format!(
+                            "{}:{}",
+                            kind.to_lowercase(),
+                            related
+                        ))?),
+                        None => links.push_not_exists(Link::RelatesTo(related)),
+                    },
+                }
+            }
+            "X-TODO-CONTEXT" => context = Some(unescape(value)),
+            "X-TODO-RECURRENCE" => recurrence = Some(Recurrence::decode(&unescape(value))?),
+            _ => {}
+        }
+    }
+
+    let todo = Todo::new(
+        id,
+        created.or(stamp).unwrap_or_else(Local::now),
+        status.clone(),
+        prio,
+        subject,
+        description,
+        tags,
+        context,
+        links,
+    )
+    .with_due(due)
+    .with_after(after)
+    .with_recurrence(recurrence)
+    .with_parent(parent)
+    .with_completed(completed.filter(|_| status == Status::Done));
+    Ok(todo)
+}
+
+fn status_name(status: &Status) -> &'static str {
+    match status {
+        Status::New | Status::Blocked => "NEEDS-ACTION",
+        Status::Started => "IN-PROCESS",
+        Status::Done => "COMPLETED",
+    }
+}
+
+fn parse_status(s: &str) -> Status {
+    match s.to_uppercase().as_str() {
+        "IN-PROCESS" => Status::Started,
+        "COMPLETED" => Status::Done,
+        _ => Status::New,
+    }
+}
+
+// Priorities range from 1 (highest) to 9 (lowest), where 0 is undefined.
+fn prio_number(prio: &Prio) -> u8 {
+    match prio {
+        Prio::Critical => 1,
+        Prio::High => 3,
+        Prio::Normal => 5,
+        Prio::Low => 7,
+    }
+}
+
+fn parse_prio(s: &str) -> Prio {
+    match s.trim().parse::<u8>() {
+        Ok(1) => Prio::Critical,
+        Ok(2..=4) => Prio::High,
+        Ok(6..=9) => Prio::Low,
+        _ => Prio::Normal,
+    }
+}
+
+fn format_time(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc).format(TIME_FORMAT).to_string()
+}
+
+// Parses a date, a time in UTC or a floating time, which is taken as
+// local time. Time zones given by TZID are not supported.
+fn parse_time(s: &str) -> Result<DateTime<Local>> {
+    if let Ok(date) = NaiveDate::parse_from_str(s, DATE_FORMAT) {
+        return Ok(start_of_day(date));
+    }
+    if let Ok(time) = NaiveDateTime::parse_from_str(s, TIME_FORMAT) {
+        return Ok(Utc.from_utc_datetime(&time).with_timezone(&Local));
+    }
+    let time = NaiveDateTime::parse_from_str(s, "%Y%m%dT%H%M%S")
+        .ok()
+        .and_then(|time| Local.from_local_datetime(&time).earliest());
+    match time {
+        Some(time) => Ok(time),
+        None => err!("invalid time: {}", s),
+    }
+}
+
+// Parses a content line, `NAME;PARAM=VALUE:value`.
+fn parse_property(line: &str) -> Result<Property> {
+    let mut quoted = false;
+    let colon = line.char_indices().find(|(_, c)| {
+        if *c == '"' {
+            quoted = !quoted;
+        }
+        *c == ':' && !quoted
+    });
+    let (head, value) = match colon {
+        Some((i, _)) => (&line[..i], &line[i + 1..]),
+        None => return err!("invalid line: {}", line),
+    };
+
+    let mut parts = head.split(';');
+    let name = parts.next().unwrap_or_default().trim().to_uppercase();
+    let params = parts
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.to_uppercase(), value.trim_matches('"').to_uppercase()))
+        .collect();
+    Ok(Property {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+// Folds the line into lines of at most LINE_LIMIT octets, where the lines
+// after the first start with a space.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded
+}
+
+fn unfold(s: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in s.lines() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\r', "")
+        .replace('\n', "\\n")
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match (c, c == '\\') {
+            (_, true) => match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => unescaped.push('\\'),
+            },
+            (c, false) => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+// Splits a list of values on the commas that aren't escaped.
+fn split_list(s: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ',' => {
+                values.push(unescape(&s[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    values.push(unescape(&s[start..]));
+    values.retain(|value| !value.is_empty());
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interop::Import;
+    use chrono::TimeZone;
+
+    fn build_todos() -> Vec<Todo> {
+        let created = Local.with_ymd_and_hms(2026, 10, 18, 9, 30, 0).unwrap();
+        let spec = Todo::new(
+            ID::new(1),
+            created,
+            Status::Started,
+            Prio::Critical,
+            "Write spec; then review".to_string(),
+            "Line 1\nLine 2, done".to_string(),
+            CSV::new(vec!["feat".to_string(), "a,b".to_string()]),
+            Some("work".to_string()),
+            CSV::new(vec![Link::Blocks(ID::new(2))]),
+        )
+        .with_due(NaiveDate::from_ymd_opt(2026, 10, 20))
+        .with_recurrence(Some(Recurrence::Weekly(vec![
+            chrono::Weekday::Mon,
+            chrono::Weekday::Fri,
+        ])));
+        let review = Todo::new(
+            ID::new(2),
+            created,
+            Status::Blocked,
+            Prio::Low,
+            "Review".to_string(),
+            "".to_string(),
+            CSV::empty(),
+            None,
+            CSV::new(vec![
+                Link::BlockedBy(ID::new(1)),
+                Link::RelatesTo(ID::new(3)),
+            ]),
+        )
+        .with_parent(Some(ID::new(1)));
+        let done = Todo::new(
+            ID::new(3),
+            created,
+            Status::Done,
+            Prio::Normal,
+            "A very long subject that goes on and on, well past the limit of a line".to_string(),
+            "".to_string(),
+            CSV::empty(),
+            None,
+            CSV::new(vec![Link::RelatesTo(ID::new(2))]),
+        )
+        .with_completed(Some(created));
+        vec![spec, review, done]
+    }
+
+    #[test]
+    fn export_todos() {
+        let todos = build_todos();
+        let s = export(&todos);
+        assert!(s.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(s.ends_with("END:VTODO\r\nEND:VCALENDAR"));
+        assert!(s.lines().all(|line| line.len() <= LINE_LIMIT + 1));
+        assert_eq!(s.matches("BEGIN:VTODO").count(), 3);
+
+        let lines: Vec<String> = unfold(&s);
+        let uid = uuid(&todos[0]);
+        for line in [
+            format!("UID:{}", uid),
+            "SUMMARY:Write spec\\; then review".to_string(),
+            "DESCRIPTION:Line 1\\nLine 2\\, done".to_string(),
+            "STATUS:IN-PROCESS".to_string(),
+            "PRIORITY:1".to_string(),
+            "CATEGORIES:feat,a\\,b".to_string(),
+            "DUE;VALUE=DATE:20261020".to_string(),
+            format!("RELATED-TO;RELTYPE=X-BLOCKS:{}", uuid(&todos[1])),
+            format!("RELATED-TO;RELTYPE=PARENT:{}", uid),
+            format!("RELATED-TO;RELTYPE=DEPENDS-ON:{}", uid),
+            "X-TODO-CONTEXT:work".to_string(),
+            "X-TODO-RECURRENCE:weekly:mon\\,fri".to_string(),
+            "STATUS:COMPLETED".to_string(),
+        ] {
+            assert!(lines.contains(&line), "missing line: {}", line);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let todos = build_todos();
+        let imported = Import::new(import(&export(&todos)).unwrap());
+        assert_eq!(imported.todos.len(), 3);
+        assert_eq!(imported.matches(&todos).len(), 3);
+
+        for (todo, imported) in todos.iter().zip(&imported.todos) {
+            assert_eq!(imported.id, todo.id);
+            assert_eq!(imported.created, todo.created);
+            assert_eq!(imported.status, todo.status);
+            assert_eq!(imported.prio, todo.prio);
+            assert_eq!(imported.subject, todo.subject);
+            assert_eq!(imported.description, todo.description);
+            assert_eq!(imported.context, todo.context);
+            assert_eq!(imported.tags.encode(), todo.tags.encode());
+            assert_eq!(imported.links.encode(), todo.links.encode());
+            assert_eq!(imported.due, todo.due);
+            assert_eq!(imported.recurrence, todo.recurrence);
+            assert_eq!(imported.parent, todo.parent);
+            assert_eq!(imported.completed, todo.completed);
+        }
+    }
+
+    #[test]
+    fn import_vtodos() {
+        let s = "BEGIN:VCALENDAR\r\n\
+            VERSION:2.0\r\n\
+            BEGIN:VTODO\r\n\
+            UID:first@example.com\r\n\
+            DTSTAMP:20261001T080000Z\r\n\
+            SUMMARY:Call the\r\n  plumber\r\n\
+            PRIORITY:2\r\n\
+            CATEGORIES:home\r\n\
+            CATEGORIES:urgent\r\n\
+            DUE;TZID=Europe/Stockholm:20261005T120000\r\n\
+            BEGIN:VALARM\r\n\
+            ACTION:DISPLAY\r\n\
+            DESCRIPTION:Reminder\r\n\
+            END:VALARM\r\n\
+            END:VTODO\r\n\
+            BEGIN:VTODO\r\n\
+            UID:second@example.com\r\n\
+            SUMMARY:Cancelled\r\n\
+            STATUS:CANCELLED\r\n\
+            END:VTODO\r\n\
+            BEGIN:VTODO\r\n\
+            UID:third@example.com\r\n\
+            SUMMARY:Pay the bill\r\n\
+            RELATED-TO;RELTYPE=DEPENDS-ON:first@example.com\r\n\
+            RELATED-TO:unknown@example.com\r\n\
+            END:VTODO\r\n\
+            END:VCALENDAR\r\n";
+        let todos = import(s).unwrap();
+        assert_eq!(todos.len(), 2);
+        assert_eq!(todos[0].uid.as_deref(), Some("first@example.com"));
+
+        assert_eq!(todos[0].subject, "Call the plumber");
+        assert_eq!(todos[0].description, "");
+        assert_eq!(todos[0].prio, Prio::High);
+        assert_eq!(todos[0].tags.encode(), "home,urgent");
+        assert_eq!(todos[0].due, NaiveDate::from_ymd_opt(2026, 10, 5));
+        assert_eq!(
+            todos[0].created,
+            Utc.with_ymd_and_hms(2026, 10, 1, 8, 0, 0).unwrap()
+        );
+        assert_eq!(todos[0].links.encode(), "blocks:2");
+
+        assert_eq!(todos[1].id, ID::new(2));
+        assert_eq!(todos[1].status, Status::Blocked);
+        assert_eq!(todos[1].links.encode(), "blockedby:1");
+        assert_eq!(todos[1].parent, None);
+
+        assert!(import("BEGIN:VCALENDAR\r\nno colon\r\n").is_err());
+    }
+}
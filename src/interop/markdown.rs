@@ -0,0 +1,218 @@
+use crate::model::{Status, Todo};
+
+// The order of the status sections, open work first.
+const STATUSES: [Status; 4] = [Status::Started, Status::New, Status::Blocked, Status::Done];
+
+/// Formats the todos as a Markdown report with a section per context and
+/// status, keeping the order of the todos within a section.
+pub fn export(todos: &[Todo]) -> String {
+    let mut contexts: Vec<Option<&str>> = Vec::new();
+    for todo in todos {
+        let context = todo.context.as_deref();
+        if !contexts.contains(&context) {
+            contexts.push(context);
+        }
+    }
+    // Named contexts sorted by name, followed by todos without one.
+    contexts.sort_by_key(|context| (context.is_none(), *context));
+
+    let mut lines = vec!["# Todos".to_string()];
+    for context in contexts {
+        blank_line(&mut lines);
+        lines.push(format!("## {}", context.unwrap_or("No context")));
+        for status in &STATUSES {
+            let section: Vec<&Todo> = todos
+                .iter()
+                .filter(|todo| todo.context.as_deref() == context && &todo.status == status)
+                .collect();
+            if section.is_empty() {
+                continue;
+            }
+            blank_line(&mut lines);
+            lines.push(format!("### {}", capitalize(&status.to_string())));
+            lines.push(String::new());
+            for todo in section {
+                format_todo(todo, &mut lines);
+            }
+        }
+    }
+    lines.join("\n").trim_end().to_string()
+}
+
+// Formats a todo as a task list item followed by its description, which is
+// indented to continue the item.
+fn format_todo(todo: &Todo, lines: &mut Vec<String>) {
+    let check = if todo.is_done() { 'x' } else { ' ' };
+    let mut words = vec![
+        format!("- [{}] #{}", check, todo.id),
+        escape(todo.subject.trim()),
+        format!("**{}**", todo.prio),
+    ];
+    words.extend(todo.tags.values().iter().map(|tag| code(tag)));
+
+    let mut refs: Vec<String> = todo
+        .links
+        .values()
+        .iter()
+        .map(|link| format!("{} #{}", link.name().replace(['-', '_'], " "), link.id()))
+        .collect();
+    if let Some(parent) = todo.parent {
+        refs.push(format!("subtask of #{}", parent));
+    }
+    if let Some(due) = todo.due {
+        refs.push(format!("due {}", due.format("%Y-%m-%d")));
+    }
+    if !refs.is_empty() {
+        words.push(format!("({})", refs.join(", ")));
+    }
+    lines.push(words.join(" "));
+
+    let description = todo.description.trim();
+    if !description.is_empty() {
+        lines.push(String::new());
+        for line in description.lines() {
+            match line.trim_end() {
+                "" => lines.push(String::new()),
+                line => lines.push(format!("  {}", line)),
+            }
+        }
+        lines.push(String::new());
+    }
+}
+
+// Escapes the characters that would otherwise start inline Markdown,
+// like emphasis, links, code or HTML.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '~' | '&'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// Formats `s` as inline code, using a fence longer than any run of
+// backticks in it.
+fn code(s: &str) -> String {
+    let longest = s
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest + 1);
+    if s.starts_with('`') || s.ends_with('`') {
+        format!("{} {} {}", fence, s, fence)
+    } else {
+        format!("{}{}{}", fence, s, fence)
+    }
+}
+
+// Separates blocks by a single blank line.
+fn blank_line(lines: &mut Vec<String>) {
+    if lines.last().is_some_and(|line| !line.is_empty()) {
+        lines.push(String::new());
+    }
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Link, Prio, CSV, ID};
+    use chrono::{Local, NaiveDate, TimeZone};
+
+    fn todo(id: u16, status: Status, subject: &str, context: Option<&str>) -> Todo {
+        let mut todo = Todo::new(
+            ID::new(id),
+            Local.with_ymd_and_hms(2026, 10, 18, 9, 30, 0).unwrap(),
+            status,
+            Prio::Normal,
+            subject.to_string(),
+            "".to_string(),
+            CSV::empty(),
+            None,
+            CSV::empty(),
+        );
+        todo.context = context.map(str::to_string);
+        todo
+    }
+
+    #[test]
+    fn export_report() {
+        let mut blocker = todo(1, Status::Started, "Fix login", Some("work"));
+        blocker.prio = Prio::High;
+        blocker.tags = CSV::new(vec!["api".to_string()]);
+        blocker.links = CSV::new(vec![Link::Blocks(ID::new(2))]);
+        blocker.description = "Sessions expire early.\n\nSee logs.".to_string();
+        blocker.due = NaiveDate::from_ymd_opt(2026, 10, 20);
+        let mut blocked = todo(2, Status::Blocked, "Release", Some("work"));
+        blocked.links = CSV::new(vec![Link::BlockedBy(ID::new(1))]);
+        let todos = vec![
+            todo(3, Status::Done, "Buy milk", None),
+            blocked,
+            blocker,
+            todo(4, Status::New, "Water plants", Some("home")),
+        ];
+
+        let expected = "# Todos
+
+## home
+
+### New
+
+- [ ] #4 Water plants **normal**
+
+## work
+
+### Started
+
+- [ ] #1 Fix login **high** `api` (blocks #2, due 2026-10-20)
+
+  Sessions expire early.
+
+  See logs.
+
+### Blocked
+
+- [ ] #2 Release **normal** (blocked by #1)
+
+## No context
+
+### Done
+
+- [x] #3 Buy milk **normal**";
+        assert_eq!(export(&todos), expected);
+    }
+
+    #[test]
+    fn escape_subject_and_tags() {
+        let mut todo = todo(
+            1,
+            Status::New,
+            "Fix *all* [links] in <a> `code` & a_b",
+            None,
+        );
+        todo.tags = CSV::new(vec!["a`b".to_string(), "`c".to_string()]);
+        assert_eq!(
+            export(&[todo]).lines().last().unwrap(),
+            "- [ ] #1 Fix \\*all\\* \\[links\\] in \\<a\\> \\`code\\` \\& a\\_b **normal** ``a`b`` `` `c ``"
+        );
+    }
+
+    #[test]
+    fn export_no_todos() {
+        assert_eq!(export(&[]), "# Todos");
+    }
+}
@@ -0,0 +1,197 @@
+use crate::err;
+use crate::error::{self, Error};
+use crate::model::{Link, Status, Todo, ID};
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+use std::collections::HashMap;
+use std::fmt;
+
+mod ical;
+mod markdown;
+mod taskwarrior;
+mod todotxt;
+
+/// A format of other tools that todos can be exported to and imported from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// One todo per line as described at http://todotxt.org.
+    TodoTxt,
+    /// The JSON of `task export` and `task import`.
+    Taskwarrior,
+    /// An iCalendar (RFC 5545) document with a VTODO per todo.
+    Ical,
+    /// A Markdown report for reading only, which can't be imported.
+    Markdown,
+}
+
+impl Format {
+    pub fn values() -> [&'static str; 4] {
+        ["todotxt", "taskwarrior", "ical", "markdown"]
+    }
+
+    /// The formats that todos can be imported from.
+    pub fn importable() -> [&'static str; 3] {
+        ["todotxt", "taskwarrior", "ical"]
+    }
+
+    /// Exports the todos as a document in this format.
+    pub fn export(&self, todos: &[Todo]) -> String {
+        match self {
+            Format::TodoTxt => todotxt::export(todos),
+            Format::Taskwarrior => taskwarrior::export(todos),
+            Format::Ical => ical::export(todos),
+            Format::Markdown => markdown::export(todos),
+        }
+    }
+
+    /// Parses a document in this format. The IDs of the todos are those
+    /// given in the document, used to resolve links between them.
+    pub fn import(&self, s: &str) -> error::Result<Import> {
+        match self {
+            Format::TodoTxt => Ok(Import::new(todotxt::import(s)?)),
+            Format::Taskwarrior => Ok(Import::new(taskwarrior::import(s)?)),
+            Format::Ical => Ok(Import::new(ical::import(s)?)),
+            Format::Markdown => err!("todos can't be imported from {}", self),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::TodoTxt => write!(f, "todotxt"),
+            Format::Taskwarrior => write!(f, "taskwarrior"),
+            Format::Ical => write!(f, "ical"),
+            Format::Markdown => write!(f, "markdown"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Format {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "todotxt" | "todo.txt" => Ok(Format::TodoTxt),
+            "taskwarrior" => Ok(Format::Taskwarrior),
+            "ical" => Ok(Format::Ical),
+            "markdown" => Ok(Format::Markdown),
+            _ => err!(
+                "invalid format: {}, expected one of {}",
+                value,
+                Format::values().join(", ")
+            ),
+        }
+    }
+}
+
+/// Todos read from a document, which have UIDs for formats that
+/// identify todos by those.
+pub struct Import {
+    pub todos: Vec<Todo>,
+}
+
+impl Import {
+    fn new(todos: Vec<Todo>) -> Self {
+        Self { todos }
+    }
+
+    /// Matches the imported todos with `todos` by UID, returning the
+    /// IDs of the imported todos mapped to the IDs of their matches.
+    pub fn matches(&self, todos: &[Todo]) -> HashMap<ID, ID> {
+        let existing: HashMap<String, ID> =
+            todos.iter().map(|todo| (uuid(todo), todo.id)).collect();
+        self.todos
+            .iter()
+            .filter_map(|todo| {
+                let uid = todo.uid.as_ref()?;
+                existing.get(uid).map(|existing| (todo.id, *existing))
+            })
+            .collect()
+    }
+}
+
+/// Returns the UID the todo was imported with, or else a UUID derived
+/// from its creation time and ID so that it is the same in every export.
+fn uuid(todo: &Todo) -> String {
+    if let Some(uid) = &todo.uid {
+        return uid.clone();
+    }
+    let time = todo.created.timestamp_micros() as u64;
+    format!(
+        "{:08x}-{:04x}-{:04x}-{:04x}-{:0>12}",
+        time >> 32,
+        (time >> 16) & 0xffff,
+        0x8000 | ((time >> 4) & 0x0fff),
+        0x8000 | ((time & 0xf) << 8),
+        todo.id.to_string()
+    )
+}
+
+// Adds the counterparts of blocking links, which formats tend to give on
+// one side only, and sets new todos that are blocked as such.
+fn link_blocks(todos: &mut [Todo]) {
+    let mut links = Vec::new();
+    for todo in todos.iter() {
+        for link in todo.links.values() {
+            if let Some(other) = link
+                .bi_directional(todo.id)
+                .filter(|_| link.is_blocks() || link.is_blocked_by())
+            {
+                links.push((link.id(), other));
+            }
+        }
+    }
+    for (id, link) in links {
+        if let Some(todo) = todos.iter_mut().find(|todo| todo.id == id) {
+            todo.links.push_not_exists(link);
+        }
+    }
+    for todo in todos.iter_mut() {
+        let blocked = todo.links.values().iter().any(Link::is_blocked_by);
+        if blocked && todo.status == Status::New {
+            todo.status = Status::Blocked;
+        }
+    }
+}
+
+// Dates are taken as the start of the day in local time.
+fn start_of_day(date: NaiveDate) -> DateTime<Local> {
+    Local
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+        .unwrap_or_else(Local::now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Prio, Status, CSV, ID};
+    use chrono::{Local, TimeZone};
+
+    #[test]
+    fn parse_format() {
+        assert_eq!(Format::try_from("todotxt").unwrap(), Format::TodoTxt);
+        assert_eq!(Format::try_from("todo.txt").unwrap(), Format::TodoTxt);
+        assert_eq!(Format::try_from("ical").unwrap(), Format::Ical);
+        assert!(Format::try_from("task").is_err());
+    }
+
+    #[test]
+    fn todo_uuid() {
+        let todo = Todo::new(
+            ID::new(42),
+            Local.with_ymd_and_hms(2026, 10, 18, 9, 30, 0).unwrap(),
+            Status::New,
+            Prio::Normal,
+            "Subject".to_string(),
+            "".to_string(),
+            CSV::empty(),
+            None,
+            CSV::empty(),
+        );
+        let uuid = uuid(&todo);
+        assert_eq!(uuid.len(), 36);
+        assert!(uuid.ends_with("-000000000042"));
+        assert_eq!(uuid, super::uuid(&todo.clone()));
+    }
+}
@@ -0,0 +1,346 @@
+use super::{link_blocks, start_of_day, uuid};
+use crate::err;
+use crate::error::Result;
+use crate::model::{Link, Prio, Status, Todo, CSV, ID};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const TIME_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+// Taskwarrior has no priority above H, so critical todos are exported
+// with priority H and this tag.
+const CRITICAL_TAG: &str = "critical";
+
+/// A task as given by `task export`, where attributes without
+/// a counterpart in todos are ignored.
+#[derive(Debug, Deserialize, Serialize)]
+struct Task {
+    uuid: String,
+    description: String,
+    status: String,
+    entry: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    wait: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    depends: Option<Depends>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    annotations: Vec<Annotation>,
+}
+
+/// UUIDs of the tasks a task depends on, given as a comma separated
+/// string before Taskwarrior 2.6.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+enum Depends {
+    List(Vec<String>),
+    Text(String),
+}
+
+impl Depends {
+    fn uuids(&self) -> Vec<&str> {
+        match self {
+            Depends::List(uuids) => uuids.iter().map(String::as_str).collect(),
+            Depends::Text(s) => s.split(',').map(str::trim).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Annotation {
+    entry: String,
+    description: String,
+}
+
+/// Formats the todos as a JSON array of tasks, one task per line
+/// like `task export`.
+pub fn export(todos: &[Todo]) -> String {
+    let uuids: HashMap<ID, String> = todos.iter().map(|todo| (todo.id, uuid(todo))).collect();
+    let tasks: Vec<String> = todos
+        .iter()
+        .map(|todo| {
+            let task = to_task(todo, &uuids);
+            serde_json::to_string(&task).expect("serialize task")
+        })
+        .collect();
+    match tasks.is_empty() {
+        true => "[]".to_string(),
+        false => format!("[\n{}\n]", tasks.join(",\n")),
+    }
+}
+
+/// Parses tasks from the JSON of `task export`, either an array or
+/// one task per line. Deleted tasks and templates of recurring tasks
+/// are skipped, and the todos get IDs in the order of the tasks.
+pub fn import(s: &str) -> Result<Vec<Todo>> {
+    let parsed = if s.trim_start().starts_with('[') {
+        serde_json::from_str::<Vec<Task>>(s)
+    } else {
+        s.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str::<Task>)
+            .collect()
+    };
+    let tasks: Vec<Task> = match parsed {
+        Ok(tasks) => tasks
+            .into_iter()
+            .filter(|task| !matches!(task.status.as_str(), "deleted" | "recurring"))
+            .collect(),
+        Err(e) => return err!("invalid Taskwarrior JSON: {}", e),
+    };
+
+    let mut ids = HashMap::new();
+    for (i, task) in tasks.iter().enumerate() {
+        ids.insert(
+            task.uuid.as_str(),
+            ID::try_from((i + 1).to_string().as_str())?,
+        );
+    }
+
+    let mut todos = Vec::new();
+    for task in &tasks {
+        match to_todo(task, &ids) {
+            Ok(todo) => todos.push(todo),
+            Err(e) => return err!("invalid task {}: {}", task.uuid, e),
+        }
+    }
+
+    // Dependencies on done todos are dropped as if they were completed here.
+    let done: Vec<ID> = todos
+        .iter()
+        .filter(|todo| todo.is_done())
+        .map(|todo| todo.id)
+        .collect();
+    for todo in &mut todos {
+        if !todo.is_done() {
+            let links = todo
+                .links
+                .values()
+                .iter()
+                .filter(|link| !done.contains(&link.id()))
+                .cloned()
+                .collect();
+            todo.links = CSV::new(links);
+        }
+    }
+    link_blocks(&mut todos);
+    Ok(todos)
+}
+
+fn to_task(todo: &Todo, uuids: &HashMap<ID, String>) -> Task {
+    let mut tags = todo.tags.values().clone();
+    let priority = match todo.prio {
+        Prio::Critical => {
+            tags.push(CRITICAL_TAG.to_string());
+            "H"
+        }
+        Prio::High => "H",
+        Prio::Normal => "M",
+        Prio::Low => "L",
+    };
+    let depends: Vec<String> = todo
+        .links
+        .values()
+        .iter()
+        .filter(|link| link.is_blocked_by())
+        .filter_map(|link| uuids.get(&link.id()).cloned())
+        .collect();
+    let annotations = match todo.description.is_empty() {
+        true => vec![],
+        false => vec![Annotation {
+            entry: format_time(todo.created),
+            description: todo.description.clone(),
+        }],
+    };
+
+    Task {
+        uuid: uuids[&todo.id].clone(),
+        description: todo.subject.clone(),
+        status: match todo.status {
+            Status::Done => "completed".to_string(),
+            _ => "pending".to_string(),
+        },
+        entry: format_time(todo.created),
+        // The time a todo was started isn't kept, so the creation time
+        // is given instead.
+        start: match todo.status {
+            Status::Started => Some(format_time(todo.created)),
+            _ => None,
+        },
+        end: match todo.status {
+            Status::Done => Some(format_time(todo.completed.unwrap_or(todo.created))),
+            _ => None,
+        },
+        due: todo.due.map(|date| format_time(start_of_day(date))),
+        wait: todo.after.map(|date| format_time(start_of_day(date))),
+        priority: Some(priority.to_string()),
+        project: todo.context.clone(),
+        tags,
+        depends: match depends.is_empty() {
+            true => None,
+            false => Some(Depends::List(depends)),
+        },
+        annotations,
+    }
+}
+
+fn to_todo(task: &Task, ids: &HashMap<&str, ID>) -> Result<Todo> {
+    let status = match task.status.as_str() {
+        "completed" => Status::Done,
+        "pending" | "waiting" if task.start.is_some() => Status::Started,
+        "pending" | "waiting" => Status::New,
+        status => return err!("unknown status: {}", status),
+    };
+
+    let critical = task.tags.iter().any(|tag| tag == CRITICAL_TAG);
+    let prio = match task.priority.as_deref() {
+        Some("H") if critical => Prio::Critical,
+        Some("H") => Prio::High,
+        Some("L") => Prio::Low,
+        _ => Prio::Normal,
+    };
+    let tags = task
+        .tags
+        .iter()
+        .filter(|tag| !(prio == Prio::Critical && *tag == CRITICAL_TAG))
+        .cloned()
+        .collect();
+
+    let mut links = CSV::empty();
+    for uuid in task.depends.iter().flat_map(Depends::uuids) {
+        match ids.get(uuid) {
+            Some(id) => links.push_not_exists(Link::BlockedBy(*id)),
+            None => log::warn!("Dropped dependency of task {} on {}", task.uuid, uuid),
+        }
+    }
+
+    let description: Vec<&str> = task
+        .annotations
+        .iter()
+        .map(|annotation| annotation.description.as_str())
+        .collect();
+
+    let todo = Todo::new(
+        ids[task.uuid.as_str()],
+        parse_time(&task.entry)?,
+        status,
+        prio,
+        task.description.clone(),
+        description.join("\n"),
+        CSV::new(tags),
+        task.project.clone(),
+        links,
+    )
+    .with_due(parse_date(task.due.as_deref())?)
+    .with_after(parse_date(task.wait.as_deref())?)
+    .with_uid(Some(task.uuid.clone()))
+    .with_completed(match task.status.as_str() {
+        "completed" => task.end.as_deref().map(parse_time).transpose()?,
+        _ => None,
+    });
+    Ok(todo)
+}
+
+fn format_time(time: DateTime<Local>) -> String {
+    time.with_timezone(&Utc).format(TIME_FORMAT).to_string()
+}
+
+fn parse_time(s: &str) -> Result<DateTime<Local>> {
+    match NaiveDateTime::parse_from_str(s, TIME_FORMAT) {
+        Ok(time) => Ok(Utc.from_utc_datetime(&time).with_timezone(&Local)),
+        Err(_) => err!("invalid time: {}", s),
+    }
+}
+
+// Dates are given by Taskwarrior as the start of the day in local time.
+fn parse_date(s: Option<&str>) -> Result<Option<NaiveDate>> {
+    s.map(|s| parse_time(s).map(|time| time.date_naive()))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Code;
+
+    const TASKS: &str = r#"[
+{"id":1,"description":"Write spec","entry":"20261001T080000Z","modified":"20261002T080000Z","priority":"H","project":"work","start":"20261002T080000Z","status":"pending","tags":["critical","api"],"uuid":"a5b6c7d8-0000-4000-8000-000000000001","annotations":[{"entry":"20261001T090000Z","description":"First note"},{"entry":"20261001T100000Z","description":"Second note"}],"urgency":9.2},
+{"id":2,"description":"Review spec","depends":"a5b6c7d8-0000-4000-8000-000000000001,a5b6c7d8-0000-4000-8000-000000000003","due":"20261020T000000Z","entry":"20261001T080000Z","status":"pending","uuid":"a5b6c7d8-0000-4000-8000-000000000002"},
+{"id":0,"description":"Draft","end":"20261003T080000Z","entry":"20261001T080000Z","status":"completed","priority":"L","uuid":"a5b6c7d8-0000-4000-8000-000000000003"},
+{"id":0,"description":"Gone","entry":"20261001T080000Z","status":"deleted","uuid":"a5b6c7d8-0000-4000-8000-000000000004"}
+]"#;
+
+    #[test]
+    fn import_tasks() {
+        let todos = import(TASKS).unwrap();
+        assert_eq!(todos.len(), 3);
+
+        let spec = &todos[0];
+        assert_eq!(spec.id, ID::new(1));
+        assert_eq!(spec.status, Status::Started);
+        assert_eq!(spec.prio, Prio::Critical);
+        assert_eq!(spec.tags.encode(), "api");
+        assert_eq!(spec.context.as_deref(), Some("work"));
+        assert_eq!(spec.description, "First note\nSecond note");
+        assert_eq!(spec.created, parse_time("20261001T080000Z").unwrap());
+        assert_eq!(spec.links.encode(), "blocks:2");
+
+        // The dependency on the completed task is dropped.
+        let review = &todos[1];
+        assert_eq!(review.status, Status::Blocked);
+        assert_eq!(review.prio, Prio::Normal);
+        assert_eq!(review.links.encode(), "blockedby:1");
+        assert!(review.due.is_some());
+
+        let draft = &todos[2];
+        assert_eq!(draft.status, Status::Done);
+        assert_eq!(draft.prio, Prio::Low);
+        assert_eq!(
+            draft.completed,
+            Some(parse_time("20261003T080000Z").unwrap())
+        );
+
+        assert!(import("not json").is_err());
+        assert!(import(&export(&[])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        let todos = import(TASKS).unwrap();
+        let s = export(&todos);
+        assert_eq!(s.lines().count(), 5);
+
+        let value: serde_json::Value = serde_json::from_str(&s).unwrap();
+        assert_eq!(value[0]["priority"], "H");
+        assert_eq!(value[0]["tags"], serde_json::json!(["api", "critical"]));
+        assert_eq!(value[1]["depends"], serde_json::json!([uuid(&todos[0])]));
+        assert_eq!(value[2]["status"], "completed");
+
+        let imported = import(&s).unwrap();
+        for (todo, imported) in todos.iter().zip(&imported) {
+            assert_eq!(imported.id, todo.id);
+            assert_eq!(imported.created, todo.created);
+            assert_eq!(imported.status, todo.status);
+            assert_eq!(imported.prio, todo.prio);
+            assert_eq!(imported.subject, todo.subject);
+            assert_eq!(imported.description, todo.description);
+            assert_eq!(imported.context, todo.context);
+            assert_eq!(imported.tags.encode(), todo.tags.encode());
+            assert_eq!(imported.links.encode(), todo.links.encode());
+            assert_eq!(imported.due, todo.due);
+            assert_eq!(imported.completed, todo.completed);
+        }
+    }
+}
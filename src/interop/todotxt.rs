@@ -0,0 +1,424 @@
+use super::start_of_day;
+use crate::err;
+use crate::error::Result;
+use crate::model::{Code, Link, Prio, Recurrence, Status, Todo, CSV, ID};
+use chrono::{DateTime, Local, NaiveDate};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+// Keys of the fields todo.txt has no syntax for, added as `key:value`.
+// Due and threshold (t) are the ones commonly used by todo.txt apps.
+const KEYS: [&str; 9] = [
+    "due", "t", "rec", "status", "pri", "id", "parent", "link", "desc",
+];
+
+/// Formats the todos as todo.txt, one todo per line.
+pub fn export(todos: &[Todo]) -> String {
+    todos
+        .iter()
+        .map(format_todo)
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Parses todos from todo.txt, skipping blank lines. Todos without an
+/// `id` key get the line number as ID.
+pub fn import(s: &str) -> Result<Vec<Todo>> {
+    let mut todos = Vec::new();
+    for (n, line) in s.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match parse_todo(line, n + 1) {
+            Ok(todo) => todos.push(todo),
+            Err(err) => return err!("invalid todo on line {}: {}", n + 1, err),
+        }
+    }
+    Ok(todos)
+}
+
+// Formats a todo as: [x completed] [(prio)] created subject @context +tags key:value...
+// where the prio of done todos is kept as `pri` since they don't have one.
+// Words of the subject that look like those are escaped.
+fn format_todo(todo: &Todo) -> String {
+    let mut words = Vec::new();
+    if todo.is_done() {
+        words.push("x".to_string());
+        words.push(format_date(todo.completed.unwrap_or(todo.created)));
+    } else {
+        words.push(format!("({})", prio_letter(&todo.prio)));
+    }
+    words.push(format_date(todo.created));
+    words.extend(todo.subject.split_whitespace().map(escape_word));
+
+    if let Some(context) = &todo.context {
+        words.push(format!("@{}", escape(context)));
+    }
+    for tag in todo.tags.values() {
+        words.push(format!("+{}", escape(tag)));
+    }
+    if todo.is_done() {
+        words.push(format!("pri:{}", prio_letter(&todo.prio)));
+    }
+    if matches!(todo.status, Status::Started | Status::Blocked) {
+        words.push(format!("status:{}", todo.status));
+    }
+    if let Some(due) = todo.due {
+        words.push(format!("due:{}", due.format(DATE_FORMAT)));
+    }
+    if let Some(after) = todo.after {
+        words.push(format!("t:{}", after.format(DATE_FORMAT)));
+    }
+    if let Some(recurrence) = &todo.recurrence {
+        words.push(format!("rec:{}", recurrence.encode()));
+    }
+    words.push(format!("id:{}", todo.id));
+    if let Some(parent) = todo.parent {
+        words.push(format!("parent:{}", parent));
+    }
+    for link in todo.links.values() {
+        words.push(format!("link:{}", link.encode()));
+    }
+    if !todo.description.is_empty() {
+        words.push(format!("desc:{}", escape(&todo.description)));
+    }
+    words.join(" ")
+}
+
+// Parses a line of todo.txt. Contexts and tags at the end of the line are
+// removed from the subject while those within the text are kept, and the
+// ones at the end take precedence as the context.
+fn parse_todo(line: &str, n: usize) -> Result<Todo> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let mut i = 0;
+
+    let done = words.first() == Some(&"x");
+    if done {
+        i += 1;
+    }
+    let mut prio = words.get(i).and_then(|word| parse_prio(word));
+    if prio.is_some() {
+        i += 1;
+    }
+    // Done todos have the completion date first, if any dates.
+    let mut dates = Vec::new();
+    while dates.len() < if done { 2 } else { 1 } {
+        match words.get(i).and_then(|word| parse_date(word).ok()) {
+            Some(date) => dates.push(date),
+            None => break,
+        }
+        i += 1;
+    }
+    let (completed, created) = match done {
+        true => (dates.first(), dates.get(1)),
+        false => (None, dates.first()),
+    };
+
+    let body = &words[i..];
+    let end = body.len()
+        - body
+            .iter()
+            .rev()
+            .take_while(|word| is_metadata(word))
+            .count();
+
+    let mut subject = Vec::new();
+    let mut context = None;
+    let mut inline_context = None;
+    let mut tags = CSV::empty();
+    let mut links = CSV::empty();
+    let mut status = None;
+    let mut description = String::new();
+    let mut due = None;
+    let mut after = None;
+    let mut recurrence = None;
+    let mut id = None;
+    let mut parent = None;
+    for (index, word) in body.iter().enumerate() {
+        if let Some((key, value)) = key_value(word) {
+            match key {
+                "due" => due = Some(parse_date(value)?),
+                "t" => after = Some(parse_date(value)?),
+                "rec" => recurrence = Some(Recurrence::decode(value)?),
+                "status" => status = Some(Status::try_from(value)?),
+                "pri" => match parse_prio(&format!("({})", value)) {
+                    Some(p) => prio = prio.or(Some(p)),
+                    None => return err!("invalid priority: {}", value),
+                },
+                "id" => id = Some(ID::try_from(value)?),
+                "parent" => parent = Some(ID::try_from(value)?),
+                "link" => links.push(Link::decode(value)?),
+                "desc" => description = unescape(value),
+                _ => unreachable!("unknown key: {}", key),
+            }
+            continue;
+        }
+
+        let trailing = index >= end;
+        if let Some(name) = word.strip_prefix('@').filter(|s| !s.is_empty()) {
+            if !trailing {
+                inline_context = inline_context.or(Some(unescape(name)));
+            } else if context.is_none() {
+                context = Some(unescape(name));
+                continue;
+            }
+        } else if let Some(tag) = word.strip_prefix('+').filter(|s| !s.is_empty()) {
+            tags.push_not_exists(unescape(tag));
+            if trailing {
+                continue;
+            }
+        }
+        subject.push(unescape(word));
+    }
+
+    let status = match done {
+        true => Status::Done,
+        false => status.unwrap_or(Status::New),
+    };
+    let id = match id {
+        Some(id) => id,
+        None => ID::try_from(n.to_string().as_str())?,
+    };
+    let todo = Todo::new(
+        id,
+        created
+            .map(|date| start_of_day(*date))
+            .unwrap_or_else(Local::now),
+        status,
+        prio.unwrap_or(Prio::Normal),
+        subject.join(" "),
+        description,
+        tags,
+        context.or(inline_context),
+        links,
+    )
+    .with_due(due)
+    .with_after(after)
+    .with_recurrence(recurrence)
+    .with_parent(parent)
+    .with_completed(completed.map(|date| start_of_day(*date)));
+    Ok(todo)
+}
+
+// Returns true if the word is a context, tag or any `key:value` pair,
+// which make up the end of a line.
+fn is_metadata(word: &str) -> bool {
+    match word.split_once(':') {
+        Some((key, value)) => !key.is_empty() && !value.is_empty(),
+        None => word.len() > 1 && word.starts_with(['@', '+']),
+    }
+}
+
+// Returns the key and value if the word is a pair with a known key.
+fn key_value(word: &str) -> Option<(&str, &str)> {
+    word.split_once(':')
+        .filter(|(key, value)| KEYS.contains(key) && !value.is_empty())
+}
+
+fn prio_letter(prio: &Prio) -> char {
+    match prio {
+        Prio::Critical => 'A',
+        Prio::High => 'B',
+        Prio::Normal => 'C',
+        Prio::Low => 'D',
+    }
+}
+
+// Parses a priority such as `(A)`, where letters after D are low.
+fn parse_prio(s: &str) -> Option<Prio> {
+    let letter = s.strip_prefix('(')?.strip_suffix(')')?;
+    match letter {
+        "A" => Some(Prio::Critical),
+        "B" => Some(Prio::High),
+        "C" => Some(Prio::Normal),
+        _ if letter.len() == 1 && letter.as_bytes()[0].is_ascii_uppercase() => Some(Prio::Low),
+        _ => None,
+    }
+}
+
+fn format_date(time: DateTime<Local>) -> String {
+    time.format(DATE_FORMAT).to_string()
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate> {
+    match NaiveDate::parse_from_str(s, DATE_FORMAT) {
+        Ok(date) => Ok(date),
+        Err(_) => err!("invalid date: {}", s),
+    }
+}
+
+// Percent-encodes `%` and whitespace so the value is a single word.
+fn escape(s: &str) -> String {
+    let mut escaped = String::new();
+    for c in s.chars() {
+        if c == '%' || c.is_whitespace() {
+            for b in c.to_string().bytes() {
+                escaped.push_str(&format!("%{:02X}", b));
+            }
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+// Escapes a word of the subject that would otherwise be read as a context,
+// tag or known `key:value` pair.
+fn escape_word(word: &str) -> String {
+    let word = escape(word);
+    if word.len() > 1 && word.starts_with(['@', '+']) {
+        format!("%{:02X}{}", word.as_bytes()[0], &word[1..])
+    } else if key_value(&word).is_some() {
+        word.replacen(':', "%3A", 1)
+    } else {
+        word
+    }
+}
+
+// Decodes percent-encoded bytes, keeping any `%` that doesn't start one.
+fn unescape(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = match bytes[i] {
+            b'%' => s
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(day: u32) -> DateTime<Local> {
+        start_of_day(NaiveDate::from_ymd_opt(2026, 10, day).unwrap())
+    }
+
+    fn build_todo() -> Todo {
+        Todo::new(
+            ID::new(1),
+            time(18),
+            Status::Started,
+            Prio::High,
+            "Write the  spec".to_string(),
+            "Line 1\nLine 2, 100%".to_string(),
+            CSV::new(vec!["feat".to_string(), "api".to_string()]),
+            Some("work".to_string()),
+            CSV::new(vec![Link::Blocks(ID::new(2))]),
+        )
+        .with_due(NaiveDate::from_ymd_opt(2026, 10, 20))
+        .with_recurrence(Some(Recurrence::Monthly(1)))
+    }
+
+    #[test]
+    fn format_todos() {
+        let done = Todo::new(
+            ID::new(2),
+            time(18),
+            Status::Done,
+            Prio::Critical,
+            "Ship it".to_string(),
+            "".to_string(),
+            CSV::empty(),
+            None,
+            CSV::empty(),
+        )
+        .with_completed(Some(time(19)));
+
+        let s = export(&[build_todo(), done]);
+        assert_eq!(
+            s,
+            "(B) 2026-10-18 Write the spec @work +feat +api status:started due:2026-10-20 \
+            rec:monthly:1 id:1 link:blocks:2 desc:Line%201%0ALine%202,%20100%25\n\
+            x 2026-10-19 2026-10-18 Ship it pri:A id:2"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let todo = build_todo();
+        let s = export(std::slice::from_ref(&todo));
+        let imported = &import(&s).unwrap()[0];
+        assert_eq!(imported.id, todo.id);
+        assert_eq!(imported.created, todo.created);
+        assert_eq!(imported.status, todo.status);
+        assert_eq!(imported.prio, todo.prio);
+        assert_eq!(imported.subject, "Write the spec");
+        assert_eq!(imported.description, todo.description);
+        assert_eq!(imported.context, todo.context);
+        assert_eq!(imported.tags.encode(), todo.tags.encode());
+        assert_eq!(imported.links.encode(), todo.links.encode());
+        assert_eq!(imported.due, todo.due);
+        assert_eq!(imported.recurrence, todo.recurrence);
+        assert_eq!(export(std::slice::from_ref(imported)), s);
+    }
+
+    #[test]
+    fn round_trip_subjects() {
+        let subjects = [
+            "Ask @alice",
+            "Check status:ok",
+            "Vote +1 for 100% or 50%25",
+            "due:soon @ + url:https://example.com",
+        ];
+        for subject in subjects {
+            let mut todo = build_todo();
+            todo.subject = subject.to_string();
+            let s = export(std::slice::from_ref(&todo));
+            let imported = &import(&s).unwrap()[0];
+            assert_eq!(imported.subject, subject, "{}", s);
+            assert_eq!(imported.context, todo.context, "{}", s);
+            assert_eq!(imported.tags.encode(), todo.tags.encode(), "{}", s);
+            assert_eq!(imported.status, todo.status, "{}", s);
+            assert_eq!(imported.due, todo.due, "{}", s);
+        }
+    }
+
+    #[test]
+    fn import_todotxt() {
+        let s = "x (A) 2026-10-19 2026-10-01 Call mom @phone +family\n\
+            \n\
+            (E) Pick up @bob at noon +errand url:https://example.com due:2026-10-05\n\
+            Plain todo";
+        let todos = import(s).unwrap();
+        assert_eq!(todos.len(), 3);
+
+        assert_eq!(todos[0].status, Status::Done);
+        assert_eq!(todos[0].prio, Prio::Critical);
+        assert_eq!(todos[0].subject, "Call mom");
+        assert_eq!(todos[0].context.as_deref(), Some("phone"));
+        assert_eq!(todos[0].completed, Some(time(19)));
+        assert_eq!(todos[0].created, time(1));
+
+        assert_eq!(todos[1].id, ID::new(3));
+        assert_eq!(todos[1].prio, Prio::Low);
+        assert_eq!(
+            todos[1].subject,
+            "Pick up @bob at noon url:https://example.com"
+        );
+        assert_eq!(todos[1].context.as_deref(), Some("bob"));
+        assert_eq!(todos[1].tags.encode(), "errand");
+        assert_eq!(todos[1].due, NaiveDate::from_ymd_opt(2026, 10, 5));
+
+        assert_eq!(todos[2].status, Status::New);
+        assert_eq!(todos[2].prio, Prio::Normal);
+        assert_eq!(todos[2].subject, "Plain todo");
+
+        assert!(import("(A) Bad due:tomorrow").is_err());
+    }
+}
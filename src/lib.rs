@@ -1,6 +1,9 @@
+pub mod backup;
 pub mod cli;
+pub mod config;
 pub mod error;
 pub mod format;
+pub mod interop;
 pub mod model;
 pub mod repository;
 pub mod service;
@@ -1,10 +1,12 @@
-use super::{Todo, ID};
+use super::{Code, Todo, ID};
 use crate::err;
 use crate::error::Error;
 use crate::style::{Color, StyleDisplay, Styler};
+use chrono::{DateTime, Local, TimeZone};
 use core::fmt;
 use serde::{Deserialize, Serialize};
 
+/// Action is the type of change an event represents.
 #[derive(Deserialize, Serialize)]
 pub enum Action {
     Add,
@@ -46,7 +48,44 @@ impl TryFrom<String> for Action {
     }
 }
 
+/// State of an event with regards to undo and redo.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum State {
+    /// The change is in effect.
+    Applied,
+    /// The change has been undone and can be redone.
+    Undone,
+    /// The change was undone and can no longer be redone
+    /// since other changes have been made after it.
+    Discarded,
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            State::Applied => write!(f, "applied"),
+            State::Undone => write!(f, "undone"),
+            State::Discarded => write!(f, "discarded"),
+        }
+    }
+}
+
+impl TryFrom<String> for State {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "applied" => Ok(Self::Applied),
+            "undone" => Ok(Self::Undone),
+            "discarded" => Ok(Self::Discarded),
+            _ => err!("invalid event state: {}", value),
+        }
+    }
+}
+
+/// Kind describes what changed, including the data needed to revert it.
 #[derive(Deserialize, Serialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum Kind {
     AddTodo(Todo),
     UpdateTodo { before: Todo, after: Todo },
@@ -75,6 +114,39 @@ impl Kind {
             } => "set_context".to_string(),
         }
     }
+
+    pub fn action(&self) -> Action {
+        match self {
+            AddTodo(_) | AddContext(_) => Action::Add,
+            UpdateTodo { .. } | SetContext { .. } => Action::Update,
+            RemoveTodo(_) | RemoveContext(_, _) => Action::Remove,
+        }
+    }
+
+    /// Returns the ID of the todo this event is about, if any.
+    pub fn todo_id(&self) -> Option<ID> {
+        match self {
+            AddTodo(todo) | RemoveTodo(todo) => Some(todo.id),
+            UpdateTodo { before, after: _ } => Some(before.id),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the todo with `id` was affected by this event.
+    pub fn involves(&self, id: &ID) -> bool {
+        match self {
+            RemoveContext(_, todos) => todos.iter().any(|todo| &todo.id == id),
+            kind => kind.todo_id().as_ref() == Some(id),
+        }
+    }
+}
+
+// Formats an optional value for displaying changes.
+fn optional<T: fmt::Display>(value: &Option<T>) -> String {
+    match value {
+        Some(v) => v.to_string(),
+        None => "none".to_string(),
+    }
 }
 
 impl fmt::Display for Kind {
@@ -98,30 +170,62 @@ impl fmt::Display for Kind {
                 if before.description != after.description {
                     changes.push("description: <...>".to_string());
                 }
-                if before.context != after.context {
-                    if before.context.is_some() && after.context.is_some() {
-                        changes.push(format!(
-                            "context: '{}' -> '{}'",
-                            before.context.as_ref().unwrap(),
-                            after.context.as_ref().unwrap()
-                        ));
-                    } else if before.context.is_some() {
-                        changes.push(format!(
-                            "context: removed from '{}'",
-                            before.context.as_ref().unwrap()
-                        ));
-                    } else if after.context.is_some() {
-                        changes.push(format!(
-                            "context: set to '{}'",
-                            after.context.as_ref().unwrap()
-                        ));
+                if before.due != after.due {
+                    changes.push(format!(
+                        "due: '{}' -> '{}'",
+                        optional(&before.due),
+                        optional(&after.due)
+                    ))
+                }
+                if before.after != after.after {
+                    changes.push(format!(
+                        "deferred: '{}' -> '{}'",
+                        optional(&before.after),
+                        optional(&after.after)
+                    ))
+                }
+                if before.recurrence != after.recurrence {
+                    changes.push(format!(
+                        "recurrence: '{}' -> '{}'",
+                        optional(&before.recurrence),
+                        optional(&after.recurrence)
+                    ))
+                }
+                if before.parent != after.parent {
+                    changes.push(format!(
+                        "parent: '{}' -> '{}'",
+                        optional(&before.parent),
+                        optional(&after.parent)
+                    ))
+                }
+                if before.tags.encode() != after.tags.encode() {
+                    changes.push(format!("tags: '{}' -> '{}'", before.tags, after.tags))
+                }
+                if before.links.encode() != after.links.encode() {
+                    changes.push(format!("links: '{}' -> '{}'", before.links, after.links))
+                }
+                match (&before.context, &after.context) {
+                    (Some(b), Some(a)) if b != a => {
+                        changes.push(format!("context: '{}' -> '{}'", b, a));
+                    }
+                    (Some(b), None) => {
+                        changes.push(format!("context: removed from '{}'", b));
+                    }
+                    (None, Some(a)) => {
+                        changes.push(format!("context: set to '{}'", a));
                     }
+                    _ => {}
                 }
 
                 if changes.is_empty() {
-                    write!(f, "Updated todo: no changes")
+                    write!(f, "Updated todo with ID {}: no changes", after.id)
                 } else {
-                    write!(f, "Updated todo: {}", changes.join(", "))
+                    write!(
+                        f,
+                        "Updated todo with ID {}: {}",
+                        after.id,
+                        changes.join(", ")
+                    )
                 }
             }
             Kind::RemoveTodo(todo) => write!(f, "Removed todo with ID {}", todo.id),
@@ -151,22 +255,52 @@ impl StyleDisplay for Kind {
     }
 }
 
+/// Event is a record of a change made to todos or contexts.
 pub struct Event {
-    pub id: ID,
+    /// Sequence number of this event.
+    pub id: i64,
     pub action: Action,
     pub kind: Kind,
+    /// Unix timestamp (seconds) when the event occurred.
     pub timestamp: i64,
+    /// Name of the user that made the change, if known.
+    pub author: Option<String>,
+    /// Events made by the same command share a batch.
+    pub batch: i64,
+    pub state: State,
 }
 
 impl Event {
-    pub fn new(id: ID, action: Action, kind: Kind, timestamp: i64) -> Self {
+    pub fn new(id: i64, action: Action, kind: Kind, timestamp: i64) -> Self {
         Self {
             id,
             action,
             kind,
             timestamp,
+            author: None,
+            batch: 0,
+            state: State::Applied,
         }
     }
+
+    pub fn with_batch(mut self, batch: i64, state: State) -> Self {
+        self.batch = batch;
+        self.state = state;
+        self
+    }
+
+    pub fn with_author(mut self, author: Option<String>) -> Self {
+        self.author = author;
+        self
+    }
+
+    /// Returns the timestamp as a local datetime.
+    pub fn datetime(&self) -> DateTime<Local> {
+        Local
+            .timestamp_opt(self.timestamp, 0)
+            .single()
+            .unwrap_or_default()
+    }
 }
 
 impl fmt::Display for Event {
@@ -1,14 +1,31 @@
 use super::{Code, Item, ID};
 use crate::{err, error::Error};
 use core::fmt;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum Link {
     /// Blocking another todo.
     Blocks(ID),
     /// Blocked by other todo.
     BlockedBy(ID),
     RelatesTo(ID),
+    /// Duplicates another todo.
+    Duplicates(ID),
+    /// Duplicated by another todo.
+    DuplicatedBy(ID),
+    /// Should be done after another todo.
+    Follows(ID),
+    /// Should be done before another todo.
+    Precedes(ID),
+    /// A link of a type declared in the configuration, see `LinkTypes`.
+    /// The inverse is the type of the link added to the other todo,
+    /// if it's bi-directional.
+    Custom {
+        name: String,
+        inverse: Option<String>,
+        id: ID,
+    },
 }
 
 use Link::*;
@@ -20,6 +37,19 @@ impl Link {
         match self {
             Blocks(_) => Some(BlockedBy(other)),
             BlockedBy(_) => Some(Blocks(other)),
+            Duplicates(_) => Some(DuplicatedBy(other)),
+            DuplicatedBy(_) => Some(Duplicates(other)),
+            Follows(_) => Some(Precedes(other)),
+            Precedes(_) => Some(Follows(other)),
+            Custom {
+                name,
+                inverse: Some(inverse),
+                id: _,
+            } => Some(Custom {
+                name: inverse.clone(),
+                inverse: Some(name.clone()),
+                id: other,
+            }),
             _ => None,
         }
     }
@@ -29,6 +59,19 @@ impl Link {
             Blocks(_) => Blocks(id),
             BlockedBy(_) => BlockedBy(id),
             RelatesTo(_) => RelatesTo(id),
+            Duplicates(_) => Duplicates(id),
+            DuplicatedBy(_) => DuplicatedBy(id),
+            Follows(_) => Follows(id),
+            Precedes(_) => Precedes(id),
+            Custom {
+                name,
+                inverse,
+                id: _,
+            } => Custom {
+                name: name.clone(),
+                inverse: inverse.clone(),
+                id,
+            },
         }
     }
 
@@ -45,17 +88,63 @@ impl Link {
             Blocks(id) => *id,
             BlockedBy(id) => *id,
             RelatesTo(id) => *id,
+            Duplicates(id) => *id,
+            DuplicatedBy(id) => *id,
+            Follows(id) => *id,
+            Precedes(id) => *id,
+            Custom { id, .. } => *id,
+        }
+    }
+
+    /// Returns the name of the type of this link, e.g. blocked-by.
+    pub fn name(&self) -> &str {
+        match self {
+            Blocks(_) => "blocks",
+            BlockedBy(_) => "blocked-by",
+            RelatesTo(_) => "relates-to",
+            Duplicates(_) => "duplicates",
+            DuplicatedBy(_) => "duplicated-by",
+            Follows(_) => "follows",
+            Precedes(_) => "precedes",
+            Custom { name, .. } => name,
         }
     }
+
+    /// The kind of link as stored, e.g. `blockedby`.
+    pub fn kind(&self) -> &str {
+        match self {
+            Blocks(_) => "blocks",
+            BlockedBy(_) => "blockedby",
+            RelatesTo(_) => "relatesto",
+            Duplicates(_) => "duplicates",
+            DuplicatedBy(_) => "duplicatedby",
+            Follows(_) => "follows",
+            Precedes(_) => "precedes",
+            Custom { name, .. } => name,
+        }
+    }
+}
+
+// Returns true if `name` can be used as the name of a link type.
+fn is_valid_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+}
+
+// Returns true if links named `name` are parsed as a built-in link,
+// including its aliases like blocked_by.
+fn is_built_in(name: &str) -> bool {
+    !matches!(
+        Link::try_from(format!("{}:1", name).as_str()),
+        Ok(Custom { .. })
+    )
 }
 
 impl fmt::Display for Link {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Blocks(id) => write!(f, "blocks {}", id),
-            BlockedBy(id) => write!(f, "blocked by {}", id),
-            RelatesTo(id) => write!(f, "relates to {}", id),
-        }
+        write!(f, "{} {}", self.name().replace(['-', '_'], " "), self.id())
     }
 }
 
@@ -74,6 +163,15 @@ impl TryFrom<&str> for Link {
             "blocks" => Ok(Self::Blocks(id)),
             "blocked-by" | "blocked_by" | "blockedby" => Ok(Self::BlockedBy(id)),
             "relates-to" | "relates_to" | "relatesto" => Ok(Self::RelatesTo(id)),
+            "duplicates" => Ok(Self::Duplicates(id)),
+            "duplicated-by" | "duplicated_by" | "duplicatedby" => Ok(Self::DuplicatedBy(id)),
+            "follows" => Ok(Self::Follows(id)),
+            "precedes" => Ok(Self::Precedes(id)),
+            s if is_valid_name(s) => Ok(Self::Custom {
+                name: s.to_string(),
+                inverse: None,
+                id,
+            }),
             s => err!("invalid link: {}", s),
         }
     }
@@ -88,41 +186,136 @@ impl TryFrom<String> for Link {
 
 impl Code for Link {
     fn encode(&self) -> String {
-        match self {
-            Blocks(id) => format!("blocks:{}", id),
-            BlockedBy(id) => format!("blockedby:{}", id),
-            RelatesTo(id) => format!("relatesto:{}", id),
-        }
+        format!("{}:{}", self.kind(), self.id())
     }
 
-    fn decode(s: &str) -> Self {
-        Self::try_from(s).unwrap()
+    fn decode(s: &str) -> Result<Self, Error> {
+        Self::try_from(s)
     }
 }
 
 impl Item for Link {}
 
+impl Serialize for Link {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+impl<'de> Deserialize<'de> for Link {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::try_from(s).map_err(serde::de::Error::custom)
+    }
+}
+
+// Links are equal if they are of the same type and link to the same todo.
 impl PartialEq for Link {
     fn eq(&self, other: &Self) -> bool {
-        match self {
-            Blocks(my_id) => match other {
-                Blocks(other_id) => my_id == other_id,
-                _ => false,
-            },
-            BlockedBy(my_id) => match other {
-                BlockedBy(other_id) => my_id == other_id,
-                _ => false,
-            },
-            RelatesTo(my_id) => match other {
-                RelatesTo(other_id) => my_id == other_id,
-                _ => false,
-            },
-        }
+        self.name() == other.name() && self.id() == other.id()
     }
 }
 
 impl Eq for Link {}
 
+/// A link type declared in the configuration, like so:
+///
+/// ```toml
+/// [[link]]
+/// name = "implements"
+/// inverse = "implemented-by"
+/// ```
+///
+/// Links of a type with an inverse are bi-directional: when todo `a`
+/// implements `b`, then `b` is implemented by `a`. A link type can also
+/// be its own inverse by setting `bidirectional = true` without an inverse.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LinkType {
+    pub name: String,
+    #[serde(default)]
+    pub inverse: Option<String>,
+    #[serde(default)]
+    pub bidirectional: bool,
+}
+
+impl LinkType {
+    // Returns the inverse, if this type is bi-directional.
+    fn inverse(&self) -> Option<&str> {
+        match &self.inverse {
+            Some(inverse) => Some(inverse),
+            None if self.bidirectional => Some(&self.name),
+            None => None,
+        }
+    }
+}
+
+/// Registry of user-defined link types.
+#[derive(Default)]
+pub struct LinkTypes(Vec<LinkType>);
+
+impl LinkTypes {
+    /// Creates a registry of the link types after validating them.
+    pub fn new(types: Vec<LinkType>) -> Result<Self, Error> {
+        let mut names: Vec<&str> = Vec::new();
+        for t in &types {
+            // A type that is its own inverse declares its name once
+            let inverse = t.inverse.as_deref().filter(|inverse| *inverse != t.name);
+            for name in [Some(t.name.as_str()), inverse].into_iter().flatten() {
+                if !is_valid_name(name) {
+                    return err!("invalid link type name: {}", name);
+                }
+                if is_built_in(name) {
+                    return err!("link type is built-in: {}", name);
+                }
+                if names.contains(&name) {
+                    return err!("link type declared more than once: {}", name);
+                }
+                names.push(name);
+            }
+        }
+        Ok(Self(types))
+    }
+
+    /// Returns true if a link type with `name` is declared,
+    /// either as a name or inverse.
+    pub fn contains(&self, name: &str) -> bool {
+        self.0
+            .iter()
+            .any(|t| t.name == name || t.inverse.as_deref() == Some(name))
+    }
+
+    /// Sets the inverse of a custom link according to the declared type.
+    /// Other links, and links of undeclared types, are returned as is.
+    pub fn resolve(&self, link: Link) -> Link {
+        let (name, id) = match &link {
+            Custom { name, id, .. } => (name, *id),
+            _ => return link,
+        };
+
+        let inverse = self.0.iter().find_map(|t| {
+            if &t.name == name {
+                t.inverse().map(String::from)
+            } else if t.inverse.as_ref() == Some(name) {
+                Some(t.name.clone())
+            } else {
+                None
+            }
+        });
+
+        Custom {
+            name: name.clone(),
+            inverse,
+            id,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -136,6 +329,129 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_new_and_custom_links() {
+        let tests = [
+            ("duplicates:1", Duplicates(ID::new(1))),
+            ("duplicated-by:1", DuplicatedBy(ID::new(1))),
+            ("follows:2", Follows(ID::new(2))),
+            ("precedes:2", Precedes(ID::new(2))),
+        ];
+        for (s, expected) in tests {
+            assert_eq!(Link::try_from(s).unwrap(), expected);
+        }
+
+        let link = Link::try_from("implements:3").unwrap();
+        assert_eq!(link.name(), "implements");
+        assert_eq!(link.id(), ID::new(3));
+    }
+
+    #[test]
+    fn encode_decode() {
+        // Encodings used by existing databases must keep working
+        let tests = [
+            "blocks:1",
+            "blockedby:2",
+            "relatesto:3",
+            "duplicatedby:4",
+            "precedes:5",
+            "implements:6",
+        ];
+        for t in tests {
+            assert_eq!(Link::decode(t).unwrap().encode(), t);
+        }
+    }
+
+    #[test]
+    fn bi_directional() {
+        let id = ID::new(1);
+        assert_eq!(Blocks(id).bi_directional(id), Some(BlockedBy(id)));
+        assert_eq!(Duplicates(id).bi_directional(id), Some(DuplicatedBy(id)));
+        assert_eq!(Precedes(id).bi_directional(id), Some(Follows(id)));
+        assert_eq!(RelatesTo(id).bi_directional(id), None);
+    }
+
+    fn link_types() -> LinkTypes {
+        LinkTypes::new(vec![
+            LinkType {
+                name: "implements".to_string(),
+                inverse: Some("implemented-by".to_string()),
+                bidirectional: false,
+            },
+            LinkType {
+                name: "pairs".to_string(),
+                inverse: None,
+                bidirectional: true,
+            },
+            LinkType {
+                name: "mentions".to_string(),
+                inverse: None,
+                bidirectional: false,
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn resolve_custom_links() {
+        let types = link_types();
+        let other = ID::new(2);
+        let tests = [
+            ("implements:1", Some("implemented-by")),
+            ("implemented-by:1", Some("implements")),
+            ("pairs:1", Some("pairs")),
+            ("mentions:1", None),
+            ("unknown:1", None),
+        ];
+        for (s, inverse) in tests {
+            let link = types.resolve(Link::try_from(s).unwrap());
+            let bi = link.bi_directional(other);
+            assert_eq!(bi.as_ref().map(|l| l.name()), inverse, "{}", s);
+            if let Some(bi) = bi {
+                assert_eq!(bi.id(), other);
+            }
+        }
+        assert!(types.contains("implemented-by"));
+        assert!(!types.contains("unknown"));
+    }
+
+    #[test]
+    fn reject_invalid_link_types() {
+        let tests = [
+            ("blocks", None),
+            ("blocked_by", None),
+            ("blockedby", None),
+            ("implements", Some("relatesto")),
+            ("implements", Some("duplicatedby")),
+            ("Upper", None),
+            ("implements", Some("follows")),
+            ("implements", Some("implements-by,")),
+        ];
+        for (name, inverse) in tests {
+            let t = LinkType {
+                name: name.to_string(),
+                inverse: inverse.map(String::from),
+                bidirectional: false,
+            };
+            assert!(LinkTypes::new(vec![t]).is_err(), "{}", name);
+        }
+
+        let t = LinkType {
+            name: "implements".to_string(),
+            inverse: None,
+            bidirectional: false,
+        };
+        assert!(LinkTypes::new(vec![t.clone(), t]).is_err());
+
+        let t = LinkType {
+            name: "pairs".to_string(),
+            inverse: Some("pairs".to_string()),
+            bidirectional: false,
+        };
+        assert!(LinkTypes::new(vec![t.clone()]).is_ok());
+        assert!(LinkTypes::new(vec![t.clone(), t]).is_err());
+    }
+
     #[test]
     fn reject_invalid_links() {
         let tests = ["blocks", "blockedby:", "blocks:-1", "unknown"];
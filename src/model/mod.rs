@@ -1,21 +1,26 @@
 use crate::{err, error::Error};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
+pub mod event;
 pub mod link;
 pub mod prio;
+pub mod recurrence;
 pub mod status;
 pub mod tags;
 pub mod todo;
+pub mod view;
 
 pub use self::todo::*;
 pub use link::*;
 pub use prio::*;
+pub use recurrence::*;
 pub use status::*;
 pub use tags::*;
+pub use view::*;
 
 /// An identifier for Todos for simple referencing.
-#[derive(Clone, Copy, Debug, Eq, Serialize)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct ID(u16);
 
 impl ID {
@@ -35,21 +40,15 @@ impl TryFrom<&str> for ID {
     }
 }
 
-impl PartialEq for ID {
-    fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0
-    }
-}
-
 impl fmt::Display for ID {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
-pub trait Code {
+pub trait Code: Sized {
     fn encode(&self) -> String;
-    fn decode(s: &str) -> Self;
+    fn decode(s: &str) -> Result<Self, Error>;
 }
 
 impl Code for String {
@@ -57,8 +56,8 @@ impl Code for String {
         self.clone()
     }
 
-    fn decode(s: &str) -> Self {
-        s.to_string()
+    fn decode(s: &str) -> Result<Self, Error> {
+        Ok(s.to_string())
     }
 }
 
@@ -69,7 +68,7 @@ impl Item for String {}
 
 /// A container for values implementing Comma trait
 /// and adds ability to serialize/deserialize to string.
-#[derive(Clone, Debug, Default, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct CSV<T>(Vec<T>)
 where
     T: Item;
@@ -150,7 +149,7 @@ where
             "" => None,
             v => Some(v),
         }) {
-            let item = T::decode(v);
+            let item = T::decode(v)?;
             values.push(item);
         }
         Ok(Self(values))
@@ -166,8 +165,8 @@ where
         v.join(",")
     }
 
-    fn decode(s: &str) -> Self {
-        Self::try_from(s.to_string()).unwrap()
+    fn decode(s: &str) -> Result<Self, Error> {
+        Self::try_from(s.to_string())
     }
 }
 
@@ -193,7 +192,13 @@ mod tests {
 
     #[test]
     fn decode_csv() {
-        let csv: CSV<String> = CSV::decode("1,2,3,4");
+        let csv: CSV<String> = CSV::decode("1,2,3,4").unwrap();
         assert_eq!(4, csv.len());
     }
+
+    #[test]
+    fn decode_invalid_csv() {
+        let csv: Result<CSV<Link>, Error> = CSV::decode("blocks:1,blocks:x");
+        assert!(csv.is_err());
+    }
 }
@@ -0,0 +1,277 @@
+use super::Code;
+use crate::{err, error::Error};
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use core::fmt;
+use serde::{Deserialize, Serialize};
+
+/// Recurrence is a rule for when a todo should be done again
+/// after it has been completed.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Recurrence {
+    /// Every day.
+    Daily,
+    /// Every week on the given weekdays.
+    Weekly(Vec<Weekday>),
+    /// Every month on the given day of month.
+    Monthly(u32),
+    /// Every N days after the todo was completed.
+    Every(u64),
+}
+
+use Recurrence::*;
+
+impl Recurrence {
+    /// Returns the next date this recurs, given the date it was scheduled
+    /// for (due date or date of completion) and the date it was completed.
+    /// Dates missed before the completion are skipped.
+    pub fn next(&self, scheduled: NaiveDate, completed: NaiveDate) -> NaiveDate {
+        let mut next = scheduled;
+        loop {
+            match self.step(next, completed) {
+                Some(date) if date > completed => return date,
+                Some(date) => next = date,
+                None => return completed,
+            }
+        }
+    }
+
+    // Returns the date this recurs after `date`.
+    fn step(&self, date: NaiveDate, completed: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            Daily => date.checked_add_days(Days::new(1)),
+            Weekly(weekdays) if weekdays.is_empty() => date.checked_add_days(Days::new(7)),
+            Weekly(weekdays) => (1..=7)
+                .filter_map(|n| date.checked_add_days(Days::new(n)))
+                .find(|date| weekdays.contains(&date.weekday())),
+            Monthly(day) => {
+                let this_month = day_of_month(date, *day);
+                if this_month > date {
+                    Some(this_month)
+                } else {
+                    date.with_day(1)
+                        .and_then(|first| first.checked_add_months(Months::new(1)))
+                        .map(|next_month| day_of_month(next_month, *day))
+                }
+            }
+            Every(days) => completed.checked_add_days(Days::new(*days)),
+        }
+    }
+}
+
+// Returns the date with day of month set to `day`, or the last
+// day of the month if the month has fewer days.
+fn day_of_month(date: NaiveDate, day: u32) -> NaiveDate {
+    (1..=day)
+        .rev()
+        .find_map(|d| date.with_day(d))
+        .unwrap_or(date)
+}
+
+fn weekday_name(weekday: &Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "mon",
+        Weekday::Tue => "tue",
+        Weekday::Wed => "wed",
+        Weekday::Thu => "thu",
+        Weekday::Fri => "fri",
+        Weekday::Sat => "sat",
+        Weekday::Sun => "sun",
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Daily => write!(f, "daily"),
+            Weekly(weekdays) if weekdays.is_empty() => write!(f, "weekly"),
+            Weekly(weekdays) => {
+                let days: Vec<&str> = weekdays.iter().map(weekday_name).collect();
+                write!(f, "weekly on {}", days.join(", "))
+            }
+            Monthly(day) => write!(f, "monthly on day {}", day),
+            Every(1) => write!(f, "every day after completion"),
+            Every(days) => write!(f, "every {} days after completion", days),
+        }
+    }
+}
+
+impl TryFrom<&str> for Recurrence {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim().to_lowercase();
+        let (kind, arg) = match value.split_once(':') {
+            Some((kind, arg)) => (kind, Some(arg.trim())),
+            None => (value.as_str(), None),
+        };
+
+        match (kind, arg) {
+            ("daily", None) => Ok(Daily),
+            ("weekly", None) => Ok(Weekly(vec![])),
+            ("weekly", Some(days)) => {
+                let mut weekdays = Vec::new();
+                for day in days.split(',').map(|s| s.trim()) {
+                    match day.parse::<Weekday>() {
+                        Ok(weekday) if !weekdays.contains(&weekday) => weekdays.push(weekday),
+                        Ok(_) => {}
+                        Err(_) => return err!("invalid weekday: {}", day),
+                    }
+                }
+                weekdays.sort_by_key(|d| d.num_days_from_monday());
+                Ok(Weekly(weekdays))
+            }
+            ("monthly", Some(day)) => match day.parse::<u32>() {
+                Ok(day) if (1..=31).contains(&day) => Ok(Monthly(day)),
+                _ => err!("invalid day of month: {}", day),
+            },
+            ("every", Some(days)) => match days.trim_end_matches('d').parse::<u64>() {
+                Ok(days) if days > 0 => Ok(Every(days)),
+                _ => err!("invalid number of days: {}", days),
+            },
+            _ => err!("invalid recurrence: {}", value),
+        }
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::try_from(value.as_str())
+    }
+}
+
+impl Code for Recurrence {
+    fn encode(&self) -> String {
+        match self {
+            Daily => "daily".to_string(),
+            Weekly(weekdays) if weekdays.is_empty() => "weekly".to_string(),
+            Weekly(weekdays) => {
+                let days: Vec<&str> = weekdays.iter().map(weekday_name).collect();
+                format!("weekly:{}", days.join(","))
+            }
+            Monthly(day) => format!("monthly:{}", day),
+            Every(days) => format!("every:{}", days),
+        }
+    }
+
+    fn decode(s: &str) -> Result<Self, Error> {
+        Self::try_from(s)
+    }
+}
+
+impl Serialize for Recurrence {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.encode())
+    }
+}
+
+impl<'de> Deserialize<'de> for Recurrence {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Self::try_from(s).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parse_valid() {
+        let tests = [
+            ("daily", Daily),
+            ("weekly", Weekly(vec![])),
+            ("weekly:fri,mon", Weekly(vec![Weekday::Mon, Weekday::Fri])),
+            ("monthly:15", Monthly(15)),
+            ("every:3", Every(3)),
+            ("every:10d", Every(10)),
+        ];
+        for (s, expected) in tests {
+            assert_eq!(Recurrence::try_from(s).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn parse_invalid() {
+        let tests = [
+            "",
+            "yearly",
+            "weekly:someday",
+            "monthly",
+            "monthly:32",
+            "every:0",
+        ];
+        for t in tests {
+            assert!(Recurrence::try_from(t).is_err());
+        }
+    }
+
+    #[test]
+    fn encode_decode() {
+        let tests = [Daily, Weekly(vec![Weekday::Tue]), Monthly(1), Every(2)];
+        for r in tests {
+            assert_eq!(Recurrence::decode(&r.encode()).unwrap(), r);
+        }
+    }
+
+    #[test]
+    fn next_date() {
+        // 2022-03-02 is a wednesday
+        let scheduled = date(2022, 3, 2);
+        let completed = date(2022, 3, 4);
+        let tests = [
+            (Daily, date(2022, 3, 5)),
+            (Weekly(vec![]), date(2022, 3, 9)),
+            (Weekly(vec![Weekday::Mon, Weekday::Fri]), date(2022, 3, 7)),
+            (Weekly(vec![Weekday::Wed]), date(2022, 3, 9)),
+            (Monthly(15), date(2022, 3, 15)),
+            (Monthly(1), date(2022, 4, 1)),
+            (Every(3), date(2022, 3, 7)),
+        ];
+        for (r, expected) in tests {
+            assert_eq!(r.next(scheduled, completed), expected, "{}", r);
+        }
+    }
+
+    #[test]
+    fn next_date_completed_late() {
+        // 2026-10-01 is a thursday
+        let scheduled = date(2026, 10, 1);
+        let completed = date(2026, 10, 18);
+        let tests = [
+            (Daily, date(2026, 10, 19)),
+            (Weekly(vec![]), date(2026, 10, 22)),
+            (Weekly(vec![Weekday::Mon]), date(2026, 10, 19)),
+            (Monthly(1), date(2026, 11, 1)),
+            (Monthly(18), date(2026, 11, 18)),
+            (Every(2), date(2026, 10, 20)),
+        ];
+        for (r, expected) in tests {
+            assert_eq!(r.next(scheduled, completed), expected, "{}", r);
+        }
+        assert_eq!(
+            Daily.next(date(2026, 10, 20), completed),
+            date(2026, 10, 21)
+        );
+    }
+
+    #[test]
+    fn next_monthly_clamps_to_end_of_month() {
+        let r = Monthly(31);
+        assert_eq!(
+            r.next(date(2022, 1, 31), date(2022, 1, 31)),
+            date(2022, 2, 28)
+        );
+    }
+}
@@ -133,7 +133,7 @@ impl Ord for Status {
             },
             Done => match other {
                 Done => Ordering::Equal,
-                _ => Ordering::Less,
+                _ => Ordering::Greater,
             },
         }
     }
@@ -157,6 +157,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ordering() {
+        let mut statuses = vec![Done, Blocked, New, Started];
+        statuses.sort();
+        assert_eq!(statuses, vec![Started, New, Blocked, Done]);
+        assert!(Done > Blocked);
+    }
+
     #[test]
     fn try_from_invalid() {
         let r = Status::try_from("unknown");
@@ -1,15 +1,16 @@
-use super::{Link, Prio, Status, CSV, ID};
-use chrono::{DateTime, Local};
+use super::{Link, Prio, Recurrence, Status, CSV, ID};
+use chrono::{DateTime, Days, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
 /// Todo is the central model for this projet and represents
 /// a unit of work that has a status (current state), priority, etc.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
 // FIXME! Refactor type into more fields:
 //   - keep basic fields in root: id, created, status
 //   - properties: prio, subject, description, context
 //   - metadata: links, tags
-//   - extras (not implement, find better name): after, recurring
+//   - extras (find better name): due, after, recurrence, parent
 pub struct Todo {
     /// ID of this todo.
     pub id: ID,
@@ -30,6 +31,19 @@ pub struct Todo {
     pub links: CSV<Link>,
     /// Tags can include certain attributes for a todo.
     pub tags: CSV<String>,
+    /// The date, if any, when this todo must be finished.
+    pub due: Option<NaiveDate>,
+    /// Rule for when this todo recurs after it is done, if ever.
+    pub recurrence: Option<Recurrence>,
+    /// The date, if any, before which this todo isn't relevant.
+    /// Todos are deferred, i.e. hidden by default, until this date.
+    pub after: Option<NaiveDate>,
+    /// The todo, if any, that this todo is a subtask of.
+    pub parent: Option<ID>,
+    /// The time, if any, when this todo was done.
+    pub completed: Option<DateTime<Local>>,
+    /// The UID, if any, of this todo in the tool it was imported from.
+    pub uid: Option<String>,
 }
 
 impl Todo {
@@ -56,9 +70,45 @@ impl Todo {
             tags,
             context,
             links,
+            due: None,
+            recurrence: None,
+            after: None,
+            parent: None,
+            completed: None,
+            uid: None,
         }
     }
 
+    pub fn with_due(mut self, due: Option<NaiveDate>) -> Self {
+        self.due = due;
+        self
+    }
+
+    pub fn with_recurrence(mut self, recurrence: Option<Recurrence>) -> Self {
+        self.recurrence = recurrence;
+        self
+    }
+
+    pub fn with_after(mut self, after: Option<NaiveDate>) -> Self {
+        self.after = after;
+        self
+    }
+
+    pub fn with_parent(mut self, parent: Option<ID>) -> Self {
+        self.parent = parent;
+        self
+    }
+
+    pub fn with_completed(mut self, completed: Option<DateTime<Local>>) -> Self {
+        self.completed = completed;
+        self
+    }
+
+    pub fn with_uid(mut self, uid: Option<String>) -> Self {
+        self.uid = uid;
+        self
+    }
+
     /// Returns true if the status of this todo is done.
     pub fn is_done(&self) -> bool {
         matches!(self.status, Status::Done)
@@ -68,6 +118,48 @@ impl Todo {
         matches!(self.status, Status::Blocked)
     }
 
+    /// Returns true if this todo isn't done and the due date has passed.
+    pub fn is_overdue(&self) -> bool {
+        self.is_overdue_at(Local::now().date_naive())
+    }
+
+    /// Compares todos like `cmp`, except that todos overdue at `today` are
+    /// put first, the one with the earliest due date first.
+    pub fn cmp_at(&self, other: &Self, today: NaiveDate) -> Ordering {
+        match (self.is_overdue_at(today), other.is_overdue_at(today)) {
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (true, true) => self.due.cmp(&other.due).then_with(|| self.cmp(other)),
+            (false, false) => self.cmp(other),
+        }
+    }
+
+    pub fn is_overdue_at(&self, today: NaiveDate) -> bool {
+        match self.due {
+            Some(due) => !self.is_done() && due < today,
+            None => false,
+        }
+    }
+
+    /// Returns true if the todo is due within `days` from `today`,
+    /// including todos that are overdue.
+    pub fn is_due_within(&self, today: NaiveDate, days: u64) -> bool {
+        match (self.due, today.checked_add_days(Days::new(days))) {
+            (Some(due), Some(limit)) => due <= limit,
+            // Any due date is within a number of days that overflows
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// Returns true if this todo is deferred to a date after `today`.
+    pub fn is_deferred_at(&self, today: NaiveDate) -> bool {
+        match self.after {
+            Some(after) => after > today,
+            None => false,
+        }
+    }
+
     pub fn blocks(&self) -> Vec<&Link> {
         self.links
             .values()
@@ -92,8 +184,10 @@ impl PartialOrd for Todo {
 }
 
 /// Ordering of todos is based on fields in order:
-///     prio > status > created
+///     prio > status > due > created
 ///
+/// Todos that are done are put last. See `Todo::cmp_at` for the order
+/// that also puts overdue todos first.
 /// Note that Ordering::Less means it ends up before other
 /// values when sorting.
 impl Ord for Todo {
@@ -108,10 +202,23 @@ impl Ord for Todo {
         // The following comparison is based on status != done
         match self.prio.cmp(&other.prio) {
             Ordering::Equal => match self.status.cmp(&other.status) {
-                Ordering::Equal => self.created.cmp(&other.created),
+                Ordering::Equal => match cmp_due(&self.due, &other.due) {
+                    Ordering::Equal => self.created.cmp(&other.created),
+                    ordering => ordering,
+                },
                 ordering => ordering,
             },
             o => o,
         }
     }
 }
+
+// Todos with a due date ends up before those without one.
+fn cmp_due(a: &Option<NaiveDate>, b: &Option<NaiveDate>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
@@ -0,0 +1,92 @@
+use crate::err;
+use crate::error::Error;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How the todos of a view are shown.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    #[default]
+    Table,
+    /// Subtasks indented under their parent.
+    Tree,
+    /// Detailed output of each todo.
+    Details,
+}
+
+impl Layout {
+    pub fn values() -> [&'static str; 3] {
+        ["table", "tree", "details"]
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Layout::Table => write!(f, "table"),
+            Layout::Tree => write!(f, "tree"),
+            Layout::Details => write!(f, "details"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Layout {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "table" => Ok(Layout::Table),
+            "tree" => Ok(Layout::Tree),
+            "details" => Ok(Layout::Details),
+            _ => err!("invalid layout: {}", value),
+        }
+    }
+}
+
+/// A filter expression saved under a name, to list todos the same way
+/// again later. A view named `default` replaces the default listing.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct View {
+    pub name: String,
+    pub filter: String,
+    pub layout: Layout,
+    /// Sort order, e.g. `prio,-created`, or the default order if none.
+    pub sort: Option<String>,
+}
+
+impl View {
+    pub const DEFAULT: &'static str = "default";
+
+    pub fn new(name: String, filter: String) -> Self {
+        Self {
+            name,
+            filter,
+            layout: Layout::default(),
+            sort: None,
+        }
+    }
+
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn with_sort(mut self, sort: Option<String>) -> Self {
+        self.sort = sort;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_try_from() {
+        for value in Layout::values() {
+            assert_eq!(Layout::try_from(value).unwrap().to_string(), value);
+        }
+        assert!(Layout::try_from("grid").is_err());
+    }
+}
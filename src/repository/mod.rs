@@ -1,9 +1,17 @@
+use crate::backup;
 use crate::err;
 use crate::error::{map_sqlx_error, Error, Result};
-use crate::model::{Code, Link, Prio, Status, Todo, CSV, ID};
-use chrono::{DateTime, Local};
-use sqlx::sqlite::{SqlitePool, SqliteRow};
-use sqlx::Row;
+use crate::model::event::{Action, Event, Kind, State};
+use crate::model::{Code, Layout, Link, Prio, Recurrence, Status, Todo, View, CSV, ID};
+use chrono::{DateTime, Local, NaiveDate};
+use sqlx::sqlite::{Sqlite, SqliteArguments, SqlitePool, SqliteRow};
+use sqlx::{Row, Transaction};
+use std::collections::HashMap;
+use std::path::Path;
+
+mod query;
+
+pub use query::Query;
 
 pub struct Repository {
     pool: SqlitePool,
@@ -18,82 +26,181 @@ impl Repository {
     pub async fn get_todo(&self, id: &ID) -> Result<Todo> {
         let result = sqlx::query("SELECT * FROM todos WHERE id = $1")
             .bind(id.to_string())
-            .map(map_todo)
             .fetch_one(&self.pool)
             .await;
 
-        match result {
-            Ok(todo) => Ok(todo),
+        let mut todo = match result {
+            Ok(row) => map_todo(row)?,
             Err(err) => match map_sqlx_error(err) {
-                Error::NotFound(_) => Err(Error::NotFound(Some(id.to_string()))),
-                error => Err(error),
+                Error::NotFound(_) => return Err(Error::NotFound(Some(id.to_string()))),
+                error => return Err(error),
             },
+        };
+
+        let tags =
+            sqlx::query("SELECT todo_id, tag FROM todo_tags WHERE todo_id = $1 ORDER BY rowid")
+                .bind(id.to_string())
+                .fetch_all(&self.pool)
+                .await?;
+        let links = sqlx::query(
+            "SELECT from_id, to_id, kind FROM todo_links WHERE from_id = $1 ORDER BY rowid",
+        )
+        .bind(id.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        for row in tags {
+            todo.tags.push(row.get("tag"));
         }
+        for row in links {
+            todo.links.push(map_link(row)?);
+        }
+        Ok(todo)
     }
 
     pub async fn get_todos(&self) -> Result<Vec<Todo>> {
-        let result = sqlx::query("SELECT * FROM todos")
-            .map(map_todo)
+        self.find_todos(&Query::default()).await
+    }
+
+    /// Gets the todos matching the query.
+    pub async fn find_todos(&self, query: &Query) -> Result<Vec<Todo>> {
+        let (clause, params) = query.to_sql();
+        let source = query.source();
+
+        let sql = format!(
+            "SELECT todos.* FROM {source} WHERE {clause} ORDER BY {}",
+            query.order()
+        );
+        let rows = bind_all(sqlx::query(&sql), &params)
             .fetch_all(&self.pool)
-            .await;
-        match result {
-            Ok(todos) => Ok(todos),
-            Err(err) => err!(err),
+            .await
+            .map_err(|err| map_search_error(err, query))?;
+
+        let sql = format!(
+            "SELECT todo_id, tag FROM todo_tags
+            WHERE todo_id IN (SELECT todos.id FROM {source} WHERE {clause}) ORDER BY rowid"
+        );
+        let tags = bind_all(sqlx::query(&sql), &params)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let sql = format!(
+            "SELECT from_id, to_id, kind FROM todo_links
+            WHERE from_id IN (SELECT todos.id FROM {source} WHERE {clause}) ORDER BY rowid"
+        );
+        let links = bind_all(sqlx::query(&sql), &params)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut todos = Vec::new();
+        let mut index = HashMap::new();
+        for row in rows {
+            let todo = map_todo(row)?;
+            index.insert(todo.id, todos.len());
+            todos.push(todo);
+        }
+
+        for row in tags {
+            let id = ID::new(row.get("todo_id"));
+            if let Some(i) = index.get(&id) {
+                todos[*i].tags.push(row.get("tag"));
+            }
         }
+        for row in links {
+            let id = ID::new(row.get("from_id"));
+            if let Some(i) = index.get(&id) {
+                todos[*i].links.push(map_link(row)?);
+            }
+        }
+        Ok(todos)
     }
 
     pub async fn add_todo(&self, todo: Todo) -> Result<Todo> {
         let mut transaction = self.pool.begin().await?;
-        let todo = sqlx::query(
+        let row = sqlx::query(
             "INSERT INTO todos (
                 created,
                 subject,
                 status,
                 prio,
                 description,
-                tags,
                 context,
-                links
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, created, status, prio, subject, description, tags, context, links",
+                due,
+                recurrence,
+                after,
+                parent,
+                completed,
+                uid
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id, created, status, prio, subject, description, context, due, recurrence, after, parent, completed, uid",
         )
-        .bind(todo.created.format("%Y-%m-%d %H:%M:%S %z").to_string())
-        .bind(todo.subject)
+        .bind(format_time(todo.created))
+        .bind(&todo.subject)
         .bind(todo.status.to_string())
         .bind(todo.prio.to_string())
-        .bind(todo.description)
-        .bind(todo.tags.to_string())
-        .bind(todo.context)
-        .bind(todo.links.encode())
-        .map(map_todo)
-        .fetch_one(&mut transaction)
+        .bind(&todo.description)
+        .bind(&todo.context)
+        .bind(todo.due.map(|due| due.to_string()))
+        .bind(todo.recurrence.as_ref().map(|r| r.encode()))
+        .bind(todo.after.map(|after| after.to_string()))
+        .bind(todo.parent.map(|parent| parent.to_string()))
+        .bind(todo.completed.map(format_time))
+        .bind(&todo.uid)
+        .fetch_one(&mut *transaction)
         .await?;
 
+        let mut added = map_todo(row)?;
+        added.tags = todo.tags;
+        added.links = todo.links;
+        replace_tags_and_links(&mut transaction, &added).await?;
+
         transaction.commit().await?;
 
         log::debug!("Added todo in db");
 
-        Ok(todo)
+        Ok(added)
     }
 
+    // Inserts the todo or updates it if it already exists. Unlike REPLACE,
+    // this doesn't delete the row, which would fire the ON DELETE actions
+    // of links pointing to the todo.
     pub async fn replace_todo(&self, todo: &Todo) -> Result<()> {
         let mut transaction = self.pool.begin().await?;
         sqlx::query(
-            "REPLACE INTO todos (id, created, status, prio, subject, description, tags, context, links)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            "INSERT INTO todos (id, created, status, prio, subject, description, context, due, recurrence, after, parent, completed, uid)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (id) DO UPDATE SET
+                created = excluded.created,
+                status = excluded.status,
+                prio = excluded.prio,
+                subject = excluded.subject,
+                description = excluded.description,
+                context = excluded.context,
+                due = excluded.due,
+                recurrence = excluded.recurrence,
+                after = excluded.after,
+                parent = excluded.parent,
+                completed = excluded.completed,
+                uid = excluded.uid",
         )
         .bind(todo.id.to_string())
-        .bind(todo.created.format("%Y-%m-%d %H:%M:%S %z").to_string())
+        .bind(format_time(todo.created))
         .bind(todo.status.to_string())
         .bind(todo.prio.to_string())
         .bind(&todo.subject)
         .bind(&todo.description)
-        .bind(todo.tags.to_string())
         .bind(&todo.context)
-        .bind(todo.links.encode())
-        .execute(&mut transaction)
+        .bind(todo.due.map(|due| due.to_string()))
+        .bind(todo.recurrence.as_ref().map(|r| r.encode()))
+        .bind(todo.after.map(|after| after.to_string()))
+        .bind(todo.parent.map(|parent| parent.to_string()))
+        .bind(todo.completed.map(format_time))
+        .bind(&todo.uid)
+        .execute(&mut *transaction)
         .await?;
 
+        replace_tags_and_links(&mut transaction, todo).await?;
+
         transaction.commit().await?;
 
         log::debug!("Todo with id {} updated in db", todo.id);
@@ -101,16 +208,16 @@ impl Repository {
         Ok(())
     }
 
+    // Removes a todo, where its tags and links, including the links of
+    // other todos pointing to it, are removed by cascade.
     pub async fn remove_todo(&self, id: &ID) -> Result<Todo> {
+        let todo = self.get_todo(id).await?;
+
         let mut transaction = self.pool.begin().await?;
-        let todo = sqlx::query(
-            "DELETE FROM todos WHERE id = $1
-            RETURNING id, created, status, prio, subject, description, tags, context, links",
-        )
-        .bind(id.to_string())
-        .map(map_todo)
-        .fetch_one(&mut transaction)
-        .await?;
+        sqlx::query("DELETE FROM todos WHERE id = $1")
+            .bind(id.to_string())
+            .execute(&mut *transaction)
+            .await?;
 
         transaction.commit().await?;
 
@@ -120,6 +227,59 @@ impl Repository {
     }
 }
 
+// Reports errors of full-text queries as invalid queries, since SQLite
+// reports syntax errors in them both with and without an fts5 prefix.
+fn map_search_error(err: sqlx::Error, query: &Query) -> Error {
+    match (&err, &query.text) {
+        (sqlx::Error::Database(db), Some(text)) => {
+            Error::General(format!("invalid search query: {}: {}", text, db.message()))
+        }
+        _ => map_sqlx_error(err),
+    }
+}
+
+fn bind_all<'q>(
+    mut query: sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>>,
+    params: &'q [String],
+) -> sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>> {
+    for param in params {
+        query = query.bind(param);
+    }
+    query
+}
+
+// Rewrites the tags and outgoing links of a todo.
+async fn replace_tags_and_links(
+    transaction: &mut Transaction<'_, Sqlite>,
+    todo: &Todo,
+) -> Result<()> {
+    sqlx::query("DELETE FROM todo_tags WHERE todo_id = $1")
+        .bind(todo.id.to_string())
+        .execute(&mut **transaction)
+        .await?;
+    sqlx::query("DELETE FROM todo_links WHERE from_id = $1")
+        .bind(todo.id.to_string())
+        .execute(&mut **transaction)
+        .await?;
+
+    for tag in todo.tags.values() {
+        sqlx::query("INSERT OR IGNORE INTO todo_tags (todo_id, tag) VALUES ($1, $2)")
+            .bind(todo.id.to_string())
+            .bind(tag)
+            .execute(&mut **transaction)
+            .await?;
+    }
+    for link in todo.links.values() {
+        sqlx::query("INSERT OR IGNORE INTO todo_links (from_id, to_id, kind) VALUES ($1, $2, $3)")
+            .bind(todo.id.to_string())
+            .bind(link.id().to_string())
+            .bind(link.kind())
+            .execute(&mut **transaction)
+            .await?;
+    }
+    Ok(())
+}
+
 // For contexts.
 impl Repository {
     // Gets current context, if any.
@@ -153,7 +313,7 @@ impl Repository {
         let mut transaction = self.pool.begin().await?;
         sqlx::query("UPDATE context SET value = $1 WHERE id = 1")
             .bind(context)
-            .execute(&mut transaction)
+            .execute(&mut *transaction)
             .await?;
 
         transaction.commit().await?;
@@ -166,7 +326,7 @@ impl Repository {
     pub async fn unset_context(&self) -> Result<()> {
         let mut transaction = self.pool.begin().await?;
         sqlx::query("UPDATE context SET value = NULL WHERE id = 1")
-            .execute(&mut transaction)
+            .execute(&mut *transaction)
             .await?;
 
         transaction.commit().await?;
@@ -181,7 +341,7 @@ impl Repository {
         let mut transaction = self.pool.begin().await?;
         sqlx::query("INSERT INTO contexts (name) VALUES ($1)")
             .bind(context)
-            .execute(&mut transaction)
+            .execute(&mut *transaction)
             .await?;
 
         transaction.commit().await?;
@@ -196,7 +356,7 @@ impl Repository {
         let mut transaction = self.pool.begin().await?;
         sqlx::query("DELETE FROM contexts WHERE name = $1")
             .bind(context)
-            .execute(&mut transaction)
+            .execute(&mut *transaction)
             .await?;
 
         transaction.commit().await?;
@@ -207,31 +367,278 @@ impl Repository {
     }
 }
 
-fn map_todo(row: SqliteRow) -> Todo {
+// For views.
+impl Repository {
+    pub async fn get_view(&self, name: &str) -> Result<View> {
+        let result = sqlx::query("SELECT name, filter, layout, sort FROM views WHERE name = $1")
+            .bind(name)
+            .fetch_one(&self.pool)
+            .await;
+
+        match result {
+            Ok(row) => map_view(row),
+            Err(err) => match map_sqlx_error(err) {
+                Error::NotFound(_) => Err(Error::NotFound(Some(format!("view {}", name)))),
+                error => Err(error),
+            },
+        }
+    }
+
+    pub async fn get_views(&self) -> Result<Vec<View>> {
+        let rows = sqlx::query("SELECT name, filter, layout, sort FROM views ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+        rows.into_iter().map(map_view).collect()
+    }
+
+    // Adds a view or replaces the one with the same name.
+    pub async fn save_view(&self, view: &View) -> Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query(
+            "INSERT INTO views (name, filter, layout, sort) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (name) DO UPDATE
+            SET filter = excluded.filter, layout = excluded.layout, sort = excluded.sort",
+        )
+        .bind(&view.name)
+        .bind(&view.filter)
+        .bind(view.layout.to_string())
+        .bind(&view.sort)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        log::debug!("View saved in db: {}", view.name);
+
+        Ok(())
+    }
+
+    pub async fn remove_view(&self, name: &str) -> Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        let result = sqlx::query("DELETE FROM views WHERE name = $1")
+            .bind(name)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound(Some(format!("view {}", name))));
+        }
+
+        log::debug!("View removed from db: {name}");
+
+        Ok(())
+    }
+}
+
+// For the database.
+impl Repository {
+    /// Writes a snapshot of the database to `path`, see `backup::write`.
+    pub async fn backup(&self, path: &Path) -> Result<()> {
+        backup::write(&self.pool, path).await
+    }
+
+    /// Closes the connections to the database.
+    pub async fn close(&self) {
+        self.pool.close().await
+    }
+}
+
+// For events.
+impl Repository {
+    // Adds an event with the current time to a batch. Events that were
+    // undone can no longer be redone after this.
+    pub async fn add_event(&self, kind: &Kind, author: Option<String>, batch: i64) -> Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query("UPDATE events SET state = $1 WHERE state = $2")
+            .bind(State::Discarded.to_string())
+            .bind(State::Undone.to_string())
+            .execute(&mut *transaction)
+            .await?;
+
+        sqlx::query(
+            "INSERT INTO events (timestamp, author, action, kind, todo_id, data, batch)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(Local::now().timestamp())
+        .bind(author)
+        .bind(kind.action().to_string())
+        .bind(kind.type_str())
+        .bind(kind.todo_id().map(|id| id.to_string()))
+        .bind(serde_json::to_string(kind)?)
+        .bind(batch)
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        log::debug!("Event added to db: {}", kind.type_str());
+
+        Ok(())
+    }
+
+    // Gets events in the order they occurred, optionally only those
+    // regarding a todo and/or since a unix timestamp.
+    pub async fn get_events(&self, todo: Option<ID>, since: Option<i64>) -> Result<Vec<Event>> {
+        // Removed contexts may involve any number of todos, hence they
+        // are included here and filtered after being decoded.
+        let rows = sqlx::query(
+            "SELECT id, timestamp, author, action, data, batch, state FROM events
+            WHERE timestamp >= $1
+            AND ($2 IS NULL OR todo_id = $2 OR kind = 'remove_context')
+            ORDER BY id",
+        )
+        .bind(since.unwrap_or(0))
+        .bind(todo.map(|id| id.to_string()))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut events = Vec::new();
+        for row in rows {
+            let event = map_event(row)?;
+            match &todo {
+                Some(id) if !event.kind.involves(id) => {}
+                _ => events.push(event),
+            }
+        }
+        Ok(events)
+    }
+
+    // Gets the events of the latest batch that is applied.
+    pub async fn get_last_applied_batch(&self) -> Result<Vec<Event>> {
+        let rows = sqlx::query(
+            "SELECT id, timestamp, author, action, data, batch, state FROM events
+            WHERE batch = (SELECT MAX(batch) FROM events WHERE state = $1)
+            ORDER BY id",
+        )
+        .bind(State::Applied.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(map_event).collect()
+    }
+
+    // Gets the events of the batch that was undone last.
+    pub async fn get_last_undone_batch(&self) -> Result<Vec<Event>> {
+        let rows = sqlx::query(
+            "SELECT id, timestamp, author, action, data, batch, state FROM events
+            WHERE batch = (SELECT MIN(batch) FROM events WHERE state = $1)
+            ORDER BY id",
+        )
+        .bind(State::Undone.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(map_event).collect()
+    }
+
+    // Sets the state of all events in a batch.
+    pub async fn set_batch_state(&self, batch: i64, state: State) -> Result<()> {
+        let mut transaction = self.pool.begin().await?;
+        sqlx::query("UPDATE events SET state = $1 WHERE batch = $2")
+            .bind(state.to_string())
+            .bind(batch)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        log::debug!("Events in batch {batch} set to {state}");
+
+        Ok(())
+    }
+}
+
+fn map_view(row: SqliteRow) -> Result<View> {
+    let layout: String = row.get("layout");
+    let view = View::new(row.get("name"), row.get("filter"))
+        .with_layout(Layout::try_from(layout.as_str())?)
+        .with_sort(row.get("sort"));
+    Ok(view)
+}
+
+fn map_event(row: SqliteRow) -> Result<Event> {
+    let action: String = row.get("action");
+    let state: String = row.get("state");
+    let data: String = row.get("data");
+    let kind: Kind = serde_json::from_str(&data)?;
+
+    let event = Event::new(
+        row.get("id"),
+        Action::try_from(action)?,
+        kind,
+        row.get("timestamp"),
+    )
+    .with_author(row.get("author"))
+    .with_batch(row.get("batch"), State::try_from(state)?);
+    Ok(event)
+}
+
+// Maps a todo row, where tags and links are loaded separately.
+fn map_todo(row: SqliteRow) -> Result<Todo> {
     let created: String = row.get("created");
-    let created: DateTime<Local> = created.parse().unwrap();
+    let created: DateTime<Local> = match created.parse() {
+        Ok(created) => created,
+        Err(_) => return err!("invalid created time: {}", created),
+    };
+
+    let completed: Option<String> = row.get("completed");
+    let completed: Option<DateTime<Local>> = match completed {
+        Some(s) => match s.parse() {
+            Ok(completed) => Some(completed),
+            Err(_) => return err!("invalid completed time: {}", s),
+        },
+        None => None,
+    };
 
-    let tags: String = row.get("tags");
     let status: String = row.get("status");
     let prio: String = row.get("prio");
-    let prio = Prio::try_from(prio).unwrap();
     let context: Option<String> = row.get("context");
 
-    let links: Option<String> = row.get("links");
-    let links: CSV<Link> = match links {
-        Some(s) => CSV::decode(&s),
-        None => CSV::empty(),
-    };
+    let due: Option<String> = row.get("due");
+    let due = due.map(|s| parse_date(&s)).transpose()?;
+
+    let after: Option<String> = row.get("after");
+    let after = after.map(|s| parse_date(&s)).transpose()?;
 
-    Todo::new(
+    let recurrence: Option<String> = row.get("recurrence");
+    let recurrence = recurrence.map(|s| Recurrence::decode(&s)).transpose()?;
+
+    let parent: Option<u16> = row.get("parent");
+    let parent: Option<ID> = parent.map(ID::new);
+
+    let todo = Todo::new(
         ID::new(row.get("id")),
         created,
-        Status::try_from(status).unwrap(),
-        prio,
+        Status::try_from(status)?,
+        Prio::try_from(prio)?,
         row.get("subject"),
         row.get("description"),
-        CSV::try_from(tags).unwrap(),
+        CSV::empty(),
         context,
-        links,
+        CSV::empty(),
     )
+    .with_due(due)
+    .with_recurrence(recurrence)
+    .with_after(after)
+    .with_parent(parent)
+    .with_completed(completed)
+    .with_uid(row.get("uid"));
+    Ok(todo)
+}
+
+fn map_link(row: SqliteRow) -> Result<Link> {
+    let kind: String = row.get("kind");
+    let to_id: u16 = row.get("to_id");
+    Link::decode(&format!("{}:{}", kind, to_id))
+}
+
+fn format_time(time: DateTime<Local>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S %z").to_string()
+}
+
+fn parse_date(s: &str) -> Result<NaiveDate> {
+    match s.parse() {
+        Ok(date) => Ok(date),
+        Err(_) => err!("invalid date: {}", s),
+    }
 }
@@ -0,0 +1,137 @@
+use crate::model::Status;
+use chrono::{Datelike, NaiveDate};
+
+/// Conditions for selecting todos, evaluated in SQL.
+/// All conditions that are set must hold.
+#[derive(Debug, Default)]
+pub struct Query {
+    /// Only todos with this status.
+    pub status: Option<Status>,
+    /// Only todos without this status.
+    pub not_status: Option<Status>,
+    /// Only todos with this context, where `Some(None)` means no context.
+    pub context: Option<Option<String>>,
+    /// Only todos with at least one of the tags.
+    pub tags: Option<Vec<String>>,
+    /// Only todos that aren't done and are due before this date.
+    pub overdue_at: Option<NaiveDate>,
+    /// Only todos that are due on or before this date.
+    pub due_by: Option<NaiveDate>,
+    /// Only todos that aren't deferred to a date after this date.
+    pub available_at: Option<NaiveDate>,
+    /// Only todos whose subject or description matches this full-text
+    /// query, ordered by rank.
+    pub text: Option<String>,
+}
+
+impl Query {
+    /// The tables to select todos from.
+    pub(crate) fn source(&self) -> &str {
+        match &self.text {
+            Some(_) => "todos JOIN todos_fts ON todos_fts.rowid = todos.id",
+            None => "todos",
+        }
+    }
+
+    /// The order of the selected todos.
+    pub(crate) fn order(&self) -> &str {
+        match &self.text {
+            Some(_) => "todos_fts.rank",
+            None => "todos.id",
+        }
+    }
+
+    /// Builds a WHERE clause for the tables in `source` along with its
+    /// parameters, numbered from $1.
+    pub(crate) fn to_sql(&self) -> (String, Vec<String>) {
+        let mut clauses = Vec::new();
+        let mut params = Vec::new();
+        let mut param = |value: String| {
+            params.push(value);
+            format!("${}", params.len())
+        };
+
+        if let Some(text) = &self.text {
+            clauses.push(format!("todos_fts MATCH {}", param(text.clone())));
+        }
+
+        if let Some(status) = &self.status {
+            clauses.push(format!("status = {}", param(status.to_string())));
+        }
+        if let Some(status) = &self.not_status {
+            clauses.push(format!("status <> {}", param(status.to_string())));
+        }
+        match &self.context {
+            Some(Some(context)) => {
+                clauses.push(format!("context = {}", param(context.clone())));
+            }
+            Some(None) => clauses.push("context IS NULL".to_string()),
+            None => {}
+        }
+        if let Some(tags) = &self.tags {
+            if tags.is_empty() {
+                clauses.push("0".to_string());
+            } else {
+                let tags: Vec<String> = tags.iter().map(|tag| param(tag.clone())).collect();
+                clauses.push(format!(
+                    "EXISTS (SELECT 1 FROM todo_tags WHERE todo_tags.todo_id = todos.id AND tag IN ({}))",
+                    tags.join(", ")
+                ));
+            }
+        }
+        if let Some(date) = &self.overdue_at {
+            clauses.push(format!(
+                "status <> {} AND due < {}",
+                param(Status::Done.to_string()),
+                param(date.to_string())
+            ));
+        }
+        match &self.due_by {
+            // Dates are compared as text, which only orders four-digit years
+            Some(date) if date.year() > 9999 => clauses.push("due IS NOT NULL".to_string()),
+            Some(date) => clauses.push(format!("due <= {}", param(date.to_string()))),
+            None => {}
+        }
+        if let Some(date) = &self.available_at {
+            clauses.push(format!(
+                "(after IS NULL OR after <= {})",
+                param(date.to_string())
+            ));
+        }
+
+        if clauses.is_empty() {
+            ("1".to_string(), params)
+        } else {
+            (clauses.join(" AND "), params)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_query() {
+        let (sql, params) = Query::default().to_sql();
+        assert_eq!(sql, "1");
+        assert!(params.is_empty());
+    }
+
+    #[test]
+    fn numbered_params() {
+        let query = Query {
+            not_status: Some(Status::Done),
+            context: Some(Some("home".to_string())),
+            tags: Some(vec!["a".to_string(), "b".to_string()]),
+            ..Query::default()
+        };
+        let (sql, params) = query.to_sql();
+        assert_eq!(
+            sql,
+            "status <> $1 AND context = $2 AND EXISTS (SELECT 1 FROM todo_tags \
+            WHERE todo_tags.todo_id = todos.id AND tag IN ($3, $4))"
+        );
+        assert_eq!(params, vec!["done", "home", "a", "b"]);
+    }
+}
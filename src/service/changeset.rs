@@ -1,4 +1,5 @@
-use crate::model::{Link, Prio, Status, Todo, CSV};
+use crate::model::{Link, Prio, Recurrence, Status, Todo, CSV, ID};
+use chrono::NaiveDate;
 
 #[derive(Default)]
 pub struct Changeset {
@@ -9,6 +10,10 @@ pub struct Changeset {
     context: Option<Option<String>>,
     links: Option<CSV<Link>>,
     tags: Option<CSV<String>>,
+    due: Option<Option<NaiveDate>>,
+    recurrence: Option<Option<Recurrence>>,
+    after: Option<Option<NaiveDate>>,
+    pub parent: Option<Option<ID>>,
     updated: bool,
 }
 
@@ -39,6 +44,18 @@ impl Changeset {
         if let Some(s) = self.links {
             todo.links = s;
         }
+        if let Some(s) = self.due {
+            todo.due = s;
+        }
+        if let Some(s) = self.recurrence {
+            todo.recurrence = s;
+        }
+        if let Some(s) = self.after {
+            todo.after = s;
+        }
+        if let Some(s) = self.parent {
+            todo.parent = s;
+        }
     }
 
     pub fn with_subject(mut self, sub: String) -> Self {
@@ -87,4 +104,32 @@ impl Changeset {
         self.links = Some(links);
         self
     }
+
+    /// Sets the due date. Use `None` to unset it.
+    pub fn with_due(mut self, due: Option<NaiveDate>) -> Self {
+        self.updated = true;
+        self.due = Some(due);
+        self
+    }
+
+    /// Sets the recurrence rule. Use `None` to unset it.
+    pub fn with_recurrence(mut self, recurrence: Option<Recurrence>) -> Self {
+        self.updated = true;
+        self.recurrence = Some(recurrence);
+        self
+    }
+
+    /// Defers the todo until the given date. Use `None` to unset it.
+    pub fn with_after(mut self, after: Option<NaiveDate>) -> Self {
+        self.updated = true;
+        self.after = Some(after);
+        self
+    }
+
+    /// Makes the todo a subtask of `parent`. Use `None` to unset it.
+    pub fn with_parent(mut self, parent: Option<ID>) -> Self {
+        self.updated = true;
+        self.parent = Some(parent);
+        self
+    }
 }
@@ -0,0 +1,165 @@
+use crate::model::{Prio, Status, Todo, ID};
+use chrono::NaiveDate;
+use std::fmt;
+
+/// A field of a todo that a condition can be put on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Field {
+    Id,
+    Status,
+    Prio,
+    Tag,
+    Context,
+    Subject,
+    Created,
+    Due,
+    After,
+}
+
+impl Field {
+    /// Gets the field with the given name, if any.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "id" => Some(Field::Id),
+            "status" => Some(Field::Status),
+            "prio" | "priority" => Some(Field::Prio),
+            "tag" | "tags" => Some(Field::Tag),
+            "context" | "ctx" => Some(Field::Context),
+            "subject" => Some(Field::Subject),
+            "created" => Some(Field::Created),
+            "due" => Some(Field::Due),
+            "after" => Some(Field::After),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the field can be compared using `<`, `>` etc.
+    pub fn is_ordered(&self) -> bool {
+        matches!(
+            self,
+            Field::Id | Field::Prio | Field::Created | Field::Due | Field::After
+        )
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Field::Id => "id",
+            Field::Status => "status",
+            Field::Prio => "prio",
+            Field::Tag => "tag",
+            Field::Context => "context",
+            Field::Subject => "subject",
+            Field::Created => "created",
+            Field::Due => "due",
+            Field::After => "after",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Operator comparing a field with a value, where `:` and `=` are the same.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Op {
+    // Applies the operator to the outcome of an equality check, used for
+    // fields that aren't ordered.
+    fn equals(&self, eq: bool) -> bool {
+        match self {
+            Op::Ne => !eq,
+            _ => eq,
+        }
+    }
+
+    fn compare<T: Ord>(&self, a: T, b: T) -> bool {
+        match self {
+            Op::Eq => a == b,
+            Op::Ne => a != b,
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+        }
+    }
+}
+
+/// A value a field is compared with.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Id(ID),
+    Status(Status),
+    Prio(Prio),
+    Text(String),
+    Date(NaiveDate),
+    /// No value, e.g. todos without a due date.
+    None,
+}
+
+/// A parsed filter expression, e.g.
+/// `prio>=high and (tag:backend or tag:api) and not status:blocked`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Cond(Field, Op, Value),
+}
+
+impl Expr {
+    /// Returns true if the todo matches the expression.
+    pub fn matches(&self, todo: &Todo) -> bool {
+        match self {
+            Expr::And(a, b) => a.matches(todo) && b.matches(todo),
+            Expr::Or(a, b) => a.matches(todo) || b.matches(todo),
+            Expr::Not(expr) => !expr.matches(todo),
+            Expr::Cond(field, op, value) => matches_cond(todo, *field, *op, value),
+        }
+    }
+
+    /// Returns true if any condition in the expression is on `field`.
+    pub fn mentions(&self, field: Field) -> bool {
+        match self {
+            Expr::And(a, b) | Expr::Or(a, b) => a.mentions(field) || b.mentions(field),
+            Expr::Not(expr) => expr.mentions(field),
+            Expr::Cond(f, _, _) => *f == field,
+        }
+    }
+}
+
+fn matches_cond(todo: &Todo, field: Field, op: Op, value: &Value) -> bool {
+    match (field, value) {
+        (Field::Id, Value::Id(id)) => op.compare(&todo.id, id),
+        (Field::Status, Value::Status(status)) => op.equals(&todo.status == status),
+        // Higher priorities are ordered before lower ones when sorting.
+        (Field::Prio, Value::Prio(prio)) => op.compare(prio, &todo.prio),
+        (Field::Tag, Value::Text(tag)) => op.equals(todo.tags.contains(tag)),
+        (Field::Tag, Value::None) => op.equals(todo.tags.is_empty()),
+        (Field::Context, Value::Text(ctx)) => op.equals(todo.context.as_ref() == Some(ctx)),
+        (Field::Context, Value::None) => op.equals(todo.context.is_none()),
+        (Field::Subject, Value::Text(text)) => {
+            op.equals(todo.subject.to_lowercase().contains(&text.to_lowercase()))
+        }
+        (Field::Created, Value::Date(date)) => op.compare(&todo.created.date_naive(), date),
+        (Field::Due, value) => matches_date(todo.due, op, value),
+        (Field::After, value) => matches_date(todo.after, op, value),
+        _ => false,
+    }
+}
+
+// Todos without a date only match conditions on having no date.
+fn matches_date(date: Option<NaiveDate>, op: Op, value: &Value) -> bool {
+    match (date, value) {
+        (date, Value::None) => op.equals(date.is_none()),
+        (Some(date), Value::Date(other)) => op.compare(&date, other),
+        _ => false,
+    }
+}
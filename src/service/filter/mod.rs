@@ -0,0 +1,291 @@
+use crate::err;
+use crate::error::Error;
+use crate::model::{Status, Todo};
+use crate::repository::Query;
+use chrono::{DateTime, Days, Local, NaiveDate};
+
+mod expr;
+mod parser;
+
+pub use expr::{Expr, Field, Op, Value};
+
+/// Used to filter based on status.
+#[derive(Clone, Debug)]
+pub enum StatusFilter {
+    /// Include any status.
+    Any,
+    /// Include all statuses != done.
+    Relevant,
+    /// Only the given status.
+    Status(Status),
+}
+
+/// Used specifically to filter based on context.
+#[derive(Clone, Debug)]
+pub enum ContextFilter {
+    /// Context doesn't matter.
+    Any,
+    /// Only todos that doesn't have any context.
+    None,
+    /// Use the current context.
+    Current,
+    // Context with a given name.
+    Name(String),
+}
+
+/// Used to filter based on due date.
+#[derive(Clone, Debug)]
+pub enum DueFilter {
+    /// Due date doesn't matter.
+    Any,
+    /// Only todos that are overdue.
+    Overdue,
+    /// Only todos due today, including overdue todos.
+    Today,
+    /// Only todos due within the given number of days,
+    /// including overdue todos.
+    Within(u64),
+}
+
+impl TryFrom<&str> for DueFilter {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "any" => Ok(Self::Any),
+            "overdue" => Ok(Self::Overdue),
+            "today" => Ok(Self::Today),
+            s => match s.strip_suffix('d').unwrap_or(s).parse::<u64>() {
+                Ok(days) => Ok(Self::Within(days)),
+                Err(_) => err!("invalid due filter: {}", value),
+            },
+        }
+    }
+}
+
+/// Filter is used when listing todos, e.g. by status.
+/// The default filter includes only statuses != done,
+/// that has the current (in any) context and that isn't deferred.
+#[derive(Clone, Debug)]
+pub struct Filter {
+    /// Include only todos with this status.
+    /// None means all statuses.
+    status: StatusFilter,
+    /// Include only todos with the given context.
+    context: ContextFilter,
+    /// Todos with at least one of the tags.
+    tags: Option<Vec<String>>,
+    /// Include only todos with a due date matching this.
+    due: DueFilter,
+    /// Include todos that are deferred to a later date.
+    deferred: bool,
+    /// Include only todos matching this expression.
+    expr: Option<Expr>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            status: StatusFilter::Relevant,
+            context: ContextFilter::Current,
+            tags: None,
+            due: DueFilter::Any,
+            deferred: false,
+            expr: None,
+        }
+    }
+}
+
+impl Filter {
+    pub fn status(mut self, f: StatusFilter) -> Self {
+        self.status = f;
+        self
+    }
+
+    pub fn context(mut self, f: ContextFilter) -> Self {
+        self.context = f;
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = Some(tags);
+        self
+    }
+
+    pub fn due(mut self, f: DueFilter) -> Self {
+        self.due = f;
+        self
+    }
+
+    pub fn include_deferred(mut self, yes: bool) -> Self {
+        self.deferred = yes;
+        self
+    }
+
+    /// Conditions in the expression on status, context or deferral replace
+    /// the defaults for those, e.g. `status:done` lists todos that are done.
+    /// Todos must match all expressions if given more than once.
+    pub fn expr(mut self, expr: Expr) -> Self {
+        if expr.mentions(Field::Status) {
+            self.status = StatusFilter::Any;
+        }
+        if expr.mentions(Field::Context) {
+            self.context = ContextFilter::Any;
+        }
+        if expr.mentions(Field::After) {
+            self.deferred = true;
+        }
+        self.expr = match self.expr.take() {
+            Some(prev) => Some(Expr::And(Box::new(prev), Box::new(expr))),
+            None => Some(expr),
+        };
+        self
+    }
+
+    /// Keeps the todos that match the expression, if any. This is the part
+    /// of the filter that isn't included in `query`.
+    pub fn apply_expr(&self, todos: Vec<Todo>) -> Vec<Todo> {
+        match &self.expr {
+            Some(expr) => todos
+                .into_iter()
+                .filter(|todo| expr.matches(todo))
+                .collect(),
+            None => todos,
+        }
+    }
+
+    /// Translates the filter, except the expression, into a query evaluated
+    /// by the database, which matches the same todos as `apply_at`.
+    pub fn query(&self, current_context: Option<String>, today: NaiveDate) -> Query {
+        let mut query = Query::default();
+        match &self.status {
+            StatusFilter::Any => {}
+            StatusFilter::Relevant => query.not_status = Some(Status::Done),
+            StatusFilter::Status(status) => query.status = Some(status.clone()),
+        }
+        query.context = match &self.context {
+            ContextFilter::Any => None,
+            ContextFilter::None => Some(None),
+            ContextFilter::Current => current_context.map(Some),
+            ContextFilter::Name(ctx) => Some(Some(ctx.clone())),
+        };
+        query.tags = self.tags.clone();
+        match &self.due {
+            DueFilter::Any => {}
+            DueFilter::Overdue => query.overdue_at = Some(today),
+            DueFilter::Today => query.due_by = Some(today),
+            DueFilter::Within(days) => {
+                // Everything with a due date is due within a number of
+                // days that overflows.
+                let limit = today.checked_add_days(Days::new(*days));
+                query.due_by = Some(limit.unwrap_or(NaiveDate::MAX));
+            }
+        }
+        if !self.deferred {
+            query.available_at = Some(today);
+        }
+        query
+    }
+
+    pub fn apply(&self, todos: Vec<Todo>, current_context: Option<String>) -> Vec<Todo> {
+        let today = Local::now().date_naive();
+        self.apply_at(todos, current_context, today)
+    }
+
+    /// Same as `apply` but evaluates due dates relative to `today`.
+    pub fn apply_at(
+        &self,
+        todos: Vec<Todo>,
+        current_context: Option<String>,
+        today: NaiveDate,
+    ) -> Vec<Todo> {
+        todos
+            .into_iter()
+            .filter(|todo| match &self.status {
+                StatusFilter::Any => true,
+                StatusFilter::Relevant => todo.status != Status::Done,
+                StatusFilter::Status(status) => &todo.status == status,
+            })
+            .filter(|todo| match &self.context {
+                ContextFilter::Any => true,
+                ContextFilter::None => todo.context.is_none(),
+                ContextFilter::Current => match &current_context {
+                    Some(curr) => match &todo.context {
+                        Some(ctx) => ctx == curr,
+                        None => false,
+                    },
+                    None => true,
+                },
+                ContextFilter::Name(ctx) => match &todo.context {
+                    Some(name) => ctx == name,
+                    None => false,
+                },
+            })
+            .filter(|todo| match &self.tags {
+                Some(tags) => todo.tags.has_any(tags),
+                None => true,
+            })
+            .filter(|todo| match &self.due {
+                DueFilter::Any => true,
+                DueFilter::Overdue => todo.is_overdue_at(today),
+                DueFilter::Today => todo.is_due_within(today, 0),
+                DueFilter::Within(days) => todo.is_due_within(today, *days),
+            })
+            .filter(|todo| self.deferred || !todo.is_deferred_at(today))
+            .filter(|todo| match &self.expr {
+                Some(expr) => expr.matches(todo),
+                None => true,
+            })
+            .collect()
+    }
+}
+
+/// Filter targets for pruning.
+#[derive(Debug, Default)]
+pub struct PruneFilter {
+    /// Include all todos that are done.
+    done: bool,
+    /// Include all todos created before this time.
+    before: Option<DateTime<Local>>,
+    /// Include all todos created after this time.
+    after: Option<DateTime<Local>>,
+    /// Include all todos matching this expression.
+    expr: Option<Expr>,
+}
+
+impl PruneFilter {
+    pub fn with_done(mut self, done: bool) -> Self {
+        self.done = done;
+        self
+    }
+
+    pub fn with_before(mut self, before: DateTime<Local>) -> Self {
+        self.before = Some(before);
+        self
+    }
+
+    pub fn with_after(mut self, after: DateTime<Local>) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    pub fn with_expr(mut self, expr: Expr) -> Self {
+        self.expr = match self.expr.take() {
+            Some(prev) => Some(Expr::And(Box::new(prev), Box::new(expr))),
+            None => Some(expr),
+        };
+        self
+    }
+
+    pub fn apply(&self, todos: Vec<Todo>) -> Vec<Todo> {
+        todos
+            .into_iter()
+            .filter(|t| {
+                (self.done && t.is_done())
+                    || (self.before.is_some() && self.before.unwrap().gt(&t.created))
+                    || (self.after.is_some() && self.after.unwrap().lt(&t.created))
+                    || self.expr.as_ref().is_some_and(|expr| expr.matches(t))
+            })
+            .collect()
+    }
+}
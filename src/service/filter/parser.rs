@@ -0,0 +1,391 @@
+use super::expr::{Expr, Field, Op, Value};
+use crate::err;
+use crate::error::{Error, Result};
+use crate::model::{Prio, Status, ID};
+use crate::util;
+use chrono::{Local, NaiveDate};
+
+/// A token in a filter expression.
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Op(Op),
+    Word(String),
+    Quoted(String),
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Open => "'('".to_string(),
+            Token::Close => "')'".to_string(),
+            Token::Op(_) => "an operator".to_string(),
+            Token::Word(w) => format!("'{}'", w),
+            Token::Quoted(w) => format!("\"{}\"", w),
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+}
+
+fn error<T>(column: usize, msg: String) -> Result<T> {
+    err!("invalid filter at column {}: {}", column, msg)
+}
+
+// Splits the input into tokens, where columns start at 1.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>> {
+    let chars: Vec<char> = input.chars().collect();
+    let is_op = |c: char| ":=!<>".contains(c);
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let column = i + 1;
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '(' {
+            tokens.push((column, Token::Open));
+            i += 1;
+        } else if c == ')' {
+            tokens.push((column, Token::Close));
+            i += 1;
+        } else if is_op(c) {
+            let next = chars.get(i + 1).copied();
+            let (op, len) = match (c, next) {
+                ('!', Some('=')) => (Op::Ne, 2),
+                ('<', Some('=')) => (Op::Le, 2),
+                ('>', Some('=')) => (Op::Ge, 2),
+                (':', _) | ('=', _) => (Op::Eq, 1),
+                ('<', _) => (Op::Lt, 1),
+                ('>', _) => (Op::Gt, 1),
+                _ => return error(column, format!("unexpected '{}'", c)),
+            };
+            tokens.push((column, Token::Op(op)));
+            i += len;
+        } else if c == '"' {
+            let end = match chars[i + 1..].iter().position(|c| *c == '"') {
+                Some(n) => i + 1 + n,
+                None => return error(column, "unterminated quote".to_string()),
+            };
+            let text: String = chars[i + 1..end].iter().collect();
+            tokens.push((column, Token::Quoted(text)));
+            i = end + 1;
+        } else {
+            let start = i;
+            while i < chars.len()
+                && !chars[i].is_whitespace()
+                && !"()\"".contains(chars[i])
+                && !is_op(chars[i])
+            {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            tokens.push((column, Token::Word(word)));
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent parser for filter expressions:
+///
+/// ```text
+/// or        = and ("or" and)*
+/// and       = not ("and"? not)*
+/// not       = "not" not | primary
+/// primary   = "(" or ")" | field op value
+/// ```
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    // Column just after the input, used for errors at the end.
+    end: usize,
+    today: NaiveDate,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn accept(&mut self, keyword: &str) -> bool {
+        match self.peek() {
+            Some((_, token)) if token.is_keyword(keyword) => {
+                self.pos += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn parse(&mut self) -> Result<Expr> {
+        let expr = self.parse_or()?;
+        match self.peek() {
+            None => Ok(expr),
+            Some((column, Token::Close)) => error(*column, "unmatched ')'".to_string()),
+            Some((column, token)) => error(
+                *column,
+                format!("expected 'and' or 'or', found {}", token.describe()),
+            ),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_and()?;
+        while self.accept("or") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut expr = self.parse_not()?;
+        while self.accept("and") || self.at_operand() {
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_not()?));
+        }
+        Ok(expr)
+    }
+
+    // Returns true if the next token starts another operand, as conditions
+    // next to each other are combined using 'and'.
+    fn at_operand(&self) -> bool {
+        match self.peek() {
+            Some((_, Token::Open)) => true,
+            Some((_, token @ Token::Word(_))) => !token.is_keyword("or"),
+            _ => false,
+        }
+    }
+
+    fn parse_not(&mut self) -> Result<Expr> {
+        if self.accept("not") {
+            Ok(Expr::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_primary()
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        match self.next() {
+            Some((column, Token::Open)) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some((_, Token::Close)) => Ok(expr),
+                    Some((column, token)) => {
+                        error(column, format!("expected ')', found {}", token.describe()))
+                    }
+                    None => error(column, "unclosed '('".to_string()),
+                }
+            }
+            Some((column, Token::Word(name))) => {
+                let field = match Field::from_name(&name) {
+                    Some(field) => field,
+                    None => return error(column, format!("unknown field '{}'", name)),
+                };
+                self.parse_condition(field)
+            }
+            Some((column, token)) => error(
+                column,
+                format!("expected a field, found {}", token.describe()),
+            ),
+            None => error(self.end, "expected a field".to_string()),
+        }
+    }
+
+    fn parse_condition(&mut self, field: Field) -> Result<Expr> {
+        let (column, op) = match self.next() {
+            Some((column, Token::Op(op))) => (column, op),
+            Some((column, token)) => {
+                return error(
+                    column,
+                    format!(
+                        "expected an operator after '{}', found {}",
+                        field,
+                        token.describe()
+                    ),
+                )
+            }
+            None => return error(self.end, format!("expected an operator after '{}'", field)),
+        };
+        if !field.is_ordered() && !matches!(op, Op::Eq | Op::Ne) {
+            return error(
+                column,
+                format!("'{}' can only be compared using ':' or '!='", field),
+            );
+        }
+
+        let (column, value) = match self.next() {
+            Some((column, Token::Word(value))) | Some((column, Token::Quoted(value))) => {
+                (column, value)
+            }
+            Some((column, token)) => {
+                return error(
+                    column,
+                    format!("expected a value, found {}", token.describe()),
+                )
+            }
+            None => return error(self.end, format!("expected a value for '{}'", field)),
+        };
+
+        match self.parse_value(field, &value) {
+            Ok(value) => Ok(Expr::Cond(field, op, value)),
+            Err(_) => error(column, format!("invalid value for '{}': {}", field, value)),
+        }
+    }
+
+    fn parse_value(&self, field: Field, value: &str) -> Result<Value> {
+        let none = value.eq_ignore_ascii_case("none");
+        match field {
+            Field::Id => Ok(Value::Id(ID::try_from(value)?)),
+            Field::Status => Ok(Value::Status(Status::try_from(value.to_lowercase())?)),
+            Field::Prio => Ok(Value::Prio(Prio::try_from(value.to_lowercase())?)),
+            Field::Tag | Field::Context if none => Ok(Value::None),
+            Field::Tag | Field::Context | Field::Subject => Ok(Value::Text(value.to_string())),
+            Field::Due | Field::After if none => Ok(Value::None),
+            Field::Created | Field::Due | Field::After => {
+                Ok(Value::Date(util::parse_date_from(value, self.today)?))
+            }
+        }
+    }
+}
+
+/// Parses a filter expression with dates relative to `today`.
+pub fn parse_at(input: &str, today: NaiveDate) -> Result<Expr> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        end: input.chars().count() + 1,
+        today,
+    };
+    parser.parse()
+}
+
+impl TryFrom<&str> for Expr {
+    type Error = Error;
+
+    fn try_from(value: &str) -> std::result::Result<Self, Self::Error> {
+        parse_at(value, Local::now().date_naive())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, 2).unwrap()
+    }
+
+    fn cond(field: Field, op: Op, value: Value) -> Box<Expr> {
+        Box::new(Expr::Cond(field, op, value))
+    }
+
+    fn error_message(input: &str) -> String {
+        match parse_at(input, today()) {
+            Err(Error::General(msg)) => msg,
+            other => panic!("expected error for {}, got {:?}", input, other),
+        }
+    }
+
+    #[test]
+    fn parse_precedence() {
+        let expr = parse_at(
+            "prio>=high and (tag:backend or tag:api) and not status:blocked",
+            today(),
+        )
+        .unwrap();
+        let expected = Expr::And(
+            Box::new(Expr::And(
+                cond(Field::Prio, Op::Ge, Value::Prio(Prio::High)),
+                Box::new(Expr::Or(
+                    cond(Field::Tag, Op::Eq, Value::Text("backend".to_string())),
+                    cond(Field::Tag, Op::Eq, Value::Text("api".to_string())),
+                )),
+            )),
+            Box::new(Expr::Not(cond(
+                Field::Status,
+                Op::Eq,
+                Value::Status(Status::Blocked),
+            ))),
+        );
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn parse_or_binds_looser_than_and() {
+        let expr = parse_at("id=1 or id=2 and id=3", today()).unwrap();
+        assert!(matches!(expr, Expr::Or(_, _)));
+    }
+
+    #[test]
+    fn parse_implicit_and() {
+        let implicit = parse_at("prio>=high status:new or tag:a", today()).unwrap();
+        let explicit = parse_at("prio>=high and status:new or tag:a", today()).unwrap();
+        assert_eq!(implicit, explicit);
+    }
+
+    #[test]
+    fn parse_values() {
+        let cases = [
+            (
+                "created<2026-01-01",
+                Value::Date(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()),
+            ),
+            (
+                "due<=3d",
+                Value::Date(NaiveDate::from_ymd_opt(2026, 3, 5).unwrap()),
+            ),
+            ("due:none", Value::None),
+            ("context:none", Value::None),
+            (
+                "subject:\"release notes\"",
+                Value::Text("release notes".to_string()),
+            ),
+            ("id>10", Value::Id(ID::new(10))),
+            ("STATUS:Done", Value::Status(Status::Done)),
+        ];
+        for (input, value) in cases {
+            match parse_at(input, today()) {
+                Ok(Expr::Cond(_, _, v)) => assert_eq!(v, value, "{}", input),
+                other => panic!("unexpected result for {}: {:?}", input, other),
+            }
+        }
+    }
+
+    #[test]
+    fn parse_error_columns() {
+        let cases = [
+            ("prio>=hihg", "column 7: invalid value for 'prio': hihg"),
+            ("tag:a and foo:b", "column 11: unknown field 'foo'"),
+            ("(tag:a or tag:b", "column 1: unclosed '('"),
+            ("tag:a)", "column 6: unmatched ')'"),
+            ("tag:a and", "column 10: expected a field"),
+            (
+                "status<done",
+                "column 7: 'status' can only be compared using ':' or '!='",
+            ),
+            (
+                "tag a",
+                "column 5: expected an operator after 'tag', found 'a'",
+            ),
+            (
+                "tag:a \"b\"",
+                "column 7: expected 'and' or 'or', found \"b\"",
+            ),
+            ("subject:\"open", "column 9: unterminated quote"),
+        ];
+        for (input, expected) in cases {
+            let msg = error_message(input);
+            assert!(msg.ends_with(expected), "{}: {}", input, msg);
+        }
+    }
+}
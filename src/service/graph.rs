@@ -0,0 +1,403 @@
+use crate::model::{Link, Todo, ID};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The impact a todo has on other todos through blocking links.
+pub struct Impact {
+    pub todo: Todo,
+    /// Number of todos blocked by this todo, directly or transitively.
+    pub blocks: usize,
+    /// Number of todos in the longest chain of blocking links
+    /// that this todo is part of.
+    pub chain: usize,
+    /// True if this todo is on the critical path, i.e. the
+    /// longest chain of blocking links of all.
+    pub critical: bool,
+}
+
+/// Graph of blocking links between todos, where an edge from
+/// `a` to `b` means that `a` blocks `b`.
+pub struct Graph {
+    nodes: Vec<ID>,
+    edges: HashMap<ID, Vec<ID>>,
+}
+
+impl Graph {
+    /// Builds the graph from the `Blocks` and `BlockedBy` links of `todos`.
+    /// Links to todos not in `todos` are left out.
+    pub fn new(todos: &[Todo]) -> Self {
+        let mut nodes: Vec<ID> = todos.iter().map(|todo| todo.id).collect();
+        nodes.sort();
+
+        let mut edges: HashMap<ID, Vec<ID>> = HashMap::new();
+        for todo in todos {
+            for link in todo.links.values() {
+                let (blocker, blocked) = match link {
+                    Link::Blocks(blocked) => (todo.id, *blocked),
+                    Link::BlockedBy(blocker) => (*blocker, todo.id),
+                    _ => continue,
+                };
+                if nodes.binary_search(&blocker).is_err() || nodes.binary_search(&blocked).is_err()
+                {
+                    continue;
+                }
+
+                let blocks = edges.entry(blocker).or_default();
+                if !blocks.contains(&blocked) {
+                    blocks.push(blocked);
+                }
+            }
+        }
+
+        for blocks in edges.values_mut() {
+            blocks.sort();
+        }
+
+        Self { nodes, edges }
+    }
+
+    // Returns the graph with all edges reversed.
+    fn reversed(&self) -> Self {
+        let mut edges: HashMap<ID, Vec<ID>> = HashMap::new();
+        for (from, blocks) in &self.edges {
+            for to in blocks {
+                edges.entry(*to).or_default().push(*from);
+            }
+        }
+
+        for blocks in edges.values_mut() {
+            blocks.sort();
+        }
+
+        Self {
+            nodes: self.nodes.clone(),
+            edges,
+        }
+    }
+
+    /// Returns the todos directly blocked by `id`.
+    pub fn blocks(&self, id: &ID) -> &[ID] {
+        match self.edges.get(id) {
+            Some(blocks) => blocks,
+            None => &[],
+        }
+    }
+
+    /// Returns the shortest path of blocking links from `from` to `to`,
+    /// including both ends, if any.
+    pub fn path(&self, from: ID, to: ID) -> Option<Vec<ID>> {
+        let mut previous: HashMap<ID, ID> = HashMap::new();
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(id) = queue.pop_front() {
+            if id == to {
+                let mut path = vec![to];
+                let mut current = to;
+                while let Some(prev) = previous.get(&current) {
+                    path.push(*prev);
+                    current = *prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            for next in self.blocks(&id) {
+                if *next != from && !previous.contains_key(next) {
+                    previous.insert(*next, id);
+                    queue.push_back(*next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Returns all todos blocked by `id`, directly or transitively.
+    pub fn blocked_by(&self, id: ID) -> HashSet<ID> {
+        let mut blocked: HashSet<ID> = HashSet::new();
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            for next in self.blocks(&current) {
+                if *next != id && blocked.insert(*next) {
+                    stack.push(*next);
+                }
+            }
+        }
+        blocked
+    }
+
+    // Returns the number of todos in the longest chain of blocking links
+    // starting at each todo. Links that would form a cycle are ignored.
+    fn longest_chains(&self) -> HashMap<ID, usize> {
+        let mut lengths: HashMap<ID, usize> = HashMap::new();
+        for id in &self.nodes {
+            self.longest_chain(*id, &mut lengths, &mut HashSet::new());
+        }
+        lengths
+    }
+
+    fn longest_chain(
+        &self,
+        id: ID,
+        lengths: &mut HashMap<ID, usize>,
+        visiting: &mut HashSet<ID>,
+    ) -> usize {
+        if let Some(length) = lengths.get(&id) {
+            return *length;
+        }
+
+        visiting.insert(id);
+        let mut longest = 0;
+        for next in self.blocks(&id) {
+            if !visiting.contains(next) {
+                longest = longest.max(self.longest_chain(*next, lengths, visiting));
+            }
+        }
+        visiting.remove(&id);
+
+        lengths.insert(id, longest + 1);
+        longest + 1
+    }
+
+    /// Computes the impact of each of `todos`, which must be the todos the
+    /// graph was built from. See `Impact` for details.
+    pub fn impact(&self, todos: Vec<Todo>) -> Vec<Impact> {
+        let down = self.longest_chains();
+        let up = self.reversed().longest_chains();
+        let critical = self.critical_path(&down);
+
+        todos
+            .into_iter()
+            .filter(|todo| down.contains_key(&todo.id))
+            .map(|todo| {
+                let id = todo.id;
+                Impact {
+                    blocks: self.blocked_by(id).len(),
+                    chain: down[&id] + up[&id] - 1,
+                    critical: critical.contains(&id),
+                    todo,
+                }
+            })
+            .collect()
+    }
+
+    // Returns the longest chain of blocking links, if any.
+    fn critical_path(&self, down: &HashMap<ID, usize>) -> Vec<ID> {
+        let start = self
+            .nodes
+            .iter()
+            .filter(|id| down[*id] > 1)
+            .max_by(|a, b| down[*a].cmp(&down[*b]).then(b.cmp(a)));
+
+        let mut path: Vec<ID> = Vec::new();
+        let mut current = start.copied();
+        while let Some(id) = current {
+            path.push(id);
+            current = self
+                .blocks(&id)
+                .iter()
+                .find(|next| down[*next] + 1 == down[&id] && !path.contains(next))
+                .copied();
+        }
+        path
+    }
+
+    /// Finds cycles of blocking links. Each cycle starts and ends with the
+    /// same ID and is rotated to start with the lowest ID in the cycle.
+    /// Cycles that share edges may be reported only once.
+    pub fn cycles(&self) -> Vec<Vec<ID>> {
+        let mut cycles: Vec<Vec<ID>> = Vec::new();
+        let mut visited: HashSet<ID> = HashSet::new();
+
+        for start in self.nodes.iter().copied() {
+            if visited.contains(&start) {
+                continue;
+            }
+
+            // Depth-first search keeping the current path on a stack.
+            let mut path: Vec<ID> = vec![start];
+            let mut stack: Vec<usize> = vec![0];
+            visited.insert(start);
+
+            while let Some(index) = stack.last_mut() {
+                let current = *path.last().unwrap();
+                let next = self.blocks(&current).get(*index).copied();
+                *index += 1;
+
+                match next {
+                    Some(next) => {
+                        if let Some(pos) = path.iter().position(|id| *id == next) {
+                            let cycle = normalize(&path[pos..]);
+                            if !cycles.contains(&cycle) {
+                                cycles.push(cycle);
+                            }
+                        } else if visited.insert(next) {
+                            path.push(next);
+                            stack.push(0);
+                        }
+                    }
+                    None => {
+                        path.pop();
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        cycles
+    }
+}
+
+// Rotates the cycle to start with the lowest ID and closes it
+// by repeating the first ID at the end.
+fn normalize(cycle: &[ID]) -> Vec<ID> {
+    let min = cycle
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, id)| **id)
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+
+    let mut normalized: Vec<ID> = cycle[min..].iter().chain(&cycle[..min]).copied().collect();
+    if let Some(first) = normalized.first() {
+        normalized.push(*first);
+    }
+    normalized
+}
+
+/// Formats a path of IDs as e.g. `1 -> 2 -> 3`.
+pub fn format_path(path: &[ID]) -> String {
+    path.iter()
+        .map(|id| id.to_string())
+        .collect::<Vec<String>>()
+        .join(" -> ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Prio, Status, CSV};
+    use chrono::Local;
+
+    fn todo(id: u16, links: Vec<Link>) -> Todo {
+        Todo::new(
+            ID::new(id),
+            Local::now(),
+            Status::New,
+            Prio::Normal,
+            "".to_string(),
+            "".to_string(),
+            CSV::default(),
+            None,
+            CSV::new(links),
+        )
+    }
+
+    fn ids(ids: &[u16]) -> Vec<ID> {
+        ids.iter().map(|id| ID::new(*id)).collect()
+    }
+
+    #[test]
+    fn path_found() {
+        let graph = Graph::new(&[
+            todo(1, vec![Link::Blocks(ID::new(2))]),
+            todo(2, vec![Link::Blocks(ID::new(3))]),
+            todo(3, vec![]),
+        ]);
+        assert_eq!(graph.path(ID::new(1), ID::new(3)), Some(ids(&[1, 2, 3])));
+        assert_eq!(graph.path(ID::new(3), ID::new(1)), None);
+    }
+
+    #[test]
+    fn path_from_blocked_by() {
+        let graph = Graph::new(&[todo(1, vec![]), todo(2, vec![Link::BlockedBy(ID::new(1))])]);
+        assert_eq!(graph.path(ID::new(1), ID::new(2)), Some(ids(&[1, 2])));
+    }
+
+    #[test]
+    fn cycles_found() {
+        let graph = Graph::new(&[
+            todo(1, vec![Link::Blocks(ID::new(2))]),
+            todo(2, vec![Link::Blocks(ID::new(3))]),
+            todo(3, vec![Link::Blocks(ID::new(1))]),
+            todo(4, vec![Link::Blocks(ID::new(5))]),
+            todo(5, vec![Link::Blocks(ID::new(4))]),
+            todo(6, vec![Link::Blocks(ID::new(1))]),
+        ]);
+        assert_eq!(graph.cycles(), vec![ids(&[1, 2, 3, 1]), ids(&[4, 5, 4])]);
+    }
+
+    #[test]
+    fn no_cycles() {
+        let graph = Graph::new(&[
+            todo(1, vec![Link::Blocks(ID::new(2)), Link::Blocks(ID::new(3))]),
+            todo(2, vec![Link::Blocks(ID::new(3))]),
+            todo(4, vec![Link::RelatesTo(ID::new(1))]),
+        ]);
+        assert!(graph.cycles().is_empty());
+    }
+
+    #[test]
+    fn path_ignores_unknown_todos() {
+        let graph = Graph::new(&[todo(1, vec![Link::Blocks(ID::new(2))])]);
+        assert_eq!(graph.path(ID::new(1), ID::new(2)), None);
+    }
+
+    #[test]
+    fn impact() {
+        // 1 -> 2 -> 3 -> 4 and 5 -> 3, 6 -> 7
+        let graph_todos = vec![
+            todo(1, vec![Link::Blocks(ID::new(2))]),
+            todo(2, vec![Link::Blocks(ID::new(3))]),
+            todo(3, vec![Link::Blocks(ID::new(4))]),
+            todo(4, vec![]),
+            todo(5, vec![Link::Blocks(ID::new(3))]),
+            todo(6, vec![Link::Blocks(ID::new(7))]),
+            todo(7, vec![]),
+            todo(8, vec![]),
+        ];
+        let graph = Graph::new(&graph_todos);
+        let impact: Vec<(u16, usize, usize, bool)> = graph
+            .impact(graph_todos)
+            .iter()
+            .map(|i| {
+                (
+                    i.todo.id.to_string().parse().unwrap(),
+                    i.blocks,
+                    i.chain,
+                    i.critical,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            impact,
+            vec![
+                (1, 3, 4, true),
+                (2, 2, 4, true),
+                (3, 1, 4, true),
+                (4, 0, 4, true),
+                (5, 2, 3, false),
+                (6, 1, 2, false),
+                (7, 0, 2, false),
+                (8, 0, 1, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn impact_with_cycle() {
+        let graph_todos = vec![
+            todo(1, vec![Link::Blocks(ID::new(2))]),
+            todo(2, vec![Link::Blocks(ID::new(1))]),
+        ];
+        let graph = Graph::new(&graph_todos);
+        let impact = graph.impact(graph_todos);
+        assert_eq!(impact.len(), 2);
+        assert_eq!(impact[0].blocks, 1);
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(format_path(&ids(&[1, 2, 1])), "1 -> 2 -> 1");
+    }
+}
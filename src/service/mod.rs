@@ -1,22 +1,63 @@
-use crate::err;
-use crate::error::Result;
-use crate::model::{Link, Prio, Status, Todo, CSV, ID};
+use crate::error::{Error, Result};
+use crate::model::event::{Event, Kind, State};
+use crate::model::{Code, Link, LinkTypes, Prio, Status, Todo, View, CSV, ID};
 use crate::repository::Repository;
-use chrono::Local;
+use crate::{err, util};
+use chrono::{DateTime, Local};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{self, AtomicI64};
 
 pub mod changeset;
 pub mod filter;
+pub mod graph;
+pub mod ready;
+pub mod sort;
 pub use filter::*;
+use graph::{Graph, Impact};
+use ready::ReadyQueue;
 
 pub use self::changeset::Changeset;
+pub use self::sort::{Sort, SortField, SortKey};
 
 pub struct Service {
     repo: Repository,
+    /// Events recorded are added to this batch,
+    /// which is the unit for undo and redo.
+    batch: AtomicI64,
+    /// User-defined link types.
+    link_types: LinkTypes,
+    /// Order of listed todos unless another one is given.
+    sort: Sort,
 }
 
 impl Service {
     pub fn new(todos: Repository) -> Self {
-        Self { repo: todos }
+        Self {
+            repo: todos,
+            batch: AtomicI64::new(Local::now().timestamp_micros()),
+            link_types: LinkTypes::default(),
+            sort: Sort::default(),
+        }
+    }
+
+    pub fn with_link_types(mut self, link_types: LinkTypes) -> Self {
+        self.link_types = link_types;
+        self
+    }
+
+    pub fn with_sort(mut self, sort: Sort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Starts a new batch of changes, i.e. changes made after this
+    /// are undone separately from those made before.
+    pub fn begin_batch(&self) {
+        let now = Local::now().timestamp_micros();
+        let prev = self.batch.load(atomic::Ordering::SeqCst);
+        self.batch
+            .store(now.max(prev + 1), atomic::Ordering::SeqCst);
     }
 }
 
@@ -27,23 +68,61 @@ impl Service {
         self.repo.get_todo(id).await
     }
 
-    /// Lists available todos.
-    pub async fn list_todos(&self, filter: Option<Filter>) -> Result<Vec<Todo>> {
-        let todos = self.repo.get_todos().await?;
+    /// Lists available todos, sorted using `sort` or else the default sort.
+    pub async fn list_todos(
+        &self,
+        filter: Option<Filter>,
+        sort: Option<&Sort>,
+    ) -> Result<Vec<Todo>> {
+        let today = Local::now().date_naive();
         let mut todos = match filter {
             Some(filter) => {
                 let context = self.get_context().await?;
-                filter.apply(todos, context)
+                let query = filter.query(context, today);
+                filter.apply_expr(self.repo.find_todos(&query).await?)
             }
-            None => todos,
+            None => self.repo.get_todos().await?,
         };
 
         log::info!("Listed {} todos", todos.len());
-        todos.sort();
+        sort.unwrap_or(&self.sort).apply(&mut todos, today);
 
         Ok(todos)
     }
 
+    /// Gets the todos matching the full-text query `text` and the filter,
+    /// with the best matches first.
+    pub async fn search_todos(&self, text: &str, filter: Filter) -> Result<Vec<Todo>> {
+        if text.trim().is_empty() {
+            return err!("empty search query");
+        }
+
+        let context = self.get_context().await?;
+        let mut query = filter.query(context, Local::now().date_naive());
+        query.text = Some(text.to_string());
+        let todos = filter.apply_expr(self.repo.find_todos(&query).await?);
+
+        log::info!("Found {} todos matching '{}'", todos.len(), text);
+        Ok(todos)
+    }
+
+    /// Gets the todos that can be started right now, i.e. that aren't done,
+    /// blocked or deferred and that are in the current context, limited
+    /// to `limit` todos. Also returns why the other open todos aren't ready.
+    pub async fn next_todos(&self, limit: Option<usize>) -> Result<ReadyQueue> {
+        let todos = self.repo.get_todos().await?;
+        let context = self.get_context().await?;
+        let today = Local::now().date_naive();
+
+        let mut queue = ReadyQueue::new(todos, context.as_deref(), today);
+        if let Some(limit) = limit {
+            queue.ready.truncate(limit);
+        }
+
+        log::info!("{} todos ready", queue.ready.len());
+        Ok(queue)
+    }
+
     /// Adds a new todo from the parameter and returns the created todo including an ID.
     pub async fn add_todo(
         &self,
@@ -52,12 +131,34 @@ impl Service {
         subject: String,
         description: String,
         tags: CSV<String>,
+    ) -> Result<Todo> {
+        self.add_todo_with(
+            status,
+            prio,
+            subject,
+            description,
+            tags,
+            Changeset::default(),
+        )
+        .await
+    }
+
+    /// Same as `add_todo` but applies `extras` to the todo before it is added,
+    /// used to set optional properties such as due date.
+    pub async fn add_todo_with(
+        &self,
+        status: Status,
+        prio: Prio,
+        subject: String,
+        description: String,
+        tags: CSV<String>,
+        extras: Changeset,
     ) -> Result<Todo> {
         let context = self.get_context().await?;
         log::info!("Adding new todo in context: {:?}", context);
 
         let now = Local::now();
-        let tmp = Todo::new(
+        let mut tmp = Todo::new(
             ID::new(0),
             now,
             status,
@@ -68,28 +169,175 @@ impl Service {
             context,
             CSV::empty(),
         );
+        extras.apply(&mut tmp);
+        if tmp.is_done() {
+            tmp.completed = Some(now);
+        }
+        if let Some(parent) = &tmp.parent {
+            self.get_todo(parent).await?;
+        }
 
         let todo = self.repo.add_todo(tmp).await?;
+        self.record(Kind::AddTodo(todo.clone())).await?;
 
         log::info!("Added todo: {:?}", todo);
         Ok(todo)
     }
 
-    pub async fn remove_todo(&self, id: &ID) -> Result<()> {
-        let todo = self.get_todo(id).await?;
+    /// Adds todos read from elsewhere, e.g. by an import, keeping their
+    /// times of creation and completion as well as their context, which is
+    /// added if missing. The todos get new IDs, except those mapped by
+    /// `existing` to todos that are updated instead. Links and parents
+    /// between the todos are mapped accordingly, while ones pointing to
+    /// other todos are dropped unless already set on an updated todo.
+    /// Nothing is imported if any of the todos is invalid.
+    pub async fn import_todos(
+        &self,
+        todos: Vec<Todo>,
+        existing: &HashMap<ID, ID>,
+    ) -> Result<Vec<Todo>> {
+        let mut seen = HashSet::new();
+        for todo in &todos {
+            if !seen.insert(todo.id) {
+                return err!("duplicate todo ID in import: {}", todo.id);
+            }
+            for link in todo.links.values() {
+                if let Link::Custom { name, .. } = link {
+                    if !self.link_types.contains(name) {
+                        return err!("unknown link type: {}", name);
+                    }
+                }
+            }
+        }
 
-        // Remove links to the todo being removed
-        for link in todo.links.values() {
-            let (blocker, blocked) = match link {
-                Link::Blocks(blocked) => (id, blocked),
-                Link::BlockedBy(blocker) => (blocker, id),
-                _ => continue,
+        // Everything is checked before the first change, so that an import
+        // that fails changes nothing.
+        let mut contexts = self.list_contexts().await?;
+        let mut planned = Vec::with_capacity(todos.len());
+        let mut merged = HashSet::new();
+        for todo in &todos {
+            let mut tmp = todo.clone();
+            if let Some(context) = &todo.context {
+                tmp.context = Some(self.validate_context_name(context)?);
+            }
+            let current = match existing.get(&todo.id) {
+                Some(id) if !merged.insert(*id) => {
+                    return err!("more than one imported todo matches todo {}", id)
+                }
+                Some(id) => Some(self.get_todo(id).await?),
+                None => None,
             };
-            self.unlink_block(*blocker, *blocked).await?;
+            planned.push((tmp, current));
+        }
+        let parents: HashMap<ID, ID> = todos
+            .iter()
+            .filter_map(|todo| todo.parent.map(|parent| (todo.id, parent)))
+            .collect();
+        for todo in &todos {
+            let mut id = todo.id;
+            for _ in 0..=todos.len() {
+                match parents.get(&id) {
+                    Some(parent) => id = *parent,
+                    None => break,
+                }
+            }
+            if parents.contains_key(&id) {
+                return err!("imported todo {} is its own subtask", todo.id);
+            }
         }
 
+        let mut ids = HashMap::new();
+        let mut imported = Vec::new();
+        for (todo, (mut tmp, current)) in todos.iter().zip(planned) {
+            if let Some(context) = &tmp.context {
+                if !contexts.contains(context) {
+                    self.repo.add_context(context).await?;
+                    self.record(Kind::AddContext(context.clone())).await?;
+                    contexts.push(context.clone());
+                }
+            }
+
+            let new = match current {
+                Some(current) => {
+                    tmp.id = current.id;
+                    tmp.links = current.links;
+                    tmp.parent = current.parent;
+                    self.save_todo(&tmp).await?;
+                    tmp
+                }
+                None => {
+                    tmp.links = CSV::empty();
+                    tmp.parent = None;
+                    let new = self.repo.add_todo(tmp).await?;
+                    self.record(Kind::AddTodo(new.clone())).await?;
+                    new
+                }
+            };
+            ids.insert(todo.id, new.id);
+            imported.push(new);
+        }
+
+        // Links and parents are set once all todos exist.
+        let targets: HashSet<ID> = ids.values().copied().collect();
+        for (todo, new) in todos.iter().zip(imported.iter_mut()) {
+            let mut links: Vec<Link> = new
+                .links
+                .values()
+                .iter()
+                .filter(|link| !targets.contains(&link.id()))
+                .cloned()
+                .collect();
+            for link in todo.links.values() {
+                match ids.get(&link.id()) {
+                    Some(id) if !links.contains(&link.with_id(*id)) => {
+                        links.push(link.with_id(*id))
+                    }
+                    Some(_) => {}
+                    None => log::warn!("Dropped link {} of imported todo {}", link, todo.id),
+                }
+            }
+            let links = CSV::new(links);
+            let parent = match todo.parent.and_then(|parent| ids.get(&parent)) {
+                Some(parent) => Some(*parent),
+                None => new.parent.filter(|parent| !targets.contains(parent)),
+            };
+            if links.encode() == new.links.encode() && parent == new.parent {
+                continue;
+            }
+            new.links = links;
+            new.parent = parent;
+            self.save_todo(new).await?;
+        }
+
+        log::info!("Imported {} todo(s)", imported.len());
+        Ok(imported)
+    }
+
+    /// Removes the todo with `id`. Subtasks are removed as well if `cascade`
+    /// is true, else they are kept without a parent.
+    pub async fn remove_todo(&self, id: &ID, cascade: bool) -> Result<()> {
+        let subtasks = self.get_subtasks(id, cascade).await?;
+        if cascade {
+            // Remove the deepest subtasks first
+            for subtask in subtasks.iter().rev() {
+                self.remove_single(&subtask.id).await?;
+            }
+        } else {
+            for mut subtask in subtasks {
+                subtask.parent = None;
+                self.save_todo(&subtask).await?;
+            }
+        }
+
+        self.remove_single(id).await
+    }
+
+    async fn remove_single(&self, id: &ID) -> Result<()> {
+        self.unlink_all(id).await?;
+
         let todo = self.repo.remove_todo(id).await?;
         log::info!("Removed todo with ID {}", todo.id);
+        self.record(Kind::RemoveTodo(todo)).await?;
         Ok(())
     }
 
@@ -99,7 +347,27 @@ impl Service {
             return Ok(todo);
         }
 
+        if let Some(Some(parent)) = &changeset.parent {
+            self.validate_parent(id, parent).await?;
+        }
+
         // Handle transitions
+        let completed = !todo.is_done() && matches!(changeset.status, Some(Status::Done));
+        if completed {
+            let open = self
+                .get_subtasks(id, false)
+                .await?
+                .iter()
+                .filter(|subtask| !subtask.is_done())
+                .count();
+            if open > 0 {
+                return err!(
+                    "todo with ID {} has {} open subtask(s), complete them first or use cascade",
+                    id,
+                    open
+                );
+            }
+        }
         let mut load = false;
         if let Some(status) = &changeset.status {
             match status {
@@ -127,12 +395,125 @@ impl Service {
         let mut todo = if load { self.get_todo(id).await? } else { todo };
 
         changeset.apply(&mut todo);
+        if completed {
+            todo.completed = Some(Local::now());
+        } else if !todo.is_done() {
+            todo.completed = None;
+        }
 
-        self.repo.replace_todo(&todo).await?;
+        self.save_todo(&todo).await?;
         log::info!("Updated todo with ID {}", id);
 
+        if completed && todo.recurrence.is_some() {
+            self.add_next_recurrence(&todo).await?;
+        }
+
         Ok(todo)
     }
+
+    /// Sets the status of the todo with `id` to done. If `cascade` is true
+    /// all open subtasks are completed as well, else it's an error if any
+    /// subtask is still open.
+    pub async fn complete_todo(&self, id: &ID, cascade: bool) -> Result<Todo> {
+        if cascade {
+            let subtasks = self.get_subtasks(id, true).await?;
+            for subtask in subtasks.iter().rev().filter(|t| !t.is_done()) {
+                let changeset = Changeset::default().with_status(Status::Done);
+                self.update_todo(&subtask.id, changeset).await?;
+            }
+        }
+
+        let changeset = Changeset::default().with_status(Status::Done);
+        self.update_todo(id, changeset).await
+    }
+
+    /// Returns the subtasks of the todo with `id`. If `recursive` is true
+    /// all descendants are returned, ordered so that parents come before
+    /// their subtasks.
+    pub async fn get_subtasks(&self, id: &ID, recursive: bool) -> Result<Vec<Todo>> {
+        let mut todos = self.repo.get_todos().await?;
+        todos.sort();
+
+        let mut subtasks: Vec<Todo> = Vec::new();
+        let mut parents = vec![*id];
+        while let Some(parent) = parents.pop() {
+            for todo in todos.iter().filter(|t| t.parent == Some(parent)) {
+                if todo.id == *id || subtasks.contains(todo) {
+                    continue;
+                }
+                if recursive {
+                    parents.push(todo.id);
+                }
+                subtasks.push(todo.clone());
+            }
+        }
+
+        Ok(subtasks)
+    }
+
+    // Validates that the todo with `id` can be made a subtask of `parent`,
+    // i.e. the parent exists and isn't `id` or one of its subtasks.
+    async fn validate_parent(&self, id: &ID, parent: &ID) -> Result<()> {
+        let mut visited: Vec<ID> = Vec::new();
+        let mut current = Some(*parent);
+        while let Some(ancestor) = current {
+            if ancestor == *id {
+                return err!("todo with ID {} cannot be a subtask of {}", id, parent);
+            }
+            if visited.contains(&ancestor) {
+                break;
+            }
+            visited.push(ancestor);
+            current = self.get_todo(&ancestor).await?.parent;
+        }
+        Ok(())
+    }
+
+    /// Replaces the stored todo and records the change.
+    async fn save_todo(&self, todo: &Todo) -> Result<()> {
+        let before = self.repo.get_todo(&todo.id).await?;
+        self.repo.replace_todo(todo).await?;
+        self.record(Kind::UpdateTodo {
+            before,
+            after: todo.clone(),
+        })
+        .await
+    }
+
+    /// Adds a fresh copy of a recurring todo that was completed,
+    /// due at the next date according to its recurrence rule.
+    async fn add_next_recurrence(&self, todo: &Todo) -> Result<Todo> {
+        let recurrence = match &todo.recurrence {
+            Some(r) => r.clone(),
+            None => return err!("todo with ID {} does not recur", todo.id),
+        };
+
+        let today = Local::now().date_naive();
+        let next = recurrence.next(todo.due.unwrap_or(today), today);
+
+        let extras = Changeset::default()
+            .with_context(todo.context.clone().unwrap_or_default())
+            .with_due(Some(next))
+            .with_recurrence(Some(recurrence));
+
+        let next = self
+            .add_todo_with(
+                Status::New,
+                todo.prio.clone(),
+                todo.subject.clone(),
+                todo.description.clone(),
+                todo.tags.clone(),
+                extras,
+            )
+            .await?;
+
+        log::info!(
+            "Added todo with ID {} as next recurrence of {}",
+            next.id,
+            todo.id
+        );
+        Ok(next)
+    }
 }
 
 // Context.
@@ -148,7 +529,7 @@ impl Service {
 
         self.repo.add_context(&context).await?;
         log::info!("Added new context: {context}");
-        Ok(())
+        self.record(Kind::AddContext(context)).await
     }
 
     pub async fn set_context(&self, context: &str) -> Result<()> {
@@ -171,13 +552,28 @@ impl Service {
 
         self.repo.set_context(&context).await?;
         log::info!("Changed context to: {context}");
-        Ok(())
+        self.record(Kind::SetContext {
+            before: current,
+            after: context,
+        })
+        .await
     }
 
     pub async fn unset_context(&self) -> Result<()> {
+        let current = self.repo.get_context().await?;
         self.repo.unset_context().await?;
         log::info!("Current context was unset");
-        Ok(())
+
+        match current {
+            Some(before) => {
+                self.record(Kind::SetContext {
+                    before,
+                    after: String::new(),
+                })
+                .await
+            }
+            None => Ok(()),
+        }
     }
 
     pub async fn get_context(&self) -> Result<Option<String>> {
@@ -192,7 +588,11 @@ impl Service {
 
     pub async fn remove_context(&self, context: &str, cascade: bool) -> Result<()> {
         let current = self.get_context().await?;
-        let todos = self.list_todos(Some(Filter::default())).await?;
+        let filter = Filter::default()
+            .status(StatusFilter::Any)
+            .context(ContextFilter::Name(context.to_string()))
+            .include_deferred(true);
+        let todos = self.list_todos(Some(filter), None).await?;
         if let Some(ctx) = current {
             if ctx == context {
                 log::info!("Removing current context");
@@ -206,12 +606,29 @@ impl Service {
             context
         );
 
-        if cascade {
+        // Todos removed by cascade are kept in the event.
+        let removed = if cascade {
             log::info!(
                 "Removing {} todos due to cascading remove of context {}",
                 todos.len(),
                 context
             );
+            // Links to the todos are removed first, so that undo can restore them.
+            let mut removed = Vec::new();
+            for todo in &todos {
+                self.unlink_all(&todo.id).await?;
+                // Subtasks in other contexts are kept, without a parent
+                for mut subtask in self.get_subtasks(&todo.id, false).await? {
+                    if subtask.context.as_deref() != Some(context) {
+                        subtask.parent = None;
+                        self.save_todo(&subtask).await?;
+                    }
+                }
+            }
+            for todo in todos {
+                removed.push(self.get_todo(&todo.id).await?);
+            }
+            removed
         } else {
             log::info!(
                 "Replacing {} todos that was linked to context being removed",
@@ -219,14 +636,16 @@ impl Service {
             );
             for mut todo in todos {
                 todo.context = None;
-                self.repo.replace_todo(&todo).await?;
+                self.save_todo(&todo).await?;
             }
+            Vec::new()
         };
 
         // Remove context.
         self.repo.remove_context(context).await?;
         log::info!("Removed context: {context}");
-        Ok(())
+        self.record(Kind::RemoveContext(context.to_string(), removed))
+            .await
     }
 
     fn validate_context_name(&self, context: &str) -> Result<String> {
@@ -243,6 +662,64 @@ impl Service {
     }
 }
 
+// Views.
+impl Service {
+    /// Saves a view, replacing any view with the same name.
+    pub async fn save_view(&self, view: &View) -> Result<()> {
+        self.validate_view_name(&view.name)?;
+        Expr::try_from(view.filter.as_str())?;
+        if let Some(sort) = &view.sort {
+            Sort::try_from(sort.as_str())?;
+        }
+        self.repo.save_view(view).await?;
+        log::info!("Saved view: {}", view.name);
+        Ok(())
+    }
+
+    pub async fn get_view(&self, name: &str) -> Result<View> {
+        self.repo.get_view(name).await
+    }
+
+    pub async fn list_views(&self) -> Result<Vec<View>> {
+        self.repo.get_views().await
+    }
+
+    pub async fn remove_view(&self, name: &str) -> Result<()> {
+        self.repo.remove_view(name).await?;
+        log::info!("Removed view: {name}");
+        Ok(())
+    }
+
+    /// Gets the view that replaces the default listing, if saved.
+    pub async fn default_view(&self) -> Result<Option<View>> {
+        match self.repo.get_view(View::DEFAULT).await {
+            Ok(view) => Ok(Some(view)),
+            Err(Error::NotFound(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn validate_view_name(&self, name: &str) -> Result<()> {
+        // Names of the view sub-commands can't be used to run a view.
+        let reserved = ["save", "ls", "list", "rm", "remove"];
+        if name.is_empty() || name.len() > 20 {
+            err!("invalid view name: length must be between 1 and 20")
+        } else if !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            err!(
+                "invalid view name: {}, use lowercase letters, digits, - and _",
+                name
+            )
+        } else if reserved.contains(&name) {
+            err!("invalid view name: {} is reserved", name)
+        } else {
+            Ok(())
+        }
+    }
+}
+
 // Links.
 impl Service {
     pub async fn link(&self, id: ID, link: Link) -> Result<Todo> {
@@ -250,17 +727,25 @@ impl Service {
             return err!("cannot link to self");
         }
 
-        match link {
+        if let Link::Custom { name, .. } = &link {
+            if !self.link_types.contains(name) {
+                return err!("unknown link type: {}", name);
+            }
+        }
+
+        match self.link_types.resolve(link) {
             Link::Blocks(blocked_id) => self.link_block(id, blocked_id).await,
             Link::BlockedBy(blocker_id) => self.link_block(blocker_id, id).await,
+            link if link.bi_directional(id).is_some() => self.link_bi(id, link).await,
             link => self.link_uni(id, link).await,
         }
     }
 
     pub async fn unlink(&self, id: ID, link: Link) -> Result<Todo> {
-        match link {
+        match self.link_types.resolve(link) {
             Link::Blocks(blocked) => self.unlink_block(id, blocked).await,
             Link::BlockedBy(blocker) => self.unlink_block(blocker, id).await,
+            link if link.bi_directional(id).is_some() => self.unlink_bi(id, link).await,
             link => self.unlink_uni(id, link).await,
         }
     }
@@ -275,7 +760,7 @@ impl Service {
         }
 
         blocker.links = blocker.links.remove(&blocks_link);
-        self.repo.replace_todo(&blocker).await?;
+        self.save_todo(&blocker).await?;
 
         let mut blocked = self.get_todo(&blocked).await?;
         blocked.links = blocked.links.remove(&blocked_by_link);
@@ -287,10 +772,9 @@ impl Service {
             .any(|link| link.is_blocked_by())
         {
             blocked.status = Status::New;
-        } else {
         }
 
-        self.repo.replace_todo(&blocked).await?;
+        self.save_todo(&blocked).await?;
         Ok(blocker)
     }
 
@@ -308,23 +792,97 @@ impl Service {
             return Ok(blocker);
         }
 
-        let circular = Link::BlockedBy(blocked);
-        if blocker.links.contains(&circular) {
-            return err!("circular link not allowed");
+        let todos = self.repo.get_todos().await?;
+        if let Some(path) = Graph::new(&todos).path(blocked, blocker.id) {
+            let cycle: Vec<ID> = std::iter::once(blocker.id).chain(path).collect();
+            return err!(
+                "circular link not allowed: {} (where a -> b means a blocks b)",
+                graph::format_path(&cycle)
+            );
         }
 
         blocker.links.push_not_exists(blocks_link);
-        self.repo.replace_todo(&blocker).await?;
+        self.save_todo(&blocker).await?;
 
         let mut blocked = self.get_todo(&blocked).await?;
         blocked.links.push_not_exists(blocked_by_link);
         blocked.status = Status::Blocked;
-        self.repo.replace_todo(&blocked).await?;
+        self.save_todo(&blocked).await?;
 
         log::info!("Added link: {} blocks {}", blocker.id, blocked.id);
         Ok(blocker)
     }
 
+    /// Computes the impact of the open todos in the current context through
+    /// blocking links, ordered by the number of todos they block.
+    pub async fn impact(&self) -> Result<Vec<Impact>> {
+        let filter = Filter::default().include_deferred(true);
+        let todos = self.list_todos(Some(filter), None).await?;
+
+        let mut impact = Graph::new(&todos).impact(todos);
+        impact.sort_by(|a, b| {
+            b.blocks
+                .cmp(&a.blocks)
+                .then(b.chain.cmp(&a.chain))
+                .then(a.todo.cmp(&b.todo))
+        });
+        Ok(impact)
+    }
+
+    /// Finds cycles among the blocking links, where every todo in
+    /// a cycle is blocked forever. See `Graph::cycles`.
+    pub async fn find_cycles(&self) -> Result<Vec<Vec<ID>>> {
+        let todos = self.repo.get_todos().await?;
+        Ok(Graph::new(&todos).cycles())
+    }
+
+    // Removes the links of a todo along with their inverse links.
+    async fn unlink_all(&self, id: &ID) -> Result<()> {
+        let todo = self.get_todo(id).await?;
+        for link in todo.links.values() {
+            match self.link_types.resolve(link.clone()) {
+                Link::Blocks(blocked) => self.unlink_block(*id, blocked).await?,
+                Link::BlockedBy(blocker) => self.unlink_block(blocker, *id).await?,
+                link if link.bi_directional(*id).is_some() => self.unlink_bi(*id, link).await?,
+                _ => continue,
+            };
+        }
+        Ok(())
+    }
+
+    /// Adds a bi-directional link to todo with `id`
+    /// and the inverse link to the other todo.
+    async fn link_bi(&self, id: ID, link: Link) -> Result<Todo> {
+        let mut other = self.get_todo(&link.id()).await?;
+        let mut todo = self.get_todo(&id).await?;
+        if todo.links.contains(&link) {
+            return Ok(todo);
+        }
+
+        if let Some(inverse) = link.bi_directional(id) {
+            other.links.push_not_exists(inverse);
+            self.save_todo(&other).await?;
+        }
+
+        log::info!("Added link: {} {}", id, link);
+        todo.links.push_not_exists(link);
+        self.save_todo(&todo).await?;
+        Ok(todo)
+    }
+
+    async fn unlink_bi(&self, id: ID, link: Link) -> Result<Todo> {
+        if let Some(inverse) = link.bi_directional(id) {
+            if let Ok(mut other) = self.get_todo(&link.id()).await {
+                if other.links.contains(&inverse) {
+                    other.links = other.links.remove(&inverse);
+                    self.save_todo(&other).await?;
+                }
+            }
+        }
+
+        self.unlink_uni(id, link).await
+    }
+
     /// Adds a unilateral link to todo with `id`.
     async fn link_uni(&self, id: ID, link: Link) -> Result<Todo> {
         let mut todo = self.get_todo(&id).await?;
@@ -333,31 +891,172 @@ impl Service {
         }
 
         todo.links.push_not_exists(link);
-        self.repo.replace_todo(&todo).await?;
+        self.save_todo(&todo).await?;
         Ok(todo)
     }
 
     async fn unlink_uni(&self, id: ID, link: Link) -> Result<Todo> {
         let mut todo = self.get_todo(&id).await?;
         todo.links = todo.links.remove(&link);
-        self.repo.replace_todo(&todo).await?;
+        self.save_todo(&todo).await?;
         Ok(todo)
     }
 }
 
+// Events.
+impl Service {
+    /// Lists recorded events in the order they occurred, optionally
+    /// only those involving a todo and/or since a point in time.
+    pub async fn list_events(
+        &self,
+        todo: Option<ID>,
+        since: Option<DateTime<Local>>,
+    ) -> Result<Vec<Event>> {
+        let events = self
+            .repo
+            .get_events(todo, since.map(|dt| dt.timestamp()))
+            .await?;
+        log::info!("Listed {} events", events.len());
+        Ok(events)
+    }
+
+    async fn record(&self, kind: Kind) -> Result<()> {
+        let author = util::try_get_env("USER").or_else(|| util::try_get_env("USERNAME"));
+        let batch = self.batch.load(atomic::Ordering::SeqCst);
+        self.repo.add_event(&kind, author, batch).await
+    }
+}
+
+// Undo and redo.
+impl Service {
+    /// Returns the events that would be reverted by `undo`.
+    pub async fn pending_undo(&self) -> Result<Vec<Event>> {
+        self.repo.get_last_applied_batch().await
+    }
+
+    /// Returns the events that would be replayed by `redo`.
+    pub async fn pending_redo(&self) -> Result<Vec<Event>> {
+        self.repo.get_last_undone_batch().await
+    }
+
+    /// Reverts the latest batch of changes and returns the reverted events.
+    pub async fn undo(&self) -> Result<Vec<Event>> {
+        let events = self.pending_undo().await?;
+        let batch = match events.first() {
+            Some(event) => event.batch,
+            None => return err!("nothing to undo"),
+        };
+
+        for event in events.iter().rev() {
+            self.revert(&event.kind).await?;
+        }
+
+        self.repo.set_batch_state(batch, State::Undone).await?;
+        log::info!("Reverted {} events in batch {}", events.len(), batch);
+        Ok(events)
+    }
+
+    /// Replays the latest batch of changes that was undone
+    /// and returns the replayed events.
+    pub async fn redo(&self) -> Result<Vec<Event>> {
+        let events = self.pending_redo().await?;
+        let batch = match events.first() {
+            Some(event) => event.batch,
+            None => return err!("nothing to redo"),
+        };
+
+        for event in events.iter() {
+            self.replay(&event.kind).await?;
+        }
+
+        self.repo.set_batch_state(batch, State::Applied).await?;
+        log::info!("Replayed {} events in batch {}", events.len(), batch);
+        Ok(events)
+    }
+
+    // Reverts the change described by `kind` without recording it.
+    async fn revert(&self, kind: &Kind) -> Result<()> {
+        match kind {
+            Kind::AddTodo(todo) => {
+                self.repo.remove_todo(&todo.id).await?;
+            }
+            Kind::UpdateTodo { before, after: _ } => self.repo.replace_todo(before).await?,
+            Kind::RemoveTodo(todo) => self.repo.replace_todo(todo).await?,
+            Kind::AddContext(context) => self.repo.remove_context(context).await?,
+            Kind::RemoveContext(context, todos) => {
+                self.repo.add_context(context).await?;
+                // Links may point between the restored todos, so they
+                // are added once all todos exist.
+                for todo in todos {
+                    let mut todo = todo.clone();
+                    todo.links = CSV::empty();
+                    self.repo.replace_todo(&todo).await?;
+                }
+                for todo in todos {
+                    self.repo.replace_todo(todo).await?;
+                }
+            }
+            Kind::SetContext { before, after: _ } => self.restore_context(before).await?,
+        }
+        Ok(())
+    }
+
+    // Applies the change described by `kind` again without recording it.
+    async fn replay(&self, kind: &Kind) -> Result<()> {
+        match kind {
+            Kind::AddTodo(todo) => self.repo.replace_todo(todo).await?,
+            Kind::UpdateTodo { before: _, after } => self.repo.replace_todo(after).await?,
+            Kind::RemoveTodo(todo) => {
+                self.repo.remove_todo(&todo.id).await?;
+            }
+            Kind::AddContext(context) => self.repo.add_context(context).await?,
+            Kind::RemoveContext(context, _) => self.repo.remove_context(context).await?,
+            Kind::SetContext { before: _, after } => self.restore_context(after).await?,
+        }
+        Ok(())
+    }
+
+    // Sets the current context, where an empty string means no context.
+    async fn restore_context(&self, context: &str) -> Result<()> {
+        if context.is_empty() {
+            self.repo.unset_context().await
+        } else {
+            self.repo.set_context(context).await
+        }
+    }
+}
+
 // Context.
 impl Service {
     pub async fn prune(&self, f: PruneFilter) -> Result<()> {
-        let todos = self.list_todos(None).await?;
-        let todos = f.apply(todos);
+        let todos = self.list_prunable(&f).await?;
         log::info!("{} todos left after filtering", todos.len());
 
         for t in todos {
-            self.remove_todo(&t.id).await?;
+            self.remove_todo(&t.id, false).await?;
         }
 
         Ok(())
     }
+
+    /// Lists the todos that would be removed by prune.
+    pub async fn list_prunable(&self, f: &PruneFilter) -> Result<Vec<Todo>> {
+        let todos = self.list_todos(None, None).await?;
+        Ok(f.apply(todos))
+    }
+}
+
+// Database.
+impl Service {
+    /// Writes a snapshot of the database to `path`.
+    pub async fn backup(&self, path: &Path) -> Result<()> {
+        self.repo.backup(path).await
+    }
+
+    /// Closes the database, after which the service can't be used.
+    pub async fn close(&self) {
+        self.repo.close().await
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,112 @@
+use crate::model::{Link, Status, Todo, ID};
+use chrono::NaiveDate;
+use core::fmt;
+
+/// The reason a todo that isn't done cannot be started right now.
+#[derive(Debug, PartialEq)]
+pub enum Exclusion {
+    /// Belongs to another context than the current.
+    Context(Option<String>),
+    /// Deferred until the given date.
+    Deferred(NaiveDate),
+    /// Blocked by the todos with these IDs, which aren't done.
+    BlockedBy(Vec<ID>),
+    /// Has status blocked without being blocked by any todo,
+    /// e.g. waiting on something else.
+    Blocked,
+    /// Has this many subtasks that should be done first.
+    Subtasks(usize),
+}
+
+impl fmt::Display for Exclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exclusion::Context(Some(context)) => write!(f, "in other context: {}", context),
+            Exclusion::Context(None) => write!(f, "not in current context"),
+            Exclusion::Deferred(date) => write!(f, "deferred until {}", date),
+            Exclusion::BlockedBy(ids) => {
+                let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+                write!(f, "blocked by {}", ids.join(", "))
+            }
+            Exclusion::Blocked => write!(f, "has status blocked"),
+            Exclusion::Subtasks(1) => write!(f, "has 1 open subtask"),
+            Exclusion::Subtasks(n) => write!(f, "has {} open subtasks", n),
+        }
+    }
+}
+
+/// ReadyQueue contains the todos that can be started right now,
+/// and the reasons the other todos that aren't done cannot.
+pub struct ReadyQueue {
+    /// Todos that are ready, in order of importance.
+    pub ready: Vec<Todo>,
+    /// Todos that aren't done but aren't ready either.
+    pub excluded: Vec<(Todo, Exclusion)>,
+}
+
+impl ReadyQueue {
+    /// Sorts `todos` into the ones that are ready and the ones that are not,
+    /// given the current context and date. Todos that are done are left out.
+    pub fn new(mut todos: Vec<Todo>, context: Option<&str>, today: NaiveDate) -> Self {
+        todos.sort_by(|a, b| a.cmp_at(b, today));
+
+        let mut ready = Vec::new();
+        let mut excluded = Vec::new();
+        for todo in todos.iter().filter(|t| !t.is_done()) {
+            match exclusion(todo, &todos, context, today) {
+                Some(reason) => excluded.push((todo.clone(), reason)),
+                None => ready.push(todo.clone()),
+            }
+        }
+
+        Self { ready, excluded }
+    }
+}
+
+// Returns the first reason, if any, why `todo` isn't ready.
+fn exclusion(
+    todo: &Todo,
+    todos: &[Todo],
+    context: Option<&str>,
+    today: NaiveDate,
+) -> Option<Exclusion> {
+    if let Some(context) = context {
+        if todo.context.as_deref() != Some(context) {
+            return Some(Exclusion::Context(todo.context.clone()));
+        }
+    }
+
+    if let Some(after) = todo.after {
+        if todo.is_deferred_at(today) {
+            return Some(Exclusion::Deferred(after));
+        }
+    }
+
+    let is_open = |id: &ID| todos.iter().any(|t| &t.id == id && !t.is_done());
+    let blockers: Vec<ID> = todo
+        .links
+        .values()
+        .iter()
+        .filter_map(|link| match link {
+            Link::BlockedBy(id) if is_open(id) => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if !blockers.is_empty() {
+        return Some(Exclusion::BlockedBy(blockers));
+    }
+    let has_blockers = todo.links.values().iter().any(|link| link.is_blocked_by());
+    if todo.status == Status::Blocked && !has_blockers {
+        return Some(Exclusion::Blocked);
+    }
+
+    let subtasks = todos
+        .iter()
+        .filter(|t| t.parent == Some(todo.id) && !t.is_done())
+        .count();
+    if subtasks > 0 {
+        return Some(Exclusion::Subtasks(subtasks));
+    }
+
+    None
+}
@@ -0,0 +1,241 @@
+use crate::err;
+use crate::error::Error;
+use crate::model::Todo;
+use chrono::NaiveDate;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// A field todos can be sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortField {
+    /// Highest priority first.
+    Prio,
+    /// Started, new, blocked and then done.
+    Status,
+    /// Oldest first.
+    Created,
+    /// Earliest due date first.
+    Due,
+    Id,
+    Context,
+    /// Alphabetically, ignoring case.
+    Subject,
+}
+
+impl SortField {
+    pub fn values() -> [&'static str; 7] {
+        [
+            "prio", "status", "created", "due", "id", "context", "subject",
+        ]
+    }
+
+    fn compare(&self, a: &Todo, b: &Todo) -> Ordering {
+        match self {
+            SortField::Prio => a.prio.cmp(&b.prio),
+            SortField::Status => a.status.cmp(&b.status),
+            SortField::Created => a.created.cmp(&b.created),
+            SortField::Due => a.due.cmp(&b.due),
+            SortField::Id => a.id.cmp(&b.id),
+            SortField::Context => a.context.cmp(&b.context),
+            SortField::Subject => a.subject.to_lowercase().cmp(&b.subject.to_lowercase()),
+        }
+    }
+
+    // Returns true if the todo has no value for the field, e.g. no due date.
+    fn is_missing(&self, todo: &Todo) -> bool {
+        match self {
+            SortField::Due => todo.due.is_none(),
+            SortField::Context => todo.context.is_none(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for SortField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SortField::Prio => "prio",
+            SortField::Status => "status",
+            SortField::Created => "created",
+            SortField::Due => "due",
+            SortField::Id => "id",
+            SortField::Context => "context",
+            SortField::Subject => "subject",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl TryFrom<&str> for SortField {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "prio" | "priority" => Ok(SortField::Prio),
+            "status" => Ok(SortField::Status),
+            "created" => Ok(SortField::Created),
+            "due" => Ok(SortField::Due),
+            "id" => Ok(SortField::Id),
+            "context" | "ctx" => Ok(SortField::Context),
+            "subject" => Ok(SortField::Subject),
+            _ => err!(
+                "invalid sort key: {}, expected one of {}",
+                value,
+                SortField::values().join(", ")
+            ),
+        }
+    }
+}
+
+/// A field to sort by and the direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+/// Order of listed todos, given as comma separated keys where a leading
+/// `-` reverses the key, e.g. `prio,-created`. Later keys break ties of
+/// earlier ones and todos without a due date or context always end up
+/// last when sorting by those. Without keys the todos are sorted using
+/// the default order of todos.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Sort {
+    keys: Vec<SortKey>,
+}
+
+impl Sort {
+    pub fn keys(&self) -> &[SortKey] {
+        &self.keys
+    }
+
+    /// Sorts the todos, keeping the current order of todos that are equal.
+    /// The default order puts todos overdue at `today` first.
+    pub fn apply(&self, todos: &mut [Todo], today: NaiveDate) {
+        if self.keys.is_empty() {
+            todos.sort_by(|a, b| a.cmp_at(b, today));
+        } else {
+            todos.sort_by(|a, b| self.compare(a, b));
+        }
+    }
+
+    fn compare(&self, a: &Todo, b: &Todo) -> Ordering {
+        for key in &self.keys {
+            let field = key.field;
+            let ordering = match (field.is_missing(a), field.is_missing(b)) {
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                _ if key.descending => field.compare(b, a),
+                _ => field.compare(a, b),
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = self
+            .keys
+            .iter()
+            .map(|key| match key.descending {
+                true => format!("-{}", key.field),
+                false => key.field.to_string(),
+            })
+            .collect();
+        write!(f, "{}", keys.join(","))
+    }
+}
+
+impl TryFrom<&str> for Sort {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let mut keys = Vec::new();
+        for key in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (name, descending) = match key.strip_prefix('-') {
+                Some(name) => (name, true),
+                None => (key.strip_prefix('+').unwrap_or(key), false),
+            };
+            keys.push(SortKey {
+                field: SortField::try_from(name)?,
+                descending,
+            });
+        }
+        Ok(Sort { keys })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Prio, Status, CSV, ID};
+    use chrono::Local;
+
+    fn todo(id: u16, prio: Prio, due: Option<u32>) -> Todo {
+        Todo::new(
+            ID::new(id),
+            Local::now(),
+            Status::New,
+            prio,
+            format!("todo {}", id),
+            "".to_string(),
+            CSV::default(),
+            None,
+            CSV::new(vec![]),
+        )
+        .with_due(due.map(|day| NaiveDate::from_ymd_opt(2026, 3, day).unwrap()))
+    }
+
+    fn ids(todos: &[Todo]) -> Vec<String> {
+        todos.iter().map(|todo| todo.id.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_sort() {
+        let sort = Sort::try_from("prio, -created,+due").unwrap();
+        assert_eq!(sort.to_string(), "prio,-created,due");
+        assert!(Sort::try_from("").unwrap().keys().is_empty());
+        assert!(Sort::try_from("prio,size").is_err());
+    }
+
+    #[test]
+    fn sort_by_keys() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 3).unwrap();
+        let mut todos = vec![
+            todo(1, Prio::Low, Some(5)),
+            todo(2, Prio::High, None),
+            todo(3, Prio::High, Some(2)),
+            todo(4, Prio::Low, Some(1)),
+        ];
+
+        Sort::try_from("prio,due").unwrap().apply(&mut todos, today);
+        assert_eq!(ids(&todos), ["3", "2", "4", "1"]);
+
+        Sort::try_from("-due").unwrap().apply(&mut todos, today);
+        assert_eq!(ids(&todos), ["1", "3", "4", "2"]);
+
+        Sort::try_from("-id").unwrap().apply(&mut todos, today);
+        assert_eq!(ids(&todos), ["4", "3", "2", "1"]);
+    }
+
+    #[test]
+    fn default_sort_puts_overdue_first() {
+        let mut todos = vec![
+            todo(1, Prio::High, Some(5)),
+            todo(2, Prio::Low, Some(2)),
+            todo(3, Prio::Low, Some(1)),
+            todo(4, Prio::Normal, None),
+        ];
+
+        // Nothing is overdue on the first
+        Sort::default().apply(&mut todos, NaiveDate::from_ymd_opt(2026, 3, 1).unwrap());
+        assert_eq!(ids(&todos), ["1", "4", "3", "2"]);
+
+        Sort::default().apply(&mut todos, NaiveDate::from_ymd_opt(2026, 3, 3).unwrap());
+        assert_eq!(ids(&todos), ["3", "2", "1", "4"]);
+    }
+}
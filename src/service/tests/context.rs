@@ -100,3 +100,23 @@ async fn remove_context_cascade() -> Result<()> {
     assert!(context.is_none());
     Ok(())
 }
+
+#[tokio::test]
+async fn remove_context_keeps_deferred() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.set_context(&fixture.ctx).await?;
+    let todo = fixture.create_todo().await?;
+    let next_week = chrono::Local::now()
+        .date_naive()
+        .checked_add_days(chrono::Days::new(7));
+    fixture
+        .svc
+        .update_todo(&todo.id, Changeset::default().with_after(next_week))
+        .await?;
+
+    fixture.svc.remove_context(&fixture.ctx, false).await?;
+
+    let todo = fixture.svc.get_todo(&todo.id).await?;
+    assert_eq!(todo.context, None);
+    Ok(())
+}
@@ -0,0 +1,86 @@
+use super::*;
+use crate::error::Result;
+use crate::model::event::{Action, Kind};
+use crate::model::Status;
+
+#[tokio::test]
+async fn setup_records_events() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let events = fixture.svc.list_events(None, None).await?;
+    // 4 added todos, 1 added context and 2 todos updated by linking
+    assert_eq!(events.len(), 7);
+    Ok(())
+}
+
+#[tokio::test]
+async fn update_records_before_and_after() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let cs = Changeset::default().with_status(Status::Started);
+    fixture.svc.update_todo(&fixture.todo_new.id, cs).await?;
+
+    let events = fixture
+        .svc
+        .list_events(Some(fixture.todo_new.id), None)
+        .await?;
+    assert_eq!(events.len(), 2);
+    assert!(matches!(events[0].kind, Kind::AddTodo(_)));
+    assert!(matches!(events[1].action, Action::Update));
+    match &events[1].kind {
+        Kind::UpdateTodo { before, after } => {
+            assert_eq!(before.status, Status::New);
+            assert_eq!(after.status, Status::Started);
+        }
+        _ => panic!("expected update event"),
+    }
+    assert_eq!(
+        events[1].kind.to_string(),
+        format!(
+            "Updated todo with ID {}: status: 'new' -> 'started'",
+            fixture.todo_new.id
+        )
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn remove_records_event() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture
+        .svc
+        .remove_todo(&fixture.todo_done.id, false)
+        .await?;
+
+    let events = fixture
+        .svc
+        .list_events(Some(fixture.todo_done.id), None)
+        .await?;
+    assert!(matches!(events.last().unwrap().kind, Kind::RemoveTodo(_)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn remove_context_cascade_records_todos() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.set_context(&fixture.ctx).await?;
+    let todo = fixture.create_todo().await?;
+    fixture.svc.remove_context(&fixture.ctx, true).await?;
+
+    let events = fixture.svc.list_events(Some(todo.id), None).await?;
+    match &events.last().unwrap().kind {
+        Kind::RemoveContext(name, todos) => {
+            assert_eq!(name, &fixture.ctx);
+            assert_eq!(todos.len(), 1);
+        }
+        _ => panic!("expected remove context event"),
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_events_since() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let tomorrow = chrono::Local::now() + chrono::Duration::days(1);
+    let events = fixture.svc.list_events(None, Some(tomorrow)).await?;
+    assert!(events.is_empty());
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use chrono::Days;
 
-use super::{ContextFilter, Filter, PruneFilter, StatusFilter};
+use super::{ContextFilter, DueFilter, Expr, Filter, PruneFilter, StatusFilter};
 use crate::model::{Prio, Status, Todo, CSV, ID};
 
 #[test]
@@ -85,6 +85,76 @@ fn test_filter_tags() {
     assert_eq!(todos.len(), 2);
 }
 
+#[test]
+fn test_filter_due_overdue() {
+    let todos = build_todos();
+    let filter = Filter::default().due(DueFilter::Overdue);
+    let todos = filter.apply(todos, None);
+    assert_eq!(todos.len(), 1);
+}
+
+#[test]
+fn test_filter_due_today() {
+    let todos = build_todos();
+    let filter = Filter::default().due(DueFilter::Today);
+    let todos = filter.apply(todos, None);
+    assert_eq!(todos.len(), 2);
+}
+
+#[test]
+fn test_filter_due_within() {
+    let todos = build_todos();
+    let filter = Filter::default().due(DueFilter::Within(3));
+    let todos = filter.apply(todos, None);
+    assert_eq!(todos.len(), 3);
+}
+
+#[test]
+fn test_filter_deferred() {
+    let tomorrow = chrono::Local::now()
+        .date_naive()
+        .checked_add_days(Days::new(1));
+    let mut todos = build_todos();
+    todos[0].after = tomorrow;
+    todos[1].after = Some(chrono::Local::now().date_naive());
+
+    let filter = Filter::default();
+    assert_eq!(filter.apply(todos.clone(), None).len(), 3);
+
+    let filter = Filter::default().include_deferred(true);
+    assert_eq!(filter.apply(todos, None).len(), 4);
+}
+
+#[test]
+fn test_due_filter_try_from() {
+    assert!(matches!(
+        DueFilter::try_from("overdue"),
+        Ok(DueFilter::Overdue)
+    ));
+    assert!(matches!(DueFilter::try_from("today"), Ok(DueFilter::Today)));
+    assert!(matches!(DueFilter::try_from("7"), Ok(DueFilter::Within(7))));
+    assert!(matches!(
+        DueFilter::try_from("7d"),
+        Ok(DueFilter::Within(7))
+    ));
+    assert!(DueFilter::try_from("7ddd").is_err());
+    assert!(DueFilter::try_from("soon").is_err());
+}
+
+#[test]
+fn test_filter_due_within_overflow() {
+    let todos = build_todos();
+    let due = todos.iter().filter(|todo| todo.due.is_some()).count();
+    let filter = Filter::default()
+        .status(StatusFilter::Any)
+        .context(ContextFilter::Any)
+        .include_deferred(true)
+        .due(DueFilter::Within(u64::MAX));
+    let todos = filter.apply(todos, None);
+    assert!(due > 0);
+    assert_eq!(todos.len(), due);
+}
+
 #[test]
 fn test_prune_filter_default() {
     let todos = build_todos();
@@ -110,10 +180,45 @@ fn test_prune_filter_before() {
     assert_eq!(todos.len(), 2);
 }
 
+#[test]
+fn test_filter_expr() {
+    let cases = [
+        ("tag:feat and not tag:test", vec![2]),
+        ("tag:none", vec![1, 4]),
+        ("context:home", vec![4]),
+        ("context:home and status:done", vec![5]),
+        ("status!=new", vec![5]),
+        ("created<-1d", vec![1, 2]),
+        ("due<=today", vec![1, 2]),
+        ("due:none", vec![4]),
+        ("prio>=high", vec![]),
+        ("prio>=normal and prio<=normal", vec![1, 2, 3, 4]),
+        ("id>2 or subject:FEAT", vec![2, 3, 4]),
+        ("(id=1 or id=2) and tag:feat", vec![2]),
+    ];
+    for (input, expected) in cases {
+        let expr = Expr::try_from(input).unwrap();
+        let todos = Filter::default().expr(expr).apply(build_todos(), None);
+        let ids: Vec<ID> = todos.iter().map(|todo| todo.id).collect();
+        let expected: Vec<ID> = expected.into_iter().map(ID::new).collect();
+        assert_eq!(ids, expected, "{}", input);
+    }
+}
+
+#[test]
+fn test_prune_filter_expr() {
+    let todos = build_todos();
+    let expr = Expr::try_from("tag:test").unwrap();
+    let filter = PruneFilter::default().with_expr(expr);
+    let todos = filter.apply(todos);
+    assert_eq!(todos.len(), 2);
+}
+
 fn build_todos() -> Vec<Todo> {
     let now = chrono::Local::now();
     let yesterday = chrono::Local::now().checked_sub_days(Days::new(1)).unwrap();
     let last_week = chrono::Local::now().checked_sub_days(Days::new(7)).unwrap();
+    let today = now.date_naive();
 
     vec![
         Todo::new(
@@ -126,7 +231,8 @@ fn build_todos() -> Vec<Todo> {
             CSV::default(),
             None,
             CSV::empty(),
-        ),
+        )
+        .with_due(today.checked_sub_days(Days::new(1))),
         Todo::new(
             ID::new(2),
             last_week,
@@ -137,7 +243,8 @@ fn build_todos() -> Vec<Todo> {
             CSV::new(vec!["feat".to_string()]),
             None,
             CSV::empty(),
-        ),
+        )
+        .with_due(Some(today)),
         Todo::new(
             ID::new(3),
             yesterday,
@@ -148,7 +255,8 @@ fn build_todos() -> Vec<Todo> {
             CSV::new(vec!["feat".to_string(), "test".to_string()]),
             None,
             CSV::empty(),
-        ),
+        )
+        .with_due(today.checked_add_days(Days::new(3))),
         Todo::new(
             ID::new(4),
             now,
@@ -170,6 +278,7 @@ fn build_todos() -> Vec<Todo> {
             CSV::new(vec!["test".to_string()]),
             Some("home".to_string()),
             CSV::empty(),
-        ),
+        )
+        .with_due(today.checked_sub_days(Days::new(1))),
     ]
 }
@@ -0,0 +1,18 @@
+use super::*;
+
+#[tokio::test]
+async fn impact_orders_by_blocked() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let impact = fixture.svc.impact().await?;
+
+    // The done todo is left out
+    assert_eq!(impact.len(), 3);
+    assert_eq!(impact[0].todo.id, fixture.todo_started.id);
+    assert_eq!(impact[0].blocks, 1);
+    assert_eq!(impact[0].chain, 2);
+    assert!(impact[0].critical);
+    assert!(!impact
+        .iter()
+        .any(|i| i.todo.id == fixture.todo_new.id && i.critical));
+    Ok(())
+}
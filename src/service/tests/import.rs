@@ -0,0 +1,165 @@
+use super::*;
+use crate::error::Result;
+use crate::interop::Format;
+use crate::model::{Code, Link, Prio, Status, Todo, CSV, ID};
+use chrono::{Local, TimeZone};
+
+fn build_todo(id: u16, status: Status, context: Option<&str>) -> Todo {
+    Todo::new(
+        ID::new(id),
+        Local.with_ymd_and_hms(2026, 1, id.into(), 9, 0, 0).unwrap(),
+        status,
+        Prio::High,
+        format!("Imported {}", id),
+        "".to_string(),
+        CSV::new(vec!["imported".to_string()]),
+        context.map(str::to_string),
+        CSV::empty(),
+    )
+}
+
+#[tokio::test]
+async fn import_todos() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let completed = Local.with_ymd_and_hms(2026, 2, 1, 9, 0, 0).unwrap();
+    let mut todos = vec![
+        build_todo(1, Status::New, Some("test")),
+        build_todo(2, Status::Blocked, Some("garden")).with_parent(Some(ID::new(1))),
+        build_todo(3, Status::Done, None).with_completed(Some(completed)),
+    ];
+    todos[0].links.push(Link::Blocks(ID::new(2)));
+    todos[0].links.push(Link::RelatesTo(ID::new(99)));
+    todos[1].links.push(Link::BlockedBy(ID::new(1)));
+
+    let before = fixture.todo_count().await?;
+    let imported = fixture
+        .svc
+        .import_todos(todos.clone(), &HashMap::new())
+        .await?;
+    assert_eq!(fixture.todo_count().await?, before + 3);
+
+    let first = fixture.svc.get_todo(&imported[0].id).await?;
+    let second = fixture.svc.get_todo(&imported[1].id).await?;
+    let third = fixture.svc.get_todo(&imported[2].id).await?;
+    assert_ne!(first.id, ID::new(1));
+    assert_eq!(first.created, todos[0].created);
+    assert_eq!(first.tags.encode(), "imported");
+    // The link to a todo outside the import is dropped.
+    assert_eq!(first.links.encode(), format!("blocks:{}", second.id));
+    assert_eq!(second.links.encode(), format!("blockedby:{}", first.id));
+    assert_eq!(second.parent, Some(first.id));
+    assert_eq!(second.status, Status::Blocked);
+    assert_eq!(third.completed, Some(completed));
+
+    assert_eq!(first.context.as_deref(), Some("test"));
+    assert_eq!(second.context.as_deref(), Some("garden"));
+    assert!(fixture
+        .svc
+        .list_contexts()
+        .await?
+        .contains(&"garden".to_string()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn import_duplicate_ids_gives_error() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let before = fixture.todo_count().await?;
+    let todos = vec![
+        build_todo(1, Status::New, None),
+        build_todo(1, Status::New, None),
+    ];
+    assert!(fixture
+        .svc
+        .import_todos(todos, &HashMap::new())
+        .await
+        .is_err());
+    assert_eq!(fixture.todo_count().await?, before);
+    Ok(())
+}
+
+#[tokio::test]
+async fn import_merges_existing_todos() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let mut todo = fixture.todo_new.clone();
+    todo.id = ID::new(1);
+    todo.subject = "Merged subject".to_string();
+    todo.status = Status::Blocked;
+    todo.links.push(Link::BlockedBy(ID::new(2)));
+    let todos = vec![todo, build_todo(2, Status::New, None)];
+    let existing = HashMap::from([(ID::new(1), fixture.todo_new.id)]);
+
+    let before = fixture.todo_count().await?;
+    let imported = fixture.svc.import_todos(todos, &existing).await?;
+    assert_eq!(fixture.todo_count().await?, before + 1);
+
+    let merged = fixture.svc.get_todo(&fixture.todo_new.id).await?;
+    assert_eq!(merged.subject, "Merged subject");
+    assert_eq!(merged.created, fixture.todo_new.created);
+    assert_eq!(merged.status, Status::Blocked);
+    assert_eq!(
+        merged.links.encode(),
+        format!("blockedby:{}", imported[1].id)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_import_changes_nothing() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let mut merged = fixture.todo_new.clone();
+    merged.id = ID::new(1);
+    merged.subject = "Merged subject".to_string();
+    let existing = HashMap::from([(ID::new(1), fixture.todo_new.id)]);
+    let before = fixture.todo_count().await?;
+    let contexts = fixture.svc.list_contexts().await?;
+
+    // The last todo has an invalid context
+    let todos = vec![
+        merged.clone(),
+        build_todo(2, Status::New, Some("garden")),
+        build_todo(3, Status::New, Some("x")),
+    ];
+    assert!(fixture.svc.import_todos(todos, &existing).await.is_err());
+
+    // Subtasks in a cycle
+    let todos = vec![
+        merged,
+        build_todo(2, Status::New, None).with_parent(Some(ID::new(3))),
+        build_todo(3, Status::New, None).with_parent(Some(ID::new(2))),
+    ];
+    assert!(fixture.svc.import_todos(todos, &existing).await.is_err());
+
+    assert_eq!(fixture.todo_count().await?, before);
+    assert_eq!(fixture.svc.list_contexts().await?, contexts);
+    let todo = fixture.svc.get_todo(&fixture.todo_new.id).await?;
+    assert_eq!(todo.subject, fixture.todo_new.subject);
+    Ok(())
+}
+
+#[tokio::test]
+async fn import_foreign_file_twice() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let s = "BEGIN:VCALENDAR\r\n\
+        BEGIN:VTODO\r\n\
+        UID:abc@client\r\n\
+        SUMMARY:From client\r\n\
+        END:VTODO\r\n\
+        END:VCALENDAR\r\n";
+    let before = fixture.todo_count().await?;
+
+    let mut imported = Vec::new();
+    for _ in 0..2 {
+        let import = Format::Ical.import(s)?;
+        let existing = import.matches(&fixture.svc.list_todos(None, None).await?);
+        imported = fixture.svc.import_todos(import.todos, &existing).await?;
+    }
+    assert_eq!(fixture.todo_count().await?, before + 1);
+
+    let todo = fixture.svc.get_todo(&imported[0].id).await?;
+    assert_eq!(todo.uid.as_deref(), Some("abc@client"));
+    assert!(Format::Ical
+        .export(&[todo])
+        .contains("\r\nUID:abc@client\r\n"));
+    Ok(())
+}
@@ -5,7 +5,7 @@ use crate::model::Status;
 #[tokio::test]
 async fn list_todos_filter_none() -> Result<()> {
     let fixture = Fixture::setup().await?;
-    let todos = fixture.svc.list_todos(None).await?;
+    let todos = fixture.svc.list_todos(None, None).await?;
     assert!(!todos.is_empty());
     Ok(())
 }
@@ -14,7 +14,7 @@ async fn list_todos_filter_none() -> Result<()> {
 async fn list_todos_filter_status_done() -> Result<()> {
     let fixture = Fixture::setup().await?;
     let filter = Filter::default().status(StatusFilter::Status(Status::Done));
-    let todos = fixture.svc.list_todos(Some(filter)).await?;
+    let todos = fixture.svc.list_todos(Some(filter), None).await?;
     assert_eq!(todos.len(), 1);
     Ok(())
 }
@@ -35,10 +35,63 @@ async fn list_todos_with_context_set() -> Result<()> {
         .await?;
 
     let filter = Filter::default();
-    if let [actual] = &fixture.svc.list_todos(Some(filter)).await?[..] {
+    if let [actual] = &fixture.svc.list_todos(Some(filter), None).await?[..] {
         assert_eq!(expected.id, actual.id);
     } else {
         panic!();
     }
     Ok(())
 }
+
+#[tokio::test]
+async fn list_todos_overdue_first() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let yesterday = chrono::Local::now()
+        .date_naive()
+        .checked_sub_days(chrono::Days::new(1));
+    let overdue = fixture
+        .svc
+        .add_todo_with(
+            Status::New,
+            Prio::Low,
+            "Overdue".to_string(),
+            "Description".to_string(),
+            CSV::default(),
+            Changeset::default().with_due(yesterday),
+        )
+        .await?;
+
+    let todos = fixture
+        .svc
+        .list_todos(Some(Filter::default()), None)
+        .await?;
+    assert_eq!(todos.first().map(|t| t.id), Some(overdue.id));
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_todos_sorted() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let sort = Sort::try_from("status,-id")?;
+    let todos = fixture.svc.list_todos(None, Some(&sort)).await?;
+    assert_eq!(todos.len(), 4);
+    for pair in todos.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        assert!(a.status < b.status || (a.status == b.status && a.id > b.id));
+    }
+    assert_eq!(todos.last().map(|t| t.id), Some(fixture.todo_done.id));
+    Ok(())
+}
+
+#[tokio::test]
+async fn list_todos_default_sort() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let svc = fixture.svc.with_sort(Sort::try_from("-id")?);
+    let todos = svc.list_todos(None, None).await?;
+    assert!(todos.windows(2).all(|w| w[0].id > w[1].id));
+
+    // A given sort replaces the default.
+    let todos = svc.list_todos(None, Some(&Sort::try_from("id")?)).await?;
+    assert!(todos.windows(2).all(|w| w[0].id < w[1].id));
+    Ok(())
+}
@@ -2,18 +2,29 @@ use super::*;
 use crate::error::Result;
 use crate::model::{Prio, Status, Todo, ID};
 use crate::repository::Repository;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
 use std::str::FromStr;
 
 mod add;
 mod context;
+mod event;
 mod filter;
+mod impact;
+mod import;
 mod list;
+mod next;
+mod query;
 mod remove;
+mod search;
+mod subtask;
+mod undo;
 mod update;
+mod view;
 
 struct Fixture {
     svc: Service,
+    /// Connection to the database, for checking tables directly.
+    pool: SqlitePool,
     // Todos added for testing: todo_<status>
     todo_new: Todo,
     todo_done: Todo,
@@ -36,7 +47,7 @@ impl Fixture {
 
         let test_context_name = String::from("test");
 
-        let service = Service::new(Repository::new(pool));
+        let service = Service::new(Repository::new(pool.clone()));
 
         // Setup initial data
         let (todo_new, todo_done, todo_started, todo_blocked, _) = tokio::join!(
@@ -83,6 +94,7 @@ impl Fixture {
 
         Ok(Self {
             svc: service,
+            pool,
             ctx: test_context_name,
             todo_new,
             todo_done,
@@ -113,6 +125,6 @@ impl Fixture {
     }
 
     async fn todo_count(&self) -> Result<usize> {
-        Ok(self.svc.list_todos(None).await?.len())
+        Ok(self.svc.list_todos(None, None).await?.len())
     }
 }
@@ -0,0 +1,89 @@
+use super::*;
+use crate::service::changeset::Changeset;
+use crate::service::ready::Exclusion;
+use chrono::{Days, Local};
+
+#[tokio::test]
+async fn next_excludes_blocked_and_done() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let queue = fixture.svc.next_todos(None).await?;
+
+    let ready: Vec<ID> = queue.ready.iter().map(|t| t.id).collect();
+    assert_eq!(ready, vec![fixture.todo_started.id, fixture.todo_new.id]);
+
+    assert_eq!(queue.excluded.len(), 1);
+    let (todo, reason) = &queue.excluded[0];
+    assert_eq!(todo.id, fixture.todo_blocked.id);
+    assert_eq!(reason, &Exclusion::BlockedBy(vec![fixture.todo_started.id]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn next_with_limit() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let queue = fixture.svc.next_todos(Some(1)).await?;
+    assert_eq!(queue.ready.len(), 1);
+    assert_eq!(queue.ready[0].id, fixture.todo_started.id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn next_includes_todo_when_blocker_done() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    // Mark the blocker as done without removing the link
+    let mut blocker = fixture.svc.get_todo(&fixture.todo_started.id).await?;
+    blocker.status = Status::Done;
+    fixture.svc.repo.replace_todo(&blocker).await?;
+
+    let queue = fixture.svc.next_todos(None).await?;
+    assert!(queue.ready.contains(&fixture.todo_blocked));
+    Ok(())
+}
+
+#[tokio::test]
+async fn next_excludes_other_context() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.set_context(&fixture.ctx).await?;
+    let todo = fixture.create_todo().await?;
+
+    let queue = fixture.svc.next_todos(None).await?;
+    let ready: Vec<ID> = queue.ready.iter().map(|t| t.id).collect();
+    assert_eq!(ready, vec![todo.id]);
+    assert!(queue
+        .excluded
+        .iter()
+        .any(|(t, reason)| t.id == fixture.todo_new.id && reason == &Exclusion::Context(None)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn next_excludes_deferred_and_parents() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let tomorrow = Local::now().date_naive() + Days::new(1);
+    let cs = Changeset::default().with_after(Some(tomorrow));
+    fixture
+        .svc
+        .update_todo(&fixture.todo_started.id, cs)
+        .await?;
+    let cs = Changeset::default().with_parent(Some(fixture.todo_new.id));
+    let subtask = fixture.create_todo().await?;
+    fixture.svc.update_todo(&subtask.id, cs).await?;
+
+    let queue = fixture.svc.next_todos(None).await?;
+    let ready: Vec<ID> = queue.ready.iter().map(|t| t.id).collect();
+    assert_eq!(ready, vec![subtask.id]);
+
+    let reason = |id: ID| {
+        queue
+            .excluded
+            .iter()
+            .find(|(t, _)| t.id == id)
+            .map(|(_, reason)| reason)
+    };
+    assert_eq!(
+        reason(fixture.todo_started.id),
+        Some(&Exclusion::Deferred(tomorrow))
+    );
+    assert_eq!(reason(fixture.todo_new.id), Some(&Exclusion::Subtasks(1)));
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use super::*;
+use crate::service::filter::{ContextFilter, DueFilter, StatusFilter};
+use chrono::NaiveDate;
+
+// Todos covering the combinations of values the filters look at.
+async fn setup(fixture: &Fixture) -> Result<Vec<Todo>> {
+    fixture.svc.add_context("work").await?;
+    let statuses = [Status::New, Status::Started, Status::Blocked, Status::Done];
+    let contexts = [None, Some(fixture.ctx.clone()), Some("work".to_string())];
+    let tags = [vec![], vec!["a"], vec!["a", "b"], vec!["c"]];
+    let dates = [None, Some(date(10)), Some(date(15)), Some(date(18))];
+
+    let mut n = 0;
+    for status in &statuses {
+        for context in &contexts {
+            for tag in &tags {
+                n += 1;
+                let todo = Todo::new(
+                    ID::new(0),
+                    Local::now(),
+                    status.clone(),
+                    Prio::Normal,
+                    format!("Todo {n}"),
+                    String::new(),
+                    CSV::new(tag.iter().map(|t| t.to_string()).collect()),
+                    context.clone(),
+                    CSV::empty(),
+                )
+                .with_due(dates[n % dates.len()])
+                .with_after(dates[(n / 2) % dates.len()]);
+                fixture.svc.repo.add_todo(todo).await?;
+            }
+        }
+    }
+    fixture.svc.repo.get_todos().await
+}
+
+fn date(day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(2026, 6, day).unwrap()
+}
+
+fn statuses() -> Vec<StatusFilter> {
+    vec![
+        StatusFilter::Any,
+        StatusFilter::Relevant,
+        StatusFilter::Status(Status::New),
+        StatusFilter::Status(Status::Done),
+    ]
+}
+
+fn contexts() -> Vec<ContextFilter> {
+    vec![
+        ContextFilter::Any,
+        ContextFilter::None,
+        ContextFilter::Current,
+        ContextFilter::Name("work".to_string()),
+        ContextFilter::Name("unknown".to_string()),
+    ]
+}
+
+fn tags() -> Vec<Option<Vec<String>>> {
+    vec![
+        None,
+        Some(vec![]),
+        Some(vec!["a".to_string()]),
+        Some(vec!["b".to_string(), "c".to_string()]),
+    ]
+}
+
+fn dues() -> Vec<DueFilter> {
+    vec![
+        DueFilter::Any,
+        DueFilter::Overdue,
+        DueFilter::Today,
+        DueFilter::Within(3),
+        DueFilter::Within(u64::MAX),
+    ]
+}
+
+fn ids(todos: &[Todo]) -> Vec<ID> {
+    let mut ids: Vec<ID> = todos.iter().map(|todo| todo.id).collect();
+    ids.sort();
+    ids
+}
+
+#[tokio::test]
+async fn query_matches_filter() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let todos = setup(&fixture).await?;
+    let today = date(15);
+
+    let mut checked = 0;
+    for current in [None, Some(fixture.ctx.clone())] {
+        for status in statuses() {
+            for context in contexts() {
+                for tags in tags() {
+                    for due in dues() {
+                        for deferred in [false, true] {
+                            let mut filter = Filter::default()
+                                .status(status.clone())
+                                .context(context.clone())
+                                .due(due.clone())
+                                .include_deferred(deferred);
+                            if let Some(tags) = &tags {
+                                filter = filter.tags(tags.clone());
+                            }
+
+                            let query = filter.query(current.clone(), today);
+                            let found = fixture.svc.repo.find_todos(&query).await?;
+                            let expected = filter.apply_at(todos.clone(), current.clone(), today);
+                            assert_eq!(ids(&found), ids(&expected), "{:?}", filter);
+                            checked += !expected.is_empty() as usize;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    assert!(checked > 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn query_loads_tags_and_links() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let filter = Filter::default()
+        .context(ContextFilter::Any)
+        .tags(vec!["new".to_string()]);
+    let query = filter.query(None, Local::now().date_naive());
+    let found = fixture.svc.repo.find_todos(&query).await?;
+    assert_eq!(ids(&found), vec![fixture.todo_new.id]);
+    assert_eq!(found[0].tags.values(), fixture.todo_new.tags.values());
+
+    let query = Filter::default()
+        .status(StatusFilter::Status(Status::Blocked))
+        .query(None, Local::now().date_naive());
+    let found = fixture.svc.repo.find_todos(&query).await?;
+    assert_eq!(ids(&found), vec![fixture.todo_blocked.id]);
+    assert!(found[0]
+        .links
+        .contains(&Link::BlockedBy(fixture.todo_started.id)));
+    Ok(())
+}
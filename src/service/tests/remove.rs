@@ -4,7 +4,7 @@ use crate::error::{Error, Result};
 #[tokio::test]
 async fn remove_new_todo() -> Result<()> {
     let fixture = Fixture::setup().await?;
-    fixture.svc.remove_todo(&fixture.todo_new.id).await?;
+    fixture.svc.remove_todo(&fixture.todo_new.id, false).await?;
     assert!(!fixture.todo_exists(&fixture.todo_new.id).await?);
     Ok(())
 }
@@ -12,7 +12,10 @@ async fn remove_new_todo() -> Result<()> {
 #[tokio::test]
 async fn remove_done_todo() -> Result<()> {
     let fixture = Fixture::setup().await?;
-    fixture.svc.remove_todo(&fixture.todo_done.id).await?;
+    fixture
+        .svc
+        .remove_todo(&fixture.todo_done.id, false)
+        .await?;
     assert!(!fixture.todo_exists(&fixture.todo_done.id).await?);
     Ok(())
 }
@@ -21,7 +24,7 @@ async fn remove_done_todo() -> Result<()> {
 async fn remove_unknown() -> Result<()> {
     let fixture = Fixture::setup().await?;
     let err = matches!(
-        fixture.svc.remove_todo(&ID::new(9999)).await,
+        fixture.svc.remove_todo(&ID::new(9999), false).await,
         Err(Error::NotFound(_))
     );
     assert!(err);
@@ -34,10 +37,40 @@ async fn removing_todo_removes_links() -> Result<()> {
     let fixture = Fixture::setup().await?;
 
     // Act
-    fixture.svc.remove_todo(&fixture.todo_started.id).await?;
+    fixture
+        .svc
+        .remove_todo(&fixture.todo_started.id, false)
+        .await?;
 
     // Assert
     let todo = fixture.svc.get_todo(&fixture.todo_blocked.id).await?;
     assert!(matches!(todo.status, Status::New));
     Ok(())
 }
+
+#[tokio::test]
+async fn removing_todo_cascades_links_and_tags() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+
+    // Remove without the cleanup done by the service.
+    fixture
+        .svc
+        .repo
+        .remove_todo(&fixture.todo_started.id)
+        .await?;
+
+    let todo = fixture.svc.get_todo(&fixture.todo_blocked.id).await?;
+    assert!(todo.links.is_empty());
+    let tags: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM todo_tags WHERE todo_id = $1")
+        .bind(fixture.todo_started.id.to_string())
+        .fetch_one(&fixture.pool)
+        .await?;
+    assert_eq!(tags, 0);
+    let restored = fixture
+        .svc
+        .repo
+        .add_todo(fixture.todo_started.clone())
+        .await?;
+    assert!(restored.tags.contains(&"done".to_string()));
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use super::*;
+use crate::service::changeset::Changeset;
+use crate::service::filter::{ContextFilter, StatusFilter};
+
+async fn add(fixture: &Fixture, subject: &str, description: &str) -> Result<Todo> {
+    fixture
+        .svc
+        .add_todo(
+            Status::New,
+            Prio::Normal,
+            subject.to_string(),
+            description.to_string(),
+            CSV::new(vec!["search".to_string()]),
+        )
+        .await
+}
+
+fn ids(todos: &[Todo]) -> Vec<ID> {
+    todos.iter().map(|todo| todo.id).collect()
+}
+
+#[tokio::test]
+async fn search_subject_and_description() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let a = add(&fixture, "Deploy the server", "").await?;
+    let b = add(&fixture, "Release", "Deploy when tests pass").await?;
+
+    let found = fixture
+        .svc
+        .search_todos("deploy", Filter::default())
+        .await?;
+    let mut found = ids(&found);
+    found.sort();
+    assert_eq!(found, vec![a.id, b.id]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn search_phrase_and_prefix() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let a = add(&fixture, "Write release notes", "").await?;
+    add(&fixture, "Notes on the release", "").await?;
+
+    let found = fixture
+        .svc
+        .search_todos("\"release notes\"", Filter::default())
+        .await?;
+    assert_eq!(ids(&found), vec![a.id]);
+
+    let found = fixture.svc.search_todos("wri*", Filter::default()).await?;
+    assert_eq!(ids(&found), vec![a.id]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn search_ranks_best_match_first() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let a = add(
+        &fixture,
+        "Backup",
+        "Once the backup is done, verify the backup",
+    )
+    .await?;
+    let b = add(&fixture, "Backup backup backup", "").await?;
+
+    let found = fixture
+        .svc
+        .search_todos("backup", Filter::default())
+        .await?;
+    assert_eq!(ids(&found), vec![b.id, a.id]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn search_with_filter() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let a = add(&fixture, "Deploy", "").await?;
+    let b = add(&fixture, "Deploy again", "").await?;
+    let cs = Changeset::default().with_status(Status::Done);
+    fixture.svc.update_todo(&b.id, cs).await?;
+
+    let found = fixture
+        .svc
+        .search_todos("deploy", Filter::default())
+        .await?;
+    assert_eq!(ids(&found), vec![a.id]);
+
+    let filter = Filter::default()
+        .status(StatusFilter::Status(Status::Done))
+        .context(ContextFilter::Any)
+        .tags(vec!["search".to_string()]);
+    let found = fixture.svc.search_todos("deploy", filter).await?;
+    assert_eq!(ids(&found), vec![b.id]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn search_follows_updates_and_removes() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let a = add(&fixture, "Deploy", "").await?;
+    let cs = Changeset::default().with_subject("Release".to_string());
+    fixture.svc.update_todo(&a.id, cs).await?;
+
+    let found = fixture
+        .svc
+        .search_todos("deploy", Filter::default())
+        .await?;
+    assert!(found.is_empty());
+    let found = fixture
+        .svc
+        .search_todos("release", Filter::default())
+        .await?;
+    assert_eq!(ids(&found), vec![a.id]);
+
+    fixture.svc.remove_todo(&a.id, false).await?;
+    let found = fixture
+        .svc
+        .search_todos("release", Filter::default())
+        .await?;
+    assert!(found.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn search_invalid_query() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    assert!(fixture
+        .svc
+        .search_todos("deploy AND", Filter::default())
+        .await
+        .is_err());
+    assert!(fixture
+        .svc
+        .search_todos(" ", Filter::default())
+        .await
+        .is_err());
+    let err = fixture
+        .svc
+        .search_todos("\"write spec", Filter::default())
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.starts_with("invalid search query: \"write spec"),
+        "{}",
+        err
+    );
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use super::*;
+use crate::service::changeset::Changeset;
+
+impl Fixture {
+    async fn create_subtask(&self, parent: &ID) -> Result<Todo> {
+        self.svc
+            .add_todo_with(
+                Status::New,
+                Prio::Normal,
+                "Subtask".to_string(),
+                "Description".to_string(),
+                CSV::default(),
+                Changeset::default().with_parent(Some(*parent)),
+            )
+            .await
+    }
+}
+
+#[tokio::test]
+async fn add_subtask() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let subtask = fixture.create_subtask(&fixture.todo_new.id).await?;
+    assert_eq!(subtask.parent, Some(fixture.todo_new.id));
+
+    let subtasks = fixture
+        .svc
+        .get_subtasks(&fixture.todo_new.id, false)
+        .await?;
+    assert_eq!(subtasks.len(), 1);
+    assert_eq!(subtasks[0].id, subtask.id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_subtask_parent_not_found() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    assert!(fixture.create_subtask(&ID::new(9999)).await.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_subtasks_recursive() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let child = fixture.create_subtask(&fixture.todo_new.id).await?;
+    let grandchild = fixture.create_subtask(&child.id).await?;
+
+    let direct = fixture
+        .svc
+        .get_subtasks(&fixture.todo_new.id, false)
+        .await?;
+    assert_eq!(direct.len(), 1);
+
+    let all = fixture.svc.get_subtasks(&fixture.todo_new.id, true).await?;
+    assert_eq!(all.len(), 2);
+    assert_eq!(all[0].id, child.id);
+    assert_eq!(all[1].id, grandchild.id);
+    Ok(())
+}
+
+#[tokio::test]
+async fn set_parent_to_subtask_fails() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let child = fixture.create_subtask(&fixture.todo_new.id).await?;
+    let grandchild = fixture.create_subtask(&child.id).await?;
+
+    let cs = Changeset::default().with_parent(Some(grandchild.id));
+    let result = fixture.svc.update_todo(&fixture.todo_new.id, cs).await;
+    assert!(result.is_err());
+
+    let cs = Changeset::default().with_parent(Some(fixture.todo_new.id));
+    let result = fixture.svc.update_todo(&fixture.todo_new.id, cs).await;
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn complete_with_open_subtasks_fails() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.create_subtask(&fixture.todo_new.id).await?;
+
+    let result = fixture.svc.complete_todo(&fixture.todo_new.id, false).await;
+    assert!(result.is_err());
+    let todo = fixture.svc.get_todo(&fixture.todo_new.id).await?;
+    assert_eq!(todo.status, Status::New);
+    Ok(())
+}
+
+#[tokio::test]
+async fn complete_cascade() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let child = fixture.create_subtask(&fixture.todo_new.id).await?;
+    let grandchild = fixture.create_subtask(&child.id).await?;
+
+    let todo = fixture
+        .svc
+        .complete_todo(&fixture.todo_new.id, true)
+        .await?;
+    assert!(todo.is_done());
+    assert!(fixture.svc.get_todo(&child.id).await?.is_done());
+    assert!(fixture.svc.get_todo(&grandchild.id).await?.is_done());
+    Ok(())
+}
+
+#[tokio::test]
+async fn remove_keeps_subtasks() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let child = fixture.create_subtask(&fixture.todo_new.id).await?;
+
+    fixture.svc.remove_todo(&fixture.todo_new.id, false).await?;
+    let child = fixture.svc.get_todo(&child.id).await?;
+    assert_eq!(child.parent, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn remove_cascade() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let child = fixture.create_subtask(&fixture.todo_new.id).await?;
+    let grandchild = fixture.create_subtask(&child.id).await?;
+
+    fixture.svc.remove_todo(&fixture.todo_new.id, true).await?;
+    assert!(!fixture.todo_exists(&child.id).await?);
+    assert!(!fixture.todo_exists(&grandchild.id).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn remove_context_cascade_detaches_subtasks() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.set_context(&fixture.ctx).await?;
+    let parent = fixture.create_todo().await?;
+    let inside = fixture.create_subtask(&parent.id).await?;
+    fixture.svc.unset_context().await?;
+    let outside = fixture.create_subtask(&parent.id).await?;
+
+    fixture.svc.remove_context(&fixture.ctx, true).await?;
+    assert!(!fixture.todo_exists(&parent.id).await?);
+    assert!(!fixture.todo_exists(&inside.id).await?);
+    let outside = fixture.svc.get_todo(&outside.id).await?;
+    assert_eq!(outside.parent, None);
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use super::*;
+use crate::model::event::State;
+use crate::model::Status;
+use crate::service::changeset::Changeset;
+
+#[tokio::test]
+async fn undo_remove() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.begin_batch();
+    fixture.svc.remove_todo(&fixture.todo_new.id, false).await?;
+    assert!(!fixture.todo_exists(&fixture.todo_new.id).await?);
+
+    let events = fixture.svc.undo().await?;
+    assert_eq!(events.len(), 1);
+    let todo = fixture.svc.get_todo(&fixture.todo_new.id).await?;
+    assert_eq!(todo.subject, fixture.todo_new.subject);
+    Ok(())
+}
+
+#[tokio::test]
+async fn undo_update() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.begin_batch();
+    let cs = Changeset::default().with_status(Status::Done);
+    fixture.svc.update_todo(&fixture.todo_new.id, cs).await?;
+
+    // The confirmation shows which todo is reverted
+    let pending = fixture.svc.pending_undo().await?;
+    assert_eq!(pending.len(), 1);
+    assert!(pending[0]
+        .kind
+        .to_string()
+        .starts_with(&format!("Updated todo with ID {}:", fixture.todo_new.id)));
+
+    fixture.svc.undo().await?;
+    let todo = fixture.svc.get_todo(&fixture.todo_new.id).await?;
+    assert_eq!(todo.status, Status::New);
+    Ok(())
+}
+
+#[tokio::test]
+async fn undo_link_block() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.begin_batch();
+    fixture
+        .svc
+        .unlink_block(fixture.todo_started.id, fixture.todo_blocked.id)
+        .await?;
+
+    fixture.svc.undo().await?;
+    let blocker = fixture.svc.get_todo(&fixture.todo_started.id).await?;
+    let blocked = fixture.svc.get_todo(&fixture.todo_blocked.id).await?;
+    assert!(blocker
+        .links
+        .contains(&Link::Blocks(fixture.todo_blocked.id)));
+    assert!(blocked
+        .links
+        .contains(&Link::BlockedBy(fixture.todo_started.id)));
+    assert_eq!(blocked.status, Status::Blocked);
+    Ok(())
+}
+
+#[tokio::test]
+async fn undo_remove_context_cascade() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.set_context(&fixture.ctx).await?;
+    let todo = fixture.create_todo().await?;
+    fixture.svc.begin_batch();
+    fixture.svc.remove_context(&fixture.ctx, true).await?;
+    assert!(!fixture.todo_exists(&todo.id).await?);
+
+    fixture.svc.undo().await?;
+    assert!(fixture.svc.list_contexts().await?.contains(&fixture.ctx));
+    let restored = fixture.svc.get_todo(&todo.id).await?;
+    assert_eq!(restored.context, Some(fixture.ctx.clone()));
+    assert_eq!(fixture.svc.get_context().await?, Some(fixture.ctx.clone()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn undo_remove_context_cascade_restores_links() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.set_context(&fixture.ctx).await?;
+    let todo = fixture.create_todo().await?;
+    fixture
+        .svc
+        .link(todo.id, Link::Blocks(fixture.todo_new.id))
+        .await?;
+    fixture.svc.begin_batch();
+    fixture.svc.remove_context(&fixture.ctx, true).await?;
+    let blocked = fixture.svc.get_todo(&fixture.todo_new.id).await?;
+    assert!(blocked.links.is_empty());
+
+    fixture.svc.undo().await?;
+    let blocker = fixture.svc.get_todo(&todo.id).await?;
+    let blocked = fixture.svc.get_todo(&fixture.todo_new.id).await?;
+    assert!(blocker.links.contains(&Link::Blocks(fixture.todo_new.id)));
+    assert!(blocked.links.contains(&Link::BlockedBy(todo.id)));
+    Ok(())
+}
+
+#[tokio::test]
+async fn undo_only_last_batch() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.begin_batch();
+    fixture.svc.remove_todo(&fixture.todo_new.id, false).await?;
+    fixture.svc.begin_batch();
+    fixture
+        .svc
+        .remove_todo(&fixture.todo_done.id, false)
+        .await?;
+
+    fixture.svc.undo().await?;
+    assert!(!fixture.todo_exists(&fixture.todo_new.id).await?);
+    assert!(fixture.todo_exists(&fixture.todo_done.id).await?);
+
+    fixture.svc.undo().await?;
+    assert!(fixture.todo_exists(&fixture.todo_new.id).await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn redo_after_undo() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.begin_batch();
+    fixture.svc.remove_todo(&fixture.todo_new.id, false).await?;
+    fixture.svc.undo().await?;
+
+    let events = fixture.svc.redo().await?;
+    assert_eq!(events.len(), 1);
+    assert!(!fixture.todo_exists(&fixture.todo_new.id).await?);
+    assert!(fixture.svc.pending_redo().await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn redo_discarded_by_new_change() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.begin_batch();
+    fixture.svc.remove_todo(&fixture.todo_new.id, false).await?;
+    fixture.svc.undo().await?;
+    fixture.svc.begin_batch();
+    fixture.create_todo().await?;
+
+    assert!(fixture.svc.pending_redo().await?.is_empty());
+    assert!(fixture.svc.redo().await.is_err());
+
+    let events = fixture
+        .svc
+        .list_events(Some(fixture.todo_new.id), None)
+        .await?;
+    assert_eq!(events.last().unwrap().state, State::Discarded);
+    Ok(())
+}
+
+#[tokio::test]
+async fn undo_nothing() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    // Undo the setup of the fixture
+    fixture.svc.undo().await?;
+    assert_eq!(fixture.todo_count().await?, 0);
+    assert!(fixture.svc.undo().await.is_err());
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use super::*;
 use crate::error::Result;
-use crate::model::{Status, ID};
+use crate::model::{LinkType, LinkTypes, Prio, Recurrence, Status, ID};
 
 #[tokio::test]
 async fn update_unknown_id() -> Result<()> {
@@ -30,6 +30,23 @@ async fn update_status() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn update_status_sets_completed() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    assert!(fixture.todo_new.completed.is_none());
+    assert!(fixture.todo_done.completed.is_some());
+
+    let cs = Changeset::default().with_status(Status::Done);
+    fixture.svc.update_todo(&fixture.todo_new.id, cs).await?;
+    let todo = fixture.svc.get_todo(&fixture.todo_new.id).await?;
+    assert!(todo.completed.is_some());
+
+    let cs = Changeset::default().with_status(Status::New);
+    let todo = fixture.svc.update_todo(&todo.id, cs).await?;
+    assert!(todo.completed.is_none());
+    Ok(())
+}
+
 #[tokio::test]
 async fn update_subject() -> Result<()> {
     let fixture = Fixture::setup().await?;
@@ -75,6 +92,87 @@ async fn unset_context() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn update_due() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let due = chrono::NaiveDate::from_ymd_opt(2022, 3, 2);
+    let todo = fixture
+        .svc
+        .update_todo(&fixture.todo_new.id, Changeset::default().with_due(due))
+        .await?;
+    assert_eq!(todo.due, due);
+
+    let todo = fixture.svc.get_todo(&fixture.todo_new.id).await?;
+    assert_eq!(todo.due, due);
+
+    let todo = fixture
+        .svc
+        .update_todo(&fixture.todo_new.id, Changeset::default().with_due(None))
+        .await?;
+    assert_eq!(todo.due, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn completing_recurring_adds_next() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    fixture.svc.set_context(&fixture.ctx).await?;
+    let due = chrono::NaiveDate::from_ymd_opt(2022, 3, 2);
+    let todo = fixture
+        .svc
+        .add_todo_with(
+            Status::Started,
+            Prio::High,
+            "Recurring".to_string(),
+            "Description".to_string(),
+            CSV::new(vec!["weekly".to_string()]),
+            Changeset::default()
+                .with_due(due)
+                .with_recurrence(Some(Recurrence::Daily)),
+        )
+        .await?;
+    let before = fixture.todo_count().await?;
+
+    let cs = Changeset::default().with_status(Status::Done);
+    fixture.svc.update_todo(&todo.id, cs).await?;
+
+    let todos = fixture.svc.list_todos(None, None).await?;
+    assert_eq!(todos.len(), before + 1);
+    let next = todos
+        .iter()
+        .find(|t| t.subject == "Recurring" && !t.is_done())
+        .unwrap();
+    assert_ne!(next.id, todo.id);
+    assert_eq!(next.status, Status::New);
+    assert_eq!(next.prio, Prio::High);
+    assert_eq!(next.context, Some(fixture.ctx.clone()));
+    assert_eq!(next.tags.display_values(), vec!["weekly".to_string()]);
+    // The days missed since it was due are skipped
+    let today = chrono::Local::now().date_naive();
+    assert_eq!(next.due, today.succ_opt());
+    assert_eq!(next.recurrence, Some(Recurrence::Daily));
+    Ok(())
+}
+
+#[tokio::test]
+async fn updating_done_recurring_adds_nothing() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let todo = fixture
+        .svc
+        .update_todo(
+            &fixture.todo_done.id,
+            Changeset::default().with_recurrence(Some(Recurrence::Daily)),
+        )
+        .await?;
+    let before = fixture.todo_count().await?;
+
+    let cs = Changeset::default().with_status(Status::Done);
+    fixture.svc.update_todo(&todo.id, cs).await?;
+
+    assert_eq!(fixture.todo_count().await?, before);
+    Ok(())
+}
+
 // Links
 
 #[tokio::test]
@@ -83,7 +181,7 @@ async fn add_link() -> Result<()> {
 
     // Act
     let link = Link::Blocks(fx.todo_new.id);
-    fx.svc.link(fx.todo_started.id, link).await?;
+    fx.svc.link(fx.todo_started.id, link.clone()).await?;
 
     // Assert
     let bi_dir = link.bi_directional(fx.todo_started.id).unwrap();
@@ -93,6 +191,58 @@ async fn add_link() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn add_duplicates_link() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let link = Link::Duplicates(fixture.todo_done.id);
+    fixture.svc.link(fixture.todo_new.id, link).await?;
+
+    let done = fixture.svc.get_todo(&fixture.todo_done.id).await?;
+    assert!(done
+        .links
+        .contains(&Link::DuplicatedBy(fixture.todo_new.id)));
+
+    // Removing either side removes both
+    let link = Link::DuplicatedBy(fixture.todo_new.id);
+    fixture.svc.unlink(fixture.todo_done.id, link).await?;
+    let new = fixture.svc.get_todo(&fixture.todo_new.id).await?;
+    assert!(new.links.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn add_custom_link() -> Result<()> {
+    let mut fixture = Fixture::setup().await?;
+    let types = LinkTypes::new(vec![LinkType {
+        name: "implements".to_string(),
+        inverse: Some("implemented-by".to_string()),
+        bidirectional: false,
+    }])?;
+    fixture.svc = fixture.svc.with_link_types(types);
+
+    let link = Link::try_from(format!("implements:{}", fixture.todo_done.id))?;
+    fixture.svc.link(fixture.todo_new.id, link).await?;
+
+    let done = fixture.svc.get_todo(&fixture.todo_done.id).await?;
+    let inverse = Link::try_from(format!("implemented-by:{}", fixture.todo_new.id))?;
+    assert!(done.links.contains(&inverse));
+
+    // Removing the todo removes the inverse link
+    fixture.svc.remove_todo(&fixture.todo_new.id, false).await?;
+    let done = fixture.svc.get_todo(&fixture.todo_done.id).await?;
+    assert!(done.links.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn linking_unknown_type_gives_error() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let link = Link::try_from(format!("implements:{}", fixture.todo_done.id))?;
+    let res = fixture.svc.link(fixture.todo_new.id, link).await;
+    assert!(res.is_err());
+    Ok(())
+}
+
 #[tokio::test]
 async fn linking_unknown_gives_error() -> Result<()> {
     let fixture = Fixture::setup().await?;
@@ -127,13 +277,56 @@ async fn linking_same_on_other() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn linking_transitive_cycle_gives_error() -> Result<()> {
+    // Arrange: started blocks blocked (fixture), blocked blocks new
+    let fixture = Fixture::setup().await?;
+    let link = Link::Blocks(fixture.todo_new.id);
+    fixture.svc.link(fixture.todo_blocked.id, link).await?;
+
+    // Act: new blocks started
+    let link = Link::Blocks(fixture.todo_started.id);
+    let res = fixture.svc.link(fixture.todo_new.id, link).await;
+
+    // Assert
+    let err = res.expect_err("cycle to be rejected").to_string();
+    let path = format!(
+        "{} -> {} -> {} -> {}",
+        fixture.todo_new.id, fixture.todo_started.id, fixture.todo_blocked.id, fixture.todo_new.id
+    );
+    assert!(err.contains(&path), "{}", err);
+    assert!(fixture.svc.find_cycles().await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn find_existing_cycles() -> Result<()> {
+    // Arrange: a cycle that bypasses validation, as in older databases
+    let fixture = Fixture::setup().await?;
+    let mut todo = fixture.svc.get_todo(&fixture.todo_blocked.id).await?;
+    todo.links
+        .push_not_exists(Link::Blocks(fixture.todo_started.id));
+    fixture.svc.repo.replace_todo(&todo).await?;
+
+    // Act
+    let cycles = fixture.svc.find_cycles().await?;
+
+    // Assert
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(cycles[0].len(), 3);
+    Ok(())
+}
+
 #[tokio::test]
 async fn add_existing_does_nothing() -> Result<()> {
     let fixture = Fixture::setup().await?;
 
     // Act
     let link = Link::Blocks(fixture.todo_new.id);
-    fixture.svc.link(fixture.todo_started.id, link).await?;
+    fixture
+        .svc
+        .link(fixture.todo_started.id, link.clone())
+        .await?;
     fixture.svc.link(fixture.todo_started.id, link).await?;
     Ok(())
 }
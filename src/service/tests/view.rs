@@ -0,0 +1,74 @@
+use super::*;
+use crate::error::Error;
+use crate::model::{Layout, View};
+
+#[tokio::test]
+async fn save_and_list_views() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let triage = View::new("triage".to_string(), "prio>=high status:new".to_string())
+        .with_layout(Layout::Tree)
+        .with_sort(Some("due,-created".to_string()));
+    let mine = View::new("mine".to_string(), "tag:me".to_string());
+    fixture.svc.save_view(&triage).await?;
+    fixture.svc.save_view(&mine).await?;
+
+    assert_eq!(fixture.svc.get_view("triage").await?, triage);
+    assert_eq!(fixture.svc.list_views().await?, vec![mine, triage]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn save_view_replaces_existing() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let view = View::new("mine".to_string(), "tag:me".to_string());
+    fixture.svc.save_view(&view).await?;
+    let view = View::new("mine".to_string(), "tag:you".to_string());
+    fixture.svc.save_view(&view).await?;
+
+    assert_eq!(fixture.svc.list_views().await?, vec![view]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn save_invalid_view() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let invalid = [
+        ("ls", "tag:a"),
+        ("Mine", "tag:a"),
+        ("", "tag:a"),
+        ("mine", "tag"),
+    ];
+    for (name, filter) in invalid {
+        let view = View::new(name.to_string(), filter.to_string());
+        assert!(fixture.svc.save_view(&view).await.is_err(), "{}", name);
+    }
+    let view =
+        View::new("mine".to_string(), "tag:a".to_string()).with_sort(Some("size".to_string()));
+    assert!(fixture.svc.save_view(&view).await.is_err());
+    assert!(fixture.svc.list_views().await?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn remove_view() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    let view = View::new("mine".to_string(), "tag:me".to_string());
+    fixture.svc.save_view(&view).await?;
+    fixture.svc.remove_view("mine").await?;
+
+    assert!(fixture.svc.list_views().await?.is_empty());
+    let err = fixture.svc.remove_view("mine").await;
+    assert!(matches!(err, Err(Error::NotFound(_))));
+    Ok(())
+}
+
+#[tokio::test]
+async fn default_view() -> Result<()> {
+    let fixture = Fixture::setup().await?;
+    assert_eq!(fixture.svc.default_view().await?, None);
+
+    let view = View::new(View::DEFAULT.to_string(), "status:done".to_string());
+    fixture.svc.save_view(&view).await?;
+    assert_eq!(fixture.svc.default_view().await?, Some(view));
+    Ok(())
+}
@@ -1,5 +1,62 @@
+use crate::err;
+use crate::error::Error;
 use core::fmt;
 use crossterm::style::Stylize;
+use std::sync::OnceLock;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// How output is styled in the terminal.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Theme {
+    /// Colors, bold and underlined text.
+    #[default]
+    Default,
+    /// Bold and underlined text but no colors.
+    Mono,
+    /// No styling at all.
+    Plain,
+}
+
+impl Theme {
+    pub fn values() -> [&'static str; 3] {
+        ["default", "mono", "plain"]
+    }
+
+    /// Sets the theme used by all stylers. Only the first call has any effect.
+    pub fn apply(self) {
+        if THEME.set(self).is_err() {
+            log::warn!("Theme already set, ignoring {}", self);
+        }
+    }
+
+    fn current() -> Self {
+        THEME.get().copied().unwrap_or_default()
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::Default => write!(f, "default"),
+            Theme::Mono => write!(f, "mono"),
+            Theme::Plain => write!(f, "plain"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Theme {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim().to_lowercase().as_str() {
+            "default" => Ok(Theme::Default),
+            "mono" => Ok(Theme::Mono),
+            "plain" => Ok(Theme::Plain),
+            _ => err!("invalid theme: {}", value),
+        }
+    }
+}
 
 /// Trait for displaying with styling.
 pub trait StyleDisplay: fmt::Display {
@@ -25,6 +82,20 @@ pub enum Color {
     Cyan,
 }
 
+impl Color {
+    /// Name of the color as used in e.g. CSS and Graphviz.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Color::Red => "red",
+            Color::Green => "green",
+            Color::Yellow => "yellow",
+            Color::Blue => "blue",
+            Color::Magenta => "magenta",
+            Color::Cyan => "cyan",
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct Styler {
     fg: Option<Color>,
@@ -48,12 +119,29 @@ impl Styler {
         self
     }
 
+    /// Returns the foreground color, if any.
+    pub fn color(&self) -> Option<&Color> {
+        self.fg.as_ref()
+    }
+
     pub fn style(&self, s: &str) -> String {
-        if !(self.bold || self.underline) && self.fg.is_none() {
+        self.style_as(Theme::current(), s)
+    }
+
+    fn style_as(&self, theme: Theme, s: &str) -> String {
+        let fg = match theme {
+            Theme::Default => self.fg.as_ref(),
+            Theme::Mono | Theme::Plain => None,
+        };
+        let (bold, underline) = match theme {
+            Theme::Plain => (false, false),
+            Theme::Default | Theme::Mono => (self.bold, self.underline),
+        };
+        if !(bold || underline) && fg.is_none() {
             return s.to_string();
         }
 
-        let c = if let Some(fg) = &self.fg {
+        let c = if let Some(fg) = fg {
             match fg {
                 Color::Red => s.red(),
                 Color::Green => s.green(),
@@ -62,12 +150,14 @@ impl Styler {
                 Color::Magenta => s.magenta(),
                 Color::Cyan => s.cyan(),
             }
-        } else {
+        } else if theme == Theme::Default {
             s.white()
+        } else {
+            s.stylize()
         };
 
-        let c = if self.bold { c.bold() } else { c };
-        let c = if self.underline { c.underlined() } else { c };
+        let c = if bold { c.bold() } else { c };
+        let c = if underline { c.underlined() } else { c };
         format!("{c}")
     }
 }
@@ -104,6 +194,15 @@ mod tests {
         assert!(s.len() > 5);
     }
 
+    #[test]
+    fn test_theme() {
+        let st = Styler::default().bold(true).fg(Color::Red);
+        assert_eq!(st.style_as(Theme::Plain, "red"), "red");
+        let mono = st.style_as(Theme::Mono, "red");
+        assert!(mono.len() > 3);
+        assert!(mono.len() < st.style_as(Theme::Default, "red").len());
+    }
+
     #[test]
     fn test_fg() {
         let st = Styler::default().fg(Color::Red);
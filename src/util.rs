@@ -1,4 +1,6 @@
+use crate::err;
 use crate::error::Result;
+use chrono::{Datelike, Days, Local, NaiveDate, Weekday};
 use rand::distributions::Alphanumeric;
 use rand::Rng;
 use std::fs;
@@ -14,10 +16,7 @@ pub fn random_string(size: usize) -> String {
 }
 
 pub fn try_get_env(name: &str) -> Option<String> {
-    match std::env::var(name) {
-        Ok(v) => Some(v),
-        Err(_) => None,
-    }
+    std::env::var(name).ok()
 }
 
 pub fn read_file(path: &Path) -> Result<String> {
@@ -36,6 +35,67 @@ pub fn write_file(path: &Path, content: &str) -> Result<()> {
     Ok(())
 }
 
+/// Parses a date from user input relative to today. See `parse_date_from`.
+pub fn parse_date(s: &str) -> Result<NaiveDate> {
+    parse_date_from(s, Local::now().date_naive())
+}
+
+/// Parses a date from user input relative to `today`. Supported values are:
+///   - an absolute date: `2022-03-02`
+///   - `today`, `tomorrow` and `yesterday`
+///   - a weekday, e.g. `fri` or `friday`, meaning the next such day
+///   - an offset in days or weeks: `3d`, `+2w` or `-1d`
+pub fn parse_date_from(s: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let value = s.trim().to_lowercase();
+    if let Ok(date) = NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+        return Ok(date);
+    }
+
+    let date = match value.as_str() {
+        "today" => Some(today),
+        "tomorrow" => today.checked_add_days(Days::new(1)),
+        "yesterday" => today.checked_sub_days(Days::new(1)),
+        v => match v.parse::<Weekday>() {
+            Ok(weekday) => {
+                let diff = weekday.num_days_from_monday() as i64
+                    - today.weekday().num_days_from_monday() as i64;
+                let days = if diff <= 0 { diff + 7 } else { diff };
+                today.checked_add_days(Days::new(days as u64))
+            }
+            Err(_) => parse_offset(v).and_then(|(days, past)| {
+                if past {
+                    today.checked_sub_days(days)
+                } else {
+                    today.checked_add_days(days)
+                }
+            }),
+        },
+    };
+
+    match date {
+        Some(date) => Ok(date),
+        None => err!("invalid date: {}", s),
+    }
+}
+
+// Parses offsets such as `3d`, `+2w` and `-1d` into days and
+// whether the offset is in the past.
+fn parse_offset(s: &str) -> Option<(Days, bool)> {
+    let (s, past) = match s.strip_prefix('-') {
+        Some(s) => (s, true),
+        None => (s.strip_prefix('+').unwrap_or(s), false),
+    };
+
+    let (n, factor) = match s.char_indices().last()? {
+        (i, 'd') => (&s[..i], 1),
+        (i, 'w') => (&s[..i], 7),
+        _ => return None,
+    };
+
+    let n = n.parse::<u64>().ok()?;
+    Some((Days::new(n.checked_mul(factor)?), past))
+}
+
 pub fn word_chunks(s: &str, size: usize) -> Vec<String> {
     let mut lines = Vec::new();
     for line in s.lines() {
@@ -98,6 +158,42 @@ fn line_chunks(s: &str, size: usize) -> Vec<String> {
     chunks
 }
 
+/// Gets the lowercase terms of a full-text search query, leaving out
+/// operators and column names. Prefix terms keep their trailing `*`.
+pub fn search_terms(query: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for word in query.split(|c: char| c.is_whitespace() || "\"()".contains(c)) {
+        if ["AND", "OR", "NOT", "NEAR"].contains(&word) {
+            continue;
+        }
+        let word = match word.split_once(':') {
+            Some((_, term)) => term,
+            None => word,
+        };
+        let prefix = word.ends_with('*');
+        for term in word.split(|c: char| !c.is_alphanumeric()) {
+            if term.is_empty() {
+                continue;
+            }
+            let mut term = term.to_lowercase();
+            if prefix && word.trim_end_matches('*').to_lowercase().ends_with(&term) {
+                term.push('*');
+            }
+            terms.push(term);
+        }
+    }
+    terms
+}
+
+/// Returns true if `word` matches one of the search terms.
+pub fn matches_term(word: &str, terms: &[String]) -> bool {
+    let word = word.to_lowercase();
+    terms.iter().any(|term| match term.strip_suffix('*') {
+        Some(prefix) => word.starts_with(prefix),
+        None => &word == term,
+    })
+}
+
 #[test]
 fn test_word_chunks() {
     let s = "one two three four"; // length == 18
@@ -107,3 +203,63 @@ fn test_word_chunks() {
     let b = word_chunks(s, 5);
     assert_eq!(b.len(), 4);
 }
+
+#[test]
+fn test_parse_date() {
+    // A wednesday
+    let today = NaiveDate::from_ymd_opt(2022, 3, 2).unwrap();
+    let cases = [
+        ("2022-04-01", NaiveDate::from_ymd_opt(2022, 4, 1)),
+        ("today", Some(today)),
+        ("Tomorrow", NaiveDate::from_ymd_opt(2022, 3, 3)),
+        ("yesterday", NaiveDate::from_ymd_opt(2022, 3, 1)),
+        ("fri", NaiveDate::from_ymd_opt(2022, 3, 4)),
+        ("wednesday", NaiveDate::from_ymd_opt(2022, 3, 9)),
+        ("3d", NaiveDate::from_ymd_opt(2022, 3, 5)),
+        ("+2w", NaiveDate::from_ymd_opt(2022, 3, 16)),
+        ("-1d", NaiveDate::from_ymd_opt(2022, 3, 1)),
+    ];
+    for (s, expected) in cases {
+        assert_eq!(parse_date_from(s, today).unwrap(), expected.unwrap());
+    }
+
+    for s in ["", "soon", "3", "d", "2022-13-01"] {
+        assert!(parse_date_from(s, today).is_err());
+    }
+}
+
+#[test]
+fn test_parse_date_overflow() {
+    let today = NaiveDate::from_ymd_opt(2022, 3, 2).unwrap();
+    for s in [
+        "99999999999999999w",
+        "-9999999999999999999w",
+        "9999999999999999999d",
+    ] {
+        assert!(parse_date_from(s, today).is_err(), "{}", s);
+    }
+    // Overflows u64 when converted to days
+    assert!(parse_offset("9999999999999999999w").is_none());
+}
+
+#[test]
+fn test_search_terms() {
+    let cases = [
+        ("deploy", vec!["deploy"]),
+        ("\"release notes\"", vec!["release", "notes"]),
+        ("dep* AND NOT test", vec!["dep*", "test"]),
+        ("subject:Fix OR (a-b)", vec!["fix", "a", "b"]),
+    ];
+    for (query, expected) in cases {
+        assert_eq!(search_terms(query), expected, "{}", query);
+    }
+}
+
+#[test]
+fn test_matches_term() {
+    let terms = search_terms("dep* fix");
+    assert!(matches_term("Deploy", &terms));
+    assert!(matches_term("fix", &terms));
+    assert!(!matches_term("fixed", &terms));
+    assert!(!matches_term("redeploy", &terms));
+}